@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+# Download the Vosk model and recording used by the Vosk engine tests
+# Run the tests with: cargo test --features vosk (libvosk must be installed)
+
+set -e
+
+FIXTURE_DIR="$(cd "$(dirname "$0")/.." && pwd)/speaky-tauri/src-tauri/tests/fixtures/vosk"
+MODEL="vosk-model-small-en-us-0.15"
+mkdir -p "$FIXTURE_DIR"
+cd "$FIXTURE_DIR"
+
+if [ ! -d "model" ]; then
+    echo "Downloading $MODEL..."
+    curl -fsSLO "https://alphacephei.com/vosk/models/$MODEL.zip"
+    unzip -q "$MODEL.zip"
+    mv "$MODEL" model
+    rm "$MODEL.zip"
+fi
+
+# 16 kHz mono recording of "one zero zero zero one nine oh two one oh zero one eight zero three"
+if [ ! -f "test.wav" ]; then
+    echo "Downloading test.wav..."
+    curl -fsSL -o test.wav "https://raw.githubusercontent.com/alphacep/vosk-api/master/python/example/test.wav"
+fi
+
+echo "Vosk fixtures are in $FIXTURE_DIR"
@@ -0,0 +1,148 @@
+//! Compression of recordings before they are uploaded to cloud engines.
+
+use log::{debug, warn};
+
+use crate::config::AudioCompression;
+
+/// Magic bytes every Ogg page starts with, used by engines to detect compressed uploads
+pub const OGG_MAGIC: &[u8] = b"OggS";
+
+/// Compress a WAV recording with `compression`, returning the WAV unchanged
+/// when the codec is unavailable
+pub fn compress(wav: Vec<u8>, compression: AudioCompression) -> Vec<u8> {
+    let result = match compression {
+        AudioCompression::None => return wav,
+        AudioCompression::Opus => encode_opus(&wav),
+        AudioCompression::Mp3 => Err("MP3 encoding is not supported".to_string()),
+    };
+
+    match result {
+        Ok(compressed) => {
+            debug!(
+                "Compressed audio with {:?}: {} -> {} bytes ({:.1}x)",
+                compression,
+                wav.len(),
+                compressed.len(),
+                wav.len() as f64 / compressed.len().max(1) as f64
+            );
+            compressed
+        }
+        Err(e) => {
+            warn!("Sending uncompressed audio: {}", e);
+            wav
+        }
+    }
+}
+
+#[cfg(feature = "opus")]
+fn encode_opus(wav: &[u8]) -> Result<Vec<u8>, String> {
+    use super::processing;
+
+    let samples = processing::wav_samples(wav).ok_or("Not a WAV recording")?;
+    let sample_rate = processing::parse_wav_sample_rate(wav).ok_or("Not a WAV recording")?;
+    if samples.is_empty() {
+        return Err("No audio to encode".to_string());
+    }
+    opus::encode_ogg_opus(&samples, sample_rate)
+}
+
+#[cfg(not(feature = "opus"))]
+fn encode_opus(_wav: &[u8]) -> Result<Vec<u8>, String> {
+    Err("Opus encoding requires the `opus` feature".to_string())
+}
+
+#[cfg(feature = "opus")]
+mod opus {
+    use audiopus::coder::Encoder;
+    use audiopus::{Application, Channels, SampleRate};
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+    use std::convert::TryFrom;
+
+    /// Opus frames are 20 ms
+    const FRAMES_PER_SECOND: u32 = 50;
+
+    /// Ogg granule positions always count 48 kHz samples
+    const GRANULE_RATE: u64 = 48_000;
+
+    const STREAM_SERIAL: u32 = 1;
+
+    /// Encode mono 16-bit samples as an Ogg/Opus file (RFC 7845)
+    pub fn encode_ogg_opus(samples: &[i16], sample_rate: u32) -> Result<Vec<u8>, String> {
+        let rate = SampleRate::try_from(sample_rate as i32)
+            .map_err(|_| format!("Opus does not support {} Hz", sample_rate))?;
+        let encoder = Encoder::new(rate, Channels::Mono, Application::Voip)
+            .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+        let lookahead = encoder.lookahead().unwrap_or(0);
+        let pre_skip = (lookahead as u64 * GRANULE_RATE / sample_rate as u64) as u16;
+
+        let frame_size = (sample_rate / FRAMES_PER_SECOND) as usize;
+        let granule_step = GRANULE_RATE / FRAMES_PER_SECOND as u64;
+
+        let mut writer = PacketWriter::new(Vec::new());
+        let head = opus_head(sample_rate, pre_skip);
+        write(&mut writer, head, PacketWriteEndInfo::EndPage, 0)?;
+        write(&mut writer, opus_tags(), PacketWriteEndInfo::EndPage, 0)?;
+
+        let frames = samples.chunks(frame_size).count();
+        // The last granule position marks where the audio ends, so the
+        // silence padding the final frame is dropped by decoders
+        let end_granule =
+            pre_skip as u64 + samples.len() as u64 * GRANULE_RATE / sample_rate as u64;
+        let mut granule = 0;
+        let mut frame = vec![0i16; frame_size];
+        let mut packet = vec![0u8; 4000];
+        for (i, chunk) in samples.chunks(frame_size).enumerate() {
+            // Pad the last frame with silence
+            frame.fill(0);
+            frame[..chunk.len()].copy_from_slice(chunk);
+
+            let len = encoder
+                .encode(&frame, &mut packet)
+                .map_err(|e| format!("Opus encoding failed: {}", e))?;
+            granule += granule_step;
+
+            let end = if i + 1 == frames {
+                granule = granule.min(end_granule);
+                PacketWriteEndInfo::EndStream
+            } else {
+                PacketWriteEndInfo::NormalPacket
+            };
+            write(&mut writer, packet[..len].to_vec(), end, granule)?;
+        }
+
+        Ok(writer.into_inner())
+    }
+
+    fn write(
+        writer: &mut PacketWriter<Vec<u8>>,
+        packet: Vec<u8>,
+        end: PacketWriteEndInfo,
+        granule: u64,
+    ) -> Result<(), String> {
+        writer
+            .write_packet(packet.into_boxed_slice(), STREAM_SERIAL, end, granule)
+            .map_err(|e| format!("Failed to write Ogg page: {}", e))
+    }
+
+    /// Identification header
+    fn opus_head(sample_rate: u32, pre_skip: u16) -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.push(1); // version
+        head.push(1); // channels
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+        head.push(0); // mono/stereo channel mapping
+        head
+    }
+
+    /// Comment header with only the vendor string
+    fn opus_tags() -> Vec<u8> {
+        let vendor = b"speaky";
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+        tags
+    }
+}
@@ -0,0 +1,138 @@
+//! Decoding of audio from outside the recorder (MP3, Ogg/Vorbis, non-PCM16 WAV)
+//! into the mono 16-bit WAV the engines expect.
+
+use log::{info, warn};
+use std::io::Cursor;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::compression::OGG_MAGIC;
+use super::processing::{encode_wav, verify_wav_integrity};
+
+/// Container format recognized by its magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    Ogg,
+}
+
+impl AudioFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Ogg => "ogg",
+        }
+    }
+}
+
+/// Detect the format of `data`, or None if it is not audio we can decode
+pub fn detect_audio_format(data: &[u8]) -> Option<AudioFormat> {
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE" {
+        Some(AudioFormat::Wav)
+    } else if data.starts_with(OGG_MAGIC) {
+        Some(AudioFormat::Ogg)
+    } else if data.starts_with(b"ID3")
+        || (data.len() >= 2 && data[0] == 0xFF && data[1] & 0xE0 == 0xE0)
+    {
+        // An ID3 tag, or the sync word of a bare MPEG audio frame
+        Some(AudioFormat::Mp3)
+    } else {
+        None
+    }
+}
+
+/// Convert WAV, MP3 or Ogg/Vorbis audio to mono 16-bit WAV at its own sample rate
+pub fn to_wav(data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let format = detect_audio_format(&data).ok_or("Unrecognized audio format")?;
+    if format == AudioFormat::Wav
+        && verify_wav_integrity(&data).is_ok_and(|info| info.channels == 1)
+    {
+        return Ok(data);
+    }
+
+    info!("Decoding {} bytes of {:?} audio", data.len(), format);
+    let stream = MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension(format.extension());
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("Failed to read {:?} audio: {}", format, e))?;
+    let mut reader = probed.format;
+
+    let track = reader.default_track().ok_or("The audio has no tracks")?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported codec: {}", e))?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(e) => return Err(format!("Failed to read audio: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                warn!("Skipping undecodable packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+        };
+        let spec = *decoded.spec();
+        sample_rate = Some(spec.rate);
+        let channels = spec.channels.count().max(1);
+        let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        // Average the channels down to mono
+        samples.extend(buffer.samples().chunks(channels).map(|frame| {
+            (frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32) as i16
+        }));
+    }
+
+    let sample_rate = sample_rate.ok_or("Unknown sample rate")?;
+    if samples.is_empty() {
+        return Err("The audio contains no samples".to_string());
+    }
+    Ok(encode_wav(&samples, sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_are_detected_by_magic_bytes() {
+        let wav = encode_wav(&[0; 16], 16000);
+        assert_eq!(detect_audio_format(&wav), Some(AudioFormat::Wav));
+        assert_eq!(detect_audio_format(b"OggS\0\x02"), Some(AudioFormat::Ogg));
+        assert_eq!(detect_audio_format(b"ID3\x04\0"), Some(AudioFormat::Mp3));
+        assert_eq!(
+            detect_audio_format(&[0xFF, 0xFB, 0x90]),
+            Some(AudioFormat::Mp3)
+        );
+        assert_eq!(detect_audio_format(b"hello"), None);
+        assert_eq!(to_wav(wav.clone()), Ok(wav));
+    }
+}
@@ -0,0 +1,247 @@
+//! Acoustic echo cancellation for setups where speaker audio reaches the microphone.
+//!
+//! On Linux a PulseAudio/PipeWire `module-echo-cancel` source is loaded with
+//! `pactl` and made the default source, which the ALSA pulse plugin opens for
+//! the default input device; the previous default is restored when the module
+//! is unloaded. While it is loaded, other applications recording from the
+//! default source get the cancelled audio too. This needs `pactl` and a
+//! PulseAudio-compatible server.
+//!
+//! On Windows cpal gives no access to WASAPI stream flags or processing
+//! modes, so the default communications device is captured through WASAPI
+//! directly, in the communications stream category. That selects the
+//! driver's communications processing mode, whose echo cancellation depends
+//! on the driver or the system effects installed for the device.
+//!
+//! Either way only the default input device is cancelled. macOS records
+//! without cancellation.
+
+#[cfg(target_os = "linux")]
+use log::{info, warn};
+
+/// Name of the echo-cancelled source created by `EchoCancelSource::load`
+#[cfg(target_os = "linux")]
+const SOURCE_NAME: &str = "speaky_echo_cancel";
+
+/// A loaded echo-cancel module and the default source it replaced; dropping
+/// it restores that source and unloads the module
+#[cfg(target_os = "linux")]
+pub struct EchoCancelSource {
+    module: u32,
+    previous_source: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+impl EchoCancelSource {
+    /// Load the echo-cancel module and make its source the default
+    pub fn load() -> Result<Self, String> {
+        let previous_source = pactl(&["get-default-source"])
+            .inspect_err(|e| warn!("Default source will not be restored: {}", e))
+            .ok();
+        let module = pactl(&[
+            "load-module",
+            "module-echo-cancel",
+            &format!("source_name={}", SOURCE_NAME),
+            "aec_method=webrtc",
+        ])?
+        .parse::<u32>()
+        .map_err(|e| format!("Unexpected pactl output: {}", e))?;
+
+        // Unloads the module again if the source cannot be selected
+        let source = Self {
+            module,
+            previous_source,
+        };
+        pactl(&["set-default-source", SOURCE_NAME])?;
+        info!("Loaded echo cancellation module {}", module);
+        Ok(source)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for EchoCancelSource {
+    fn drop(&mut self) {
+        if let Some(ref previous) = self.previous_source {
+            if let Err(e) = pactl(&["set-default-source", previous]) {
+                warn!("Failed to restore the default source: {}", e);
+            }
+        }
+        match pactl(&["unload-module", &self.module.to_string()]) {
+            Ok(_) => info!("Unloaded echo cancellation module {}", self.module),
+            Err(e) => warn!("{}", e),
+        }
+    }
+}
+
+/// Run `pactl` with `args`, returning its trimmed stdout
+#[cfg(target_os = "linux")]
+fn pactl(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("pactl")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run pactl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "pactl {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "windows")]
+pub use wasapi::CommunicationsCapture;
+
+#[cfg(target_os = "windows")]
+mod wasapi {
+    use log::error;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use windows::Win32::Media::Audio::{
+        eCapture, eCommunications, AudioCategory_Communications, AudioClientProperties,
+        IAudioCaptureClient, IAudioClient2, IMMDeviceEnumerator, MMDeviceEnumerator,
+        AUDCLNT_BUFFERFLAGS_SILENT, AUDCLNT_SHAREMODE_SHARED, AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM,
+        AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY, AUDCLNT_STREAMOPTIONS_NONE, WAVEFORMATEX,
+        WAVE_FORMAT_PCM,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    /// Shared-mode buffer length, in 100 ns units
+    const BUFFER_DURATION: i64 = 200 * 10_000;
+    /// How often captured packets are collected
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Capture from the default communications device with communications
+    /// processing, on a thread that runs until this is dropped
+    pub struct CommunicationsCapture {
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl CommunicationsCapture {
+        /// Start delivering mono 16-bit frames at `sample_rate` to `on_frames`
+        pub fn start(
+            sample_rate: u32,
+            mut on_frames: impl FnMut(&[i16]) + Send + 'static,
+        ) -> Result<Self, String> {
+            let stop = Arc::new(AtomicBool::new(false));
+            let (ready_tx, ready_rx) = mpsc::channel();
+            let thread = {
+                let stop = Arc::clone(&stop);
+                std::thread::spawn(move || unsafe {
+                    if let Err(e) = CoInitializeEx(None, COINIT_MULTITHREADED).ok() {
+                        let _ = ready_tx.send(Err(format!("Failed to initialize COM: {}", e)));
+                        return;
+                    }
+                    match open(sample_rate) {
+                        Ok((client, capture)) => {
+                            let _ = ready_tx.send(Ok(()));
+                            if let Err(e) = run(&capture, &stop, &mut on_frames) {
+                                error!("Echo-cancelled capture failed: {}", e);
+                            }
+                            let _ = client.Stop();
+                        }
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(format!(
+                                "Failed to open the communications device: {}",
+                                e
+                            )));
+                        }
+                    }
+                    CoUninitialize();
+                })
+            };
+
+            match ready_rx.recv() {
+                Ok(Ok(())) => Ok(Self {
+                    stop,
+                    thread: Some(thread),
+                }),
+                Ok(Err(e)) => {
+                    let _ = thread.join();
+                    Err(e)
+                }
+                Err(_) => Err("Echo-cancelled capture thread exited".to_string()),
+            }
+        }
+    }
+
+    impl Drop for CommunicationsCapture {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::SeqCst);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    /// Open and start a shared-mode stream on the default communications
+    /// device, converted by the audio engine to mono 16-bit PCM
+    unsafe fn open(
+        sample_rate: u32,
+    ) -> windows::core::Result<(IAudioClient2, IAudioCaptureClient)> {
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = enumerator.GetDefaultAudioEndpoint(eCapture, eCommunications)?;
+        let client: IAudioClient2 = device.Activate(CLSCTX_ALL, None)?;
+
+        // Must be set before Initialize to pick the communications processing mode
+        client.SetClientProperties(&AudioClientProperties {
+            cbSize: std::mem::size_of::<AudioClientProperties>() as u32,
+            bIsOffload: false.into(),
+            eCategory: AudioCategory_Communications,
+            Options: AUDCLNT_STREAMOPTIONS_NONE,
+        })?;
+
+        let format = WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_PCM as u16,
+            nChannels: 1,
+            nSamplesPerSec: sample_rate,
+            nAvgBytesPerSec: sample_rate * 2,
+            nBlockAlign: 2,
+            wBitsPerSample: 16,
+            cbSize: 0,
+        };
+        client.Initialize(
+            AUDCLNT_SHAREMODE_SHARED,
+            AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM | AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY,
+            BUFFER_DURATION,
+            0,
+            &format,
+            None,
+        )?;
+        let capture: IAudioCaptureClient = client.GetService()?;
+        client.Start()?;
+        Ok((client, capture))
+    }
+
+    /// Hand captured packets to `on_frames` until `stop` is set
+    unsafe fn run(
+        capture: &IAudioCaptureClient,
+        stop: &AtomicBool,
+        on_frames: &mut impl FnMut(&[i16]),
+    ) -> windows::core::Result<()> {
+        while !stop.load(Ordering::SeqCst) {
+            std::thread::sleep(POLL_INTERVAL);
+            while capture.GetNextPacketSize()? > 0 {
+                let mut data = std::ptr::null_mut();
+                let mut frames = 0;
+                let mut flags = 0;
+                capture.GetBuffer(&mut data, &mut frames, &mut flags, None, None)?;
+                let samples = if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 {
+                    vec![0; frames as usize]
+                } else {
+                    std::slice::from_raw_parts(data as *const i16, frames as usize).to_vec()
+                };
+                capture.ReleaseBuffer(frames)?;
+                on_frames(&samples);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,10 @@
+pub mod compression;
+pub mod decode;
+mod echo_cancel;
+pub mod notification;
+pub mod processing;
 mod recorder;
+pub mod tts;
+pub mod vad;
 
-pub use recorder::AudioRecorder;
+pub use recorder::{AudioDeviceInfo, AudioLevels, AudioRecorder, DeviceBenchmark, RecorderStats};
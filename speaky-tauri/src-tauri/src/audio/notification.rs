@@ -0,0 +1,82 @@
+use log::{debug, warn};
+use rodio::{Decoder, OutputStream, Sink};
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use crate::APP_STATE;
+
+/// Built-in chimes, matching the tones of the Python app
+const START_RECORDING_WAV: &[u8] = include_bytes!("../../sounds/start.wav");
+const STOP_RECORDING_WAV: &[u8] = include_bytes!("../../sounds/stop.wav");
+const ERROR_WAV: &[u8] = include_bytes!("../../sounds/error.wav");
+
+/// Events that play a sound when notifications are enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    StartRecording,
+    StopRecording,
+    Error,
+}
+
+impl NotificationKind {
+    /// Key used in `AppearanceConfig::notification_sounds`
+    pub fn key(self) -> &'static str {
+        match self {
+            NotificationKind::StartRecording => "start_recording",
+            NotificationKind::StopRecording => "stop_recording",
+            NotificationKind::Error => "error",
+        }
+    }
+
+    fn default_sound(self) -> &'static [u8] {
+        match self {
+            NotificationKind::StartRecording => START_RECORDING_WAV,
+            NotificationKind::StopRecording => STOP_RECORDING_WAV,
+            NotificationKind::Error => ERROR_WAV,
+        }
+    }
+}
+
+/// Play the sound for `kind` on a background thread, if sound notifications are enabled
+pub fn play_notification(kind: NotificationKind) {
+    let custom_path = {
+        let config = APP_STATE.config.read();
+        if !config.core.asr.sound_notification {
+            return;
+        }
+        config
+            .appearance
+            .notification_sounds
+            .as_ref()
+            .and_then(|sounds| sounds.get(kind.key()).cloned())
+    };
+
+    std::thread::spawn(move || {
+        let sound = match custom_path {
+            Some(path) => match std::fs::read(&path) {
+                Ok(data) => Cow::Owned(data),
+                Err(e) => {
+                    warn!("Failed to read notification sound {:?}: {}", path, e);
+                    Cow::Borrowed(kind.default_sound())
+                }
+            },
+            None => Cow::Borrowed(kind.default_sound()),
+        };
+
+        // Playback problems are never worth interrupting dictation for
+        if let Err(e) = play(sound) {
+            debug!("Failed to play {:?} notification: {}", kind, e);
+        }
+    });
+}
+
+/// Play WAV data to the default output device and wait for it to finish
+fn play(sound: Cow<'static, [u8]>) -> Result<(), String> {
+    let (_stream, handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+    let sink = Sink::try_new(&handle).map_err(|e| e.to_string())?;
+    let source = Decoder::new(Cursor::new(sound)).map_err(|e| e.to_string())?;
+
+    sink.append(source);
+    sink.sleep_until_end();
+    Ok(())
+}
@@ -0,0 +1,289 @@
+//! Offline processing of recorded audio: WAV encoding/parsing, silence trimming
+//! and mixing.
+
+/// Mono output, 16-bit samples
+const CHANNELS: u16 = 1;
+const SAMPLE_WIDTH: u16 = 2;
+
+/// Encode mono 16-bit samples as a WAV file
+pub fn encode_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let data_len = samples.len() * 2;
+    let file_len = 36 + data_len;
+
+    let mut buffer = Vec::with_capacity(44 + data_len);
+
+    // RIFF header
+    buffer.extend_from_slice(b"RIFF");
+    buffer.extend_from_slice(&(file_len as u32).to_le_bytes());
+    buffer.extend_from_slice(b"WAVE");
+
+    // fmt subchunk
+    buffer.extend_from_slice(b"fmt ");
+    buffer.extend_from_slice(&16u32.to_le_bytes()); // Subchunk1Size
+    buffer.extend_from_slice(&1u16.to_le_bytes()); // AudioFormat (PCM)
+    buffer.extend_from_slice(&CHANNELS.to_le_bytes()); // NumChannels
+    buffer.extend_from_slice(&sample_rate.to_le_bytes()); // SampleRate
+    let byte_rate = sample_rate * CHANNELS as u32 * SAMPLE_WIDTH as u32;
+    buffer.extend_from_slice(&byte_rate.to_le_bytes()); // ByteRate
+    let block_align = CHANNELS * SAMPLE_WIDTH;
+    buffer.extend_from_slice(&block_align.to_le_bytes()); // BlockAlign
+    let bits_per_sample = SAMPLE_WIDTH * 8;
+    buffer.extend_from_slice(&bits_per_sample.to_le_bytes()); // BitsPerSample
+
+    // data subchunk
+    buffer.extend_from_slice(b"data");
+    buffer.extend_from_slice(&(data_len as u32).to_le_bytes());
+
+    for sample in samples {
+        buffer.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    buffer
+}
+
+/// Get the sample rate from a WAV header, or None if the data is not a WAV file
+pub fn parse_wav_sample_rate(data: &[u8]) -> Option<u32> {
+    if data.len() < 28 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return None;
+    }
+    Some(u32::from_le_bytes([data[24], data[25], data[26], data[27]]))
+}
+
+/// Get the PCM body of a WAV file's "data" chunk
+pub fn wav_pcm_data(data: &[u8]) -> Option<&[u8]> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return None;
+    }
+
+    // Walk the chunks; the recorder writes a 44-byte header but files from
+    // other sources may carry extra chunks
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let chunk_id = &data[offset..offset + 4];
+        let chunk_len = u32::from_le_bytes([
+            data[offset + 4],
+            data[offset + 5],
+            data[offset + 6],
+            data[offset + 7],
+        ]) as usize;
+        let body_start = offset + 8;
+
+        if chunk_id == b"data" {
+            let body_end = (body_start + chunk_len).min(data.len());
+            return Some(&data[body_start..body_end]);
+        }
+
+        // Chunks are padded to an even length
+        offset = body_start + chunk_len + (chunk_len & 1);
+    }
+
+    None
+}
+
+/// Format of a WAV file that passed `verify_wav_integrity`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WavInfo {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    pub duration_secs: f64,
+}
+
+/// Check that a WAV file is 16-bit PCM with a `fmt ` chunk before a complete
+/// `data` chunk, so engines do not transcribe garbage from a broken file
+pub fn verify_wav_integrity(data: &[u8]) -> Result<WavInfo, String> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err("Audio is not a RIFF/WAVE file".to_string());
+    }
+
+    let u16_at = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let u32_at =
+        |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let chunk_id = &data[offset..offset + 4];
+        let chunk_len = u32_at(offset + 4) as usize;
+        let body_start = offset + 8;
+
+        if chunk_id == b"fmt " {
+            if chunk_len < 16 || body_start + 16 > data.len() {
+                return Err("WAV fmt chunk is truncated".to_string());
+            }
+            let audio_format = u16_at(body_start);
+            let channels = u16_at(body_start + 2);
+            let sample_rate = u32_at(body_start + 4);
+            let bits_per_sample = u16_at(body_start + 14);
+            if audio_format != 1 {
+                return Err(format!(
+                    "Unsupported WAV encoding {}, expected PCM",
+                    audio_format
+                ));
+            }
+            if bits_per_sample != 16 {
+                return Err(format!(
+                    "Unsupported WAV bit depth {}, expected 16",
+                    bits_per_sample
+                ));
+            }
+            if channels == 0 || sample_rate == 0 {
+                return Err("WAV fmt chunk reports no channels or sample rate".to_string());
+            }
+            format = Some((sample_rate, channels, bits_per_sample));
+        } else if chunk_id == b"data" {
+            let (sample_rate, channels, bits_per_sample) =
+                format.ok_or("WAV data chunk comes before the fmt chunk")?;
+            let actual = data.len() - body_start;
+            if actual < chunk_len {
+                return Err(format!(
+                    "WAV data chunk is truncated: header reports {} bytes, found {}",
+                    chunk_len, actual
+                ));
+            }
+            let bytes_per_sec = sample_rate as f64 * channels as f64 * (bits_per_sample / 8) as f64;
+            return Ok(WavInfo {
+                sample_rate,
+                channels,
+                bits_per_sample,
+                duration_secs: chunk_len as f64 / bytes_per_sec,
+            });
+        }
+
+        // Chunks are padded to an even length
+        offset = body_start + chunk_len + (chunk_len & 1);
+    }
+
+    Err("WAV file has no data chunk".to_string())
+}
+
+/// Decode the 16-bit PCM samples of a WAV file
+pub fn wav_samples(data: &[u8]) -> Option<Vec<i16>> {
+    let pcm = wav_pcm_data(data)?;
+    Some(
+        pcm.chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect(),
+    )
+}
+
+/// Drop leading and trailing runs of samples quieter than `threshold`,
+/// keeping at least `min_speech_ms` of audio even if it is all silent
+pub fn trim_silence(
+    samples: &[i16],
+    threshold: i16,
+    min_speech_ms: u32,
+    sample_rate: u32,
+) -> &[i16] {
+    let is_loud = |s: &i16| s.unsigned_abs() >= threshold.unsigned_abs();
+    let (mut start, mut end) = match samples.iter().position(is_loud) {
+        Some(first) => {
+            let last = samples.iter().rposition(is_loud).unwrap_or(first);
+            (first, last + 1)
+        }
+        None => (0, 0),
+    };
+
+    // Grow the kept range around the speech until it is long enough
+    let min_len = ((sample_rate as u64 * min_speech_ms as u64 / 1000) as usize).min(samples.len());
+    if end - start < min_len {
+        let missing = min_len - (end - start);
+        start = start.saturating_sub(missing / 2);
+        end = (start + min_len).min(samples.len());
+        start = end - min_len;
+    }
+
+    &samples[start..end]
+}
+
+/// Combines recordings of the same moment from several microphones
+pub struct AudioMixer;
+
+impl AudioMixer {
+    /// Average corresponding samples; the result is as long as the longest
+    /// stream, and the tail of it averages only the streams still running
+    pub fn mix(streams: &[Vec<i16>]) -> Vec<i16> {
+        let len = streams.iter().map(Vec::len).max().unwrap_or(0);
+        (0..len)
+            .map(|i| {
+                let (sum, count) = streams
+                    .iter()
+                    .filter_map(|stream| stream.get(i))
+                    .fold((0i64, 0i64), |(sum, count), &s| (sum + s as i64, count + 1));
+                (sum / count) as i16
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    #[test]
+    fn all_silent_input_keeps_minimum() {
+        let samples = vec![10i16; RATE as usize];
+        let trimmed = trim_silence(&samples, 500, 250, RATE);
+
+        assert_eq!(trimmed.len(), 4000);
+    }
+
+    #[test]
+    fn speech_is_trimmed_to_loud_region() {
+        let mut samples = vec![0i16; 8000];
+        samples.extend(vec![8000i16; 16000]);
+        samples.extend(vec![-20i16; 8000]);
+
+        let trimmed = trim_silence(&samples, 500, 250, RATE);
+
+        assert_eq!(trimmed.len(), 16000);
+        assert!(trimmed.iter().all(|&s| s == 8000));
+    }
+
+    #[test]
+    fn short_speech_is_padded_with_surrounding_audio() {
+        let mut samples = vec![0i16; 8000];
+        samples.extend(vec![8000i16; 800]);
+        samples.extend(vec![0i16; 8000]);
+
+        let trimmed = trim_silence(&samples, 500, 250, RATE);
+
+        assert_eq!(trimmed.len(), 4000);
+        assert_eq!(trimmed.iter().filter(|&&s| s == 8000).count(), 800);
+    }
+
+    #[test]
+    fn wav_round_trip() {
+        let samples = vec![1i16, -2, 300, -32768, 32767];
+        let wav = encode_wav(&samples, RATE);
+
+        assert_eq!(parse_wav_sample_rate(&wav), Some(RATE));
+        assert_eq!(wav_samples(&wav).unwrap(), samples);
+    }
+
+    #[test]
+    fn verifies_wav_format_and_length() {
+        let wav = encode_wav(&vec![0i16; 16000], RATE);
+        let info = verify_wav_integrity(&wav).unwrap();
+        assert_eq!(info.sample_rate, RATE);
+        assert_eq!(info.channels, 1);
+        assert_eq!(info.bits_per_sample, 16);
+        assert_eq!(info.duration_secs, 16000.0 / RATE as f64);
+
+        assert!(verify_wav_integrity(&wav[..wav.len() - 10]).is_err());
+        assert!(verify_wav_integrity(b"OggS").is_err());
+
+        let mut eight_bit = wav.clone();
+        eight_bit[34..36].copy_from_slice(&8u16.to_le_bytes());
+        assert!(verify_wav_integrity(&eight_bit).is_err());
+    }
+
+    #[test]
+    fn mixer_averages_and_keeps_longest_length() {
+        let mixed = AudioMixer::mix(&[vec![100, -100, 300], vec![300, 100]]);
+
+        assert_eq!(mixed, vec![200, 0, 300]);
+    }
+}
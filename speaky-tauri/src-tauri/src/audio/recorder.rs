@@ -2,58 +2,239 @@ use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, SampleRate, Stream, StreamConfig};
 use log::{error, info, warn};
 use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::processing::encode_wav;
+use crate::config::AsrConfig;
 
 /// Audio format constants
 const SAMPLE_RATE: u32 = 16000;
 const CHANNELS: u16 = 1;
-const SAMPLE_WIDTH: u16 = 2; // 16-bit
+
+/// Number of recent samples used for the live noise level
+const NOISE_WINDOW: usize = 512;
+/// Samples the peak meter looks back over
+const PEAK_WINDOW: usize = 512;
+
+/// How long `benchmark_devices` waits for a device's first frame
+const BENCHMARK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Stream latency of one input device
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceBenchmark {
+    pub index: u32,
+    pub name: String,
+    pub open_latency_ms: u64,
+    pub first_frame_latency_ms: u64,
+}
+
+/// Common sample rates reported in `AudioDeviceInfo`
+const COMMON_SAMPLE_RATES: &[u32] = &[8000, 16000, 22050, 44100, 48000];
+
+/// Formats an input device supports
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceInfo {
+    pub name: String,
+    /// Common rates within the supported ranges, ascending
+    pub supported_sample_rates: Vec<u32>,
+    pub max_channels: u16,
+    pub default_sample_rate: u32,
+}
+
+/// Chunks with a larger fraction of clipped samples are reported as clipping events
+pub const CLIP_EVENT_FRACTION: f32 = 0.05;
+
+/// Clipping in one chunk delivered by the input stream, after gain is applied
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkClipping {
+    /// Position of the chunk in the current recording
+    pub chunk_index: u64,
+    pub samples: usize,
+    pub clipped: usize,
+}
+
+impl ChunkClipping {
+    pub fn fraction(&self) -> f32 {
+        self.clipped as f32 / self.samples.max(1) as f32
+    }
+
+    pub fn is_clip_event(&self) -> bool {
+        self.fraction() > CLIP_EVENT_FRACTION
+    }
+}
+
+/// Clipping totals across all recordings since startup
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecorderStats {
+    pub chunks: u64,
+    pub samples: u64,
+    pub clipped_samples: u64,
+    /// Chunks above `CLIP_EVENT_FRACTION`
+    pub clip_events: u64,
+}
+
+impl RecorderStats {
+    pub fn record(&mut self, chunk: &ChunkClipping) {
+        self.chunks += 1;
+        self.samples += chunk.samples as u64;
+        self.clipped_samples += chunk.clipped as u64;
+        if chunk.is_clip_event() {
+            self.clip_events += 1;
+        }
+    }
+}
+
+/// RMS and peak input levels (0.0 - 1.0)
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct AudioLevels {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+impl AudioLevels {
+    fn of(samples: &[i16]) -> Self {
+        Self {
+            rms: rms(samples),
+            peak: peak(samples),
+        }
+    }
+}
+
+type AudioLevelCallback = Box<dyn Fn(f32, AudioLevels) + Send + Sync>;
+type AudioDataCallback = Box<dyn Fn(&[u8]) + Send + Sync>;
+type AudioClipCallback = Box<dyn Fn(&ChunkClipping) + Send + Sync>;
 
 /// Audio recorder using cpal for cross-platform support
 pub struct AudioRecorder {
     device: Option<Device>,
+    /// Input device index the recorder was created for, used to reopen it
+    device_index: Option<u32>,
     stream: Option<Stream>,
     frames: Arc<Mutex<Vec<i16>>>,
     is_recording: Arc<AtomicBool>,
+    is_paused: Arc<AtomicBool>,
     gain: f64,
-    audio_level_callback: Arc<Mutex<Option<Box<dyn Fn(f32) + Send + Sync>>>>,
-    audio_data_callback: Option<Box<dyn Fn(&[u8]) + Send + Sync>>,
+    noise_gate_threshold: f32,
+    /// Requested frames per callback; `None` leaves it to the host
+    buffer_size: Option<u32>,
+    echo_cancellation: bool,
+    /// Echo-cancelled source loaded for this recorder
+    #[cfg(target_os = "linux")]
+    echo_cancel_source: Option<super::echo_cancel::EchoCancelSource>,
+    /// Capture used instead of `stream` while recording with echo cancellation
+    #[cfg(target_os = "windows")]
+    echo_cancel_capture: Option<super::echo_cancel::CommunicationsCapture>,
+    recent_samples: Arc<Mutex<VecDeque<i16>>>,
+    audio_level_callback: Arc<Mutex<Option<AudioLevelCallback>>>,
+    audio_data_callback: Arc<Mutex<Option<AudioDataCallback>>>,
+    audio_clip_callback: Arc<Mutex<Option<AudioClipCallback>>>,
+    /// Set when the OS has denied this app access to the microphone
+    permission_error: Option<String>,
+}
+
+/// Look up the input device at `index`, or the default one
+fn open_device(index: Option<u32>) -> Option<Device> {
+    let host = cpal::default_host();
+
+    let device = if let Some(index) = index {
+        host.input_devices()
+            .ok()
+            .and_then(|mut devices| devices.nth(index as usize))
+    } else {
+        host.default_input_device()
+    };
+
+    if device.is_none() {
+        warn!("No audio input device found");
+    } else {
+        info!(
+            "Audio device: {:?}",
+            device.as_ref().and_then(|d| d.name().ok())
+        );
+    }
+    device
 }
 
 impl AudioRecorder {
     /// Create a new audio recorder
     pub fn new(device_index: Option<u32>, gain: f64) -> Self {
-        let host = cpal::default_host();
-
-        let device = if let Some(index) = device_index {
-            host.input_devices()
-                .ok()
-                .and_then(|mut devices| devices.nth(index as usize))
-        } else {
-            host.default_input_device()
-        };
+        let device = open_device(device_index);
 
-        if device.is_none() {
-            warn!("No audio input device found");
-        } else {
-            info!(
-                "Audio device: {:?}",
-                device.as_ref().and_then(|d| d.name().ok())
-            );
+        let permission_error = microphone_permission_error();
+        if let Some(ref e) = permission_error {
+            warn!("{}", e);
         }
 
         Self {
             device,
+            device_index,
             stream: None,
             frames: Arc::new(Mutex::new(Vec::new())),
             is_recording: Arc::new(AtomicBool::new(false)),
+            is_paused: Arc::new(AtomicBool::new(false)),
             gain: gain.clamp(0.1, 5.0),
+            noise_gate_threshold: 0.0,
+            buffer_size: None,
+            echo_cancellation: false,
+            #[cfg(target_os = "linux")]
+            echo_cancel_source: None,
+            #[cfg(target_os = "windows")]
+            echo_cancel_capture: None,
+            recent_samples: Arc::new(Mutex::new(VecDeque::with_capacity(NOISE_WINDOW))),
             audio_level_callback: Arc::new(Mutex::new(None)),
-            audio_data_callback: None,
+            audio_data_callback: Arc::new(Mutex::new(None)),
+            audio_clip_callback: Arc::new(Mutex::new(None)),
+            permission_error,
         }
     }
 
+    /// Create a recorder from the ASR configuration
+    pub fn from_config(config: &AsrConfig) -> Self {
+        let mut recorder = Self::new(config.audio_device, config.audio_gain);
+        recorder.set_noise_gate_threshold(config.noise_gate_threshold);
+        recorder.set_buffer_size(config.audio_buffer_size);
+        recorder.set_echo_cancellation(config.echo_cancellation);
+        recorder
+    }
+
+    /// Create one recorder per entry in `extra_audio_devices`, with the same settings
+    pub fn extras_from_config(config: &AsrConfig) -> Vec<Self> {
+        config
+            .extra_audio_devices
+            .iter()
+            .map(|&device| {
+                let mut recorder = Self::new(Some(device), config.audio_gain);
+                recorder.set_noise_gate_threshold(config.noise_gate_threshold);
+                recorder.set_buffer_size(config.audio_buffer_size);
+                recorder
+            })
+            .collect()
+    }
+
+    /// Whether an input device was found
+    pub fn has_device(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// Why the OS refuses microphone access, if it does
+    pub fn permission_error(&self) -> Option<&str> {
+        self.permission_error.as_deref()
+    }
+
+    /// Set the noise gate threshold (RMS, 0.0 - 1.0); quieter chunks are silenced
+    pub fn set_noise_gate_threshold(&mut self, threshold: f32) {
+        self.noise_gate_threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Set the frames per callback, or `None` for the host default
+    pub fn set_buffer_size(&mut self, frames: Option<u32>) {
+        self.buffer_size = frames;
+    }
+
     /// Get list of available input devices
     pub fn get_devices() -> Vec<(u32, String)> {
         let host = cpal::default_host();
@@ -69,10 +250,74 @@ impl AudioRecorder {
         devices
     }
 
-    /// Set the audio level callback
+    /// Describe the sample rates and channels the input device at `index` supports
+    pub fn device_info(index: u32) -> Result<AudioDeviceInfo, String> {
+        let device = cpal::default_host()
+            .input_devices()
+            .map_err(|e| format!("Failed to list input devices: {}", e))?
+            .nth(index as usize)
+            .ok_or_else(|| format!("No input device {}", index))?;
+
+        let configs: Vec<_> = device
+            .supported_input_configs()
+            .map_err(|e| format!("Failed to query input configs: {}", e))?
+            .collect();
+        let ranges: Vec<(u32, u32)> = configs
+            .iter()
+            .map(|config| (config.min_sample_rate().0, config.max_sample_rate().0))
+            .collect();
+        let default_sample_rate = device
+            .default_input_config()
+            .map(|config| config.sample_rate().0)
+            .map_err(|e| format!("Failed to query the default config: {}", e))?;
+
+        Ok(AudioDeviceInfo {
+            name: device
+                .name()
+                .unwrap_or_else(|_| format!("Device {}", index)),
+            supported_sample_rates: common_rates_within(&ranges),
+            max_channels: configs.iter().map(|c| c.channels()).max().unwrap_or(0),
+            default_sample_rate,
+        })
+    }
+
+    /// Open each input device in turn and measure how quickly it delivers audio
+    pub fn benchmark_devices() -> Vec<DeviceBenchmark> {
+        let host = cpal::default_host();
+        let mut results: Vec<DeviceBenchmark> = Self::get_devices()
+            .into_iter()
+            .filter_map(|(index, name)| {
+                let device = host.input_devices().ok()?.nth(index as usize)?;
+                match benchmark_device(&device) {
+                    Ok((open_latency_ms, first_frame_latency_ms)) => {
+                        info!(
+                            "Device {} ({}): open {} ms, first frame {} ms",
+                            index, name, open_latency_ms, first_frame_latency_ms
+                        );
+                        Some(DeviceBenchmark {
+                            index,
+                            name,
+                            open_latency_ms,
+                            first_frame_latency_ms,
+                        })
+                    }
+                    Err(e) => {
+                        warn!("Skipping device {} ({}): {}", index, name, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        results.sort_by_key(|result| result.first_frame_latency_ms);
+        results
+    }
+
+    /// Set the audio level callback, called per chunk with the mean level and
+    /// the chunk's RMS and peak
     pub fn set_audio_level_callback<F>(&mut self, callback: F)
     where
-        F: Fn(f32) + Send + Sync + 'static,
+        F: Fn(f32, AudioLevels) + Send + Sync + 'static,
     {
         *self.audio_level_callback.lock() = Some(Box::new(callback));
     }
@@ -82,7 +327,20 @@ impl AudioRecorder {
     where
         F: Fn(&[u8]) + Send + Sync + 'static,
     {
-        self.audio_data_callback = Some(Box::new(callback));
+        *self.audio_data_callback.lock() = Some(Box::new(callback));
+    }
+
+    /// Set the callback told how many samples of each chunk clipped
+    pub fn set_audio_clip_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&ChunkClipping) + Send + Sync + 'static,
+    {
+        *self.audio_clip_callback.lock() = Some(Box::new(callback));
+    }
+
+    /// Remove the audio data callback
+    pub fn clear_audio_data_callback(&mut self) {
+        *self.audio_data_callback.lock() = None;
     }
 
     /// Start recording
@@ -91,77 +349,184 @@ impl AudioRecorder {
             return Ok(());
         }
 
+        // cpal only reports an opaque stream error in this case
+        if let Some(ref e) = self.permission_error {
+            crate::APP_STATE.emit(
+                "permission-error",
+                serde_json::json!({
+                    "message": e
+                }),
+            );
+            return Err(e.clone());
+        }
+
+        #[cfg(target_os = "linux")]
+        if self.echo_cancellation && self.echo_cancel_source.is_none() {
+            self.load_echo_cancel();
+        }
+        #[cfg(target_os = "windows")]
+        if self.echo_cancellation && self.start_echo_cancel_capture() {
+            return Ok(());
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        if self.echo_cancellation {
+            warn!("Echo cancellation is not supported on this platform");
+        }
+        self.reacquire_device();
         let device = self
             .device
             .as_ref()
             .ok_or_else(|| "No audio device available".to_string())?;
 
+        // Many USB headsets and webcams only expose a stereo stream
+        let input_channels = match device.default_input_config() {
+            Ok(default_config) if default_config.channels() >= 2 => 2,
+            _ => CHANNELS,
+        };
+        info!("Input channels: {}", input_channels);
+
         let config = StreamConfig {
-            channels: CHANNELS,
+            channels: input_channels,
             sample_rate: SampleRate(SAMPLE_RATE),
-            buffer_size: cpal::BufferSize::Default,
+            buffer_size: buffer_size(device, self.buffer_size),
         };
 
         // Clear previous frames
         self.frames.lock().clear();
+        self.is_paused.store(false, Ordering::SeqCst);
         self.is_recording.store(true, Ordering::SeqCst);
 
+        // Some cheap microphones reject 16 kHz; fall back to a rate they support
+        let (stream, config) = match self.build_stream(device, &config) {
+            Ok(stream) => (stream, config),
+            Err(e) => {
+                warn!("Device rejected {} Hz: {}", SAMPLE_RATE, e);
+                let config = negotiate_config(device).inspect_err(|_| {
+                    self.is_recording.store(false, Ordering::SeqCst);
+                })?;
+                let stream = self.build_stream(device, &config).inspect_err(|_| {
+                    self.is_recording.store(false, Ordering::SeqCst);
+                })?;
+                (stream, config)
+            }
+        };
+        info!(
+            "Device sample rate: {} Hz, {} channel(s)",
+            config.sample_rate.0, config.channels
+        );
+
+        stream.play().map_err(|e| format!("Failed to play stream: {}", e))?;
+        self.stream = Some(stream);
+
+        info!("Recording started");
+        Ok(())
+    }
+
+    /// Build an input stream that delivers 16 kHz mono frames regardless of the device format
+    fn build_stream(&self, device: &Device, config: &StreamConfig) -> Result<Stream, String> {
+        let mut handle_input = self.input_handler(config.channels, config.sample_rate.0);
+        device
+            .build_input_stream(
+                config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| handle_input(data),
+                move |err| {
+                    error!("Audio stream error: {:?}", err);
+                },
+                None,
+            )
+            .map_err(|e| format!("Failed to build stream: {}", e))
+    }
+
+    /// Process captured frames in the input format: convert them to 16 kHz
+    /// mono, apply gain and the noise gate, report levels and store them
+    fn input_handler(
+        &self,
+        input_channels: u16,
+        input_rate: u32,
+    ) -> impl FnMut(&[i16]) + Send + 'static {
         let frames = Arc::clone(&self.frames);
         let is_recording = Arc::clone(&self.is_recording);
+        let is_paused = Arc::clone(&self.is_paused);
+        let recent_samples = Arc::clone(&self.recent_samples);
         let audio_level_callback = Arc::clone(&self.audio_level_callback);
+        let audio_data_callback = Arc::clone(&self.audio_data_callback);
+        let audio_clip_callback = Arc::clone(&self.audio_clip_callback);
         let gain = self.gain;
+        let noise_gate_threshold = self.noise_gate_threshold;
+        let mut chunk_index = 0;
 
-        // Build stream with i16 samples
-        let stream = device
-            .build_input_stream(
-                &config,
-                move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    if !is_recording.load(Ordering::SeqCst) {
-                        return;
-                    }
+        move |data: &[i16]| {
+            if !is_recording.load(Ordering::SeqCst) {
+                return;
+            }
 
-                    // Apply gain
-                    let processed: Vec<i16> = data
-                        .iter()
-                        .map(|&s| {
-                            let sample = (s as f64 * gain) as i32;
-                            sample.clamp(-32768, 32767) as i16
-                        })
-                        .collect();
+            // Downmix to mono and resample to 16 kHz, then apply gain
+            let mono = downmix_to_mono(data, input_channels);
+            let mut processed: Vec<i16> = resample_linear(&mono, input_rate, SAMPLE_RATE)
+                .into_iter()
+                .map(|s| {
+                    let sample = (s as f64 * gain) as i32;
+                    sample.clamp(-32768, 32767) as i16
+                })
+                .collect();
 
-                    // Calculate audio level from current chunk
-                    let sum: i64 = processed.iter().map(|&s| (s as i64).abs()).sum();
-                    let avg = sum as f32 / processed.len().max(1) as f32;
-                    let level = (avg / 32768.0).min(1.0);
+            if let Some(ref callback) = *audio_clip_callback.lock() {
+                callback(&ChunkClipping {
+                    chunk_index,
+                    samples: processed.len(),
+                    clipped: count_clipped(&processed),
+                });
+            }
+            chunk_index += 1;
 
-                    // Emit audio level callback
-                    if let Some(ref callback) = *audio_level_callback.lock() {
-                        callback(level);
-                    }
+            // Keep a window of ungated samples for the live noise indicator
+            {
+                let mut recent = recent_samples.lock();
+                recent.extend(processed.iter().copied());
+                let excess = recent.len().saturating_sub(NOISE_WINDOW);
+                recent.drain(..excess);
+            }
 
-                    frames.lock().extend_from_slice(&processed);
-                },
-                move |err| {
-                    error!("Audio stream error: {:?}", err);
-                },
-                None,
-            )
-            .map_err(|e| format!("Failed to build stream: {}", e))?;
+            // Noise gate: silence chunks below the threshold
+            if rms(&processed) < noise_gate_threshold {
+                processed.fill(0);
+            }
 
-        stream.play().map_err(|e| format!("Failed to play stream: {}", e))?;
-        self.stream = Some(stream);
+            // Calculate audio level from current chunk
+            let sum: i64 = processed.iter().map(|&s| (s as i64).abs()).sum();
+            let avg = sum as f32 / processed.len().max(1) as f32;
+            let level = (avg / 32768.0).min(1.0);
 
-        info!("Recording started");
-        Ok(())
+            // Emit audio level callback
+            if let Some(ref callback) = *audio_level_callback.lock() {
+                callback(level, AudioLevels::of(&processed));
+            }
+
+            // Keep the stream open but drop audio captured while paused
+            if is_paused.load(Ordering::SeqCst) {
+                return;
+            }
+
+            // Forward 16 kHz little-endian PCM to the streaming consumer
+            if let Some(ref callback) = *audio_data_callback.lock() {
+                let pcm: Vec<u8> = processed.iter().flat_map(|s| s.to_le_bytes()).collect();
+                callback(&pcm);
+            }
+
+            frames.lock().extend_from_slice(&processed);
+        }
     }
 
     /// Stop recording and return WAV data
     pub fn stop(&mut self) -> Vec<u8> {
         self.is_recording.store(false, Ordering::SeqCst);
+        self.is_paused.store(false, Ordering::SeqCst);
 
         if let Some(stream) = self.stream.take() {
             drop(stream);
         }
+        #[cfg(target_os = "windows")]
+        self.echo_cancel_capture.take();
 
         let frames = self.frames.lock().clone();
         if frames.is_empty() {
@@ -169,7 +534,7 @@ impl AudioRecorder {
             return Vec::new();
         }
 
-        let wav_data = self.create_wav(&frames);
+        let wav_data = encode_wav(&frames, SAMPLE_RATE);
         info!(
             "Recording stopped, {} frames, {} bytes WAV",
             frames.len(),
@@ -178,11 +543,130 @@ impl AudioRecorder {
         wav_data
     }
 
+    /// Route the input through an echo-cancelled source; see `echo_cancel`
+    pub fn set_echo_cancellation(&mut self, enabled: bool) {
+        self.echo_cancellation = enabled;
+    }
+
+    #[cfg(target_os = "linux")]
+    fn load_echo_cancel(&mut self) {
+        if self.device_index.is_some() {
+            warn!("Echo cancellation only applies to the default input device");
+            return;
+        }
+        match super::echo_cancel::EchoCancelSource::load() {
+            Ok(source) => self.echo_cancel_source = Some(source),
+            Err(e) => warn!("Echo cancellation unavailable: {}", e),
+        }
+    }
+
+    /// Record from the communications device instead of through cpal; false
+    /// when that is not possible and the normal stream should be used
+    #[cfg(target_os = "windows")]
+    fn start_echo_cancel_capture(&mut self) -> bool {
+        if self.device_index.is_some() {
+            warn!("Echo cancellation only applies to the default input device");
+            return false;
+        }
+
+        self.frames.lock().clear();
+        self.is_paused.store(false, Ordering::SeqCst);
+        self.is_recording.store(true, Ordering::SeqCst);
+        let handle_input = self.input_handler(CHANNELS, SAMPLE_RATE);
+        match super::echo_cancel::CommunicationsCapture::start(SAMPLE_RATE, handle_input) {
+            Ok(capture) => {
+                self.echo_cancel_capture = Some(capture);
+                info!("Recording started with echo cancellation");
+                true
+            }
+            Err(e) => {
+                self.is_recording.store(false, Ordering::SeqCst);
+                warn!("Echo cancellation unavailable: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Unload the echo-cancelled source, restoring the default source. `Drop`
+    /// does this too, but the recorder in `APP_STATE` is never dropped
+    pub fn unload_echo_cancel(&mut self) {
+        #[cfg(target_os = "linux")]
+        self.echo_cancel_source.take();
+    }
+
+    /// Drop the input device while idle.
+    ///
+    /// The stream, which is what keeps capturing, already closes in `stop`.
+    /// On ALSA the cpal device additionally keeps the PCM handles it opened
+    /// while probing formats, and enumerated devices keep one per direction,
+    /// which leaves `hw:` devices busy for other applications until it is
+    /// dropped. On WASAPI it caches an uninitialized audio client and on
+    /// CoreAudio only an ID, so there this frees next to nothing.
+    pub fn release_device(&mut self) {
+        if self.is_recording() || self.device.is_none() {
+            return;
+        }
+        self.device = None;
+        info!("Audio device released");
+    }
+
+    /// Reopen the input device dropped by `release_device`
+    pub fn reacquire_device(&mut self) {
+        if self.device.is_none() {
+            self.device = open_device(self.device_index);
+        }
+    }
+
     /// Check if currently recording
     pub fn is_recording(&self) -> bool {
         self.is_recording.load(Ordering::SeqCst)
     }
 
+    /// Stop accumulating audio without closing the stream
+    pub fn pause(&self) -> Result<(), String> {
+        if !self.is_recording() {
+            return Err("Not recording".to_string());
+        }
+        self.is_paused.store(true, Ordering::SeqCst);
+        info!("Recording paused");
+        Ok(())
+    }
+
+    /// Continue accumulating audio after `pause`
+    pub fn resume(&self) -> Result<(), String> {
+        if !self.is_recording() {
+            return Err("Not recording".to_string());
+        }
+        self.is_paused.store(false, Ordering::SeqCst);
+        info!("Recording resumed");
+        Ok(())
+    }
+
+    /// Check if recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::SeqCst)
+    }
+
+    /// Get duration of the captured audio in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.frames.lock().len() as u64 * 1000 / SAMPLE_RATE as u64
+    }
+
+    /// Get current gain multiplier
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    /// Get the peak absolute amplitude of the captured audio
+    pub fn peak_amplitude(&self) -> u16 {
+        self.frames
+            .lock()
+            .iter()
+            .map(|&s| s.unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+
     /// Get current audio level (0.0 - 1.0)
     pub fn get_audio_level(&self) -> f32 {
         let frames = self.frames.lock();
@@ -199,6 +683,36 @@ impl AudioRecorder {
         (avg / 32768.0).min(1.0)
     }
 
+    /// Get the peak (0.0 - 1.0) of the last 512 captured samples
+    pub fn get_peak_level(&self) -> f32 {
+        let frames = self.frames.lock();
+        peak(&frames[frames.len().saturating_sub(PEAK_WINDOW)..])
+    }
+
+    /// Get RMS and peak (0.0 - 1.0) of the last 512 captured samples
+    pub fn get_levels(&self) -> AudioLevels {
+        let rms = {
+            let frames = self.frames.lock();
+            rms(&frames[frames.len().saturating_sub(PEAK_WINDOW)..])
+        };
+        AudioLevels {
+            rms,
+            peak: self.get_peak_level(),
+        }
+    }
+
+    /// Get RMS (0.0 - 1.0) of the captured audio split into `n_points` equal groups
+    pub fn waveform(&self, n_points: usize) -> Vec<f32> {
+        waveform(&self.frames.lock(), n_points)
+    }
+
+    /// Get RMS (0.0 - 1.0) of the most recent ungated samples
+    pub fn get_noise_level(&self) -> f32 {
+        let recent = self.recent_samples.lock();
+        let samples: Vec<i16> = recent.iter().copied().collect();
+        rms(&samples)
+    }
+
     /// Get raw PCM data (for streaming)
     pub fn get_pcm_data(&self) -> Vec<u8> {
         let frames = self.frames.lock();
@@ -209,40 +723,14 @@ impl AudioRecorder {
         data
     }
 
-    /// Create WAV file from samples
-    fn create_wav(&self, samples: &[i16]) -> Vec<u8> {
-        let data_len = samples.len() * 2;
-        let file_len = 36 + data_len;
-
-        let mut buffer = Vec::with_capacity(44 + data_len);
-
-        // RIFF header
-        buffer.extend_from_slice(b"RIFF");
-        buffer.extend_from_slice(&(file_len as u32).to_le_bytes());
-        buffer.extend_from_slice(b"WAVE");
-
-        // fmt subchunk
-        buffer.extend_from_slice(b"fmt ");
-        buffer.extend_from_slice(&16u32.to_le_bytes()); // Subchunk1Size
-        buffer.extend_from_slice(&1u16.to_le_bytes()); // AudioFormat (PCM)
-        buffer.extend_from_slice(&CHANNELS.to_le_bytes()); // NumChannels
-        buffer.extend_from_slice(&SAMPLE_RATE.to_le_bytes()); // SampleRate
-        let byte_rate = SAMPLE_RATE * CHANNELS as u32 * SAMPLE_WIDTH as u32;
-        buffer.extend_from_slice(&byte_rate.to_le_bytes()); // ByteRate
-        let block_align = CHANNELS * SAMPLE_WIDTH;
-        buffer.extend_from_slice(&block_align.to_le_bytes()); // BlockAlign
-        let bits_per_sample = SAMPLE_WIDTH * 8;
-        buffer.extend_from_slice(&bits_per_sample.to_le_bytes()); // BitsPerSample
-
-        // data subchunk
-        buffer.extend_from_slice(b"data");
-        buffer.extend_from_slice(&(data_len as u32).to_le_bytes());
-
-        for sample in samples {
-            buffer.extend_from_slice(&sample.to_le_bytes());
+    /// Take the frames captured so far, leaving the recording running, and
+    /// return them as WAV; empty when nothing new was captured
+    pub fn drain_wav(&self) -> Vec<u8> {
+        let frames = std::mem::take(&mut *self.frames.lock());
+        if frames.is_empty() {
+            return Vec::new();
         }
-
-        buffer
+        encode_wav(&frames, SAMPLE_RATE)
     }
 }
 
@@ -250,9 +738,316 @@ impl Drop for AudioRecorder {
     fn drop(&mut self) {
         self.is_recording.store(false, Ordering::SeqCst);
         self.stream.take();
+        #[cfg(target_os = "windows")]
+        self.echo_cancel_capture.take();
+        self.unload_echo_cancel();
     }
 }
 
 // AudioRecorder is Send + Sync because all its fields are thread-safe
 unsafe impl Send for AudioRecorder {}
 unsafe impl Sync for AudioRecorder {}
+
+/// Time to open a stream and to receive the first audio from a device, in milliseconds
+fn benchmark_device(device: &Device) -> Result<(u64, u64), String> {
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to query default config: {}", e))?;
+    let (first_frame_tx, first_frame_rx) = std::sync::mpsc::sync_channel(1);
+
+    let started = Instant::now();
+    let stream = device
+        .build_input_stream_raw(
+            &supported.config(),
+            supported.sample_format(),
+            move |_: &cpal::Data, _: &cpal::InputCallbackInfo| {
+                let _ = first_frame_tx.try_send(());
+            },
+            move |err| {
+                error!("Audio stream error: {:?}", err);
+            },
+            None,
+        )
+        .map_err(|e| format!("Failed to build stream: {}", e))?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to play stream: {}", e))?;
+    let open_latency_ms = started.elapsed().as_millis() as u64;
+
+    first_frame_rx
+        .recv_timeout(BENCHMARK_TIMEOUT)
+        .map_err(|_| "No audio within the timeout".to_string())?;
+    let first_frame_latency_ms = started.elapsed().as_millis() as u64;
+
+    drop(stream);
+    Ok((open_latency_ms, first_frame_latency_ms))
+}
+
+/// Root mean square of a chunk, normalized to 0.0 - 1.0
+fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f64 = samples
+        .iter()
+        .map(|&s| {
+            let v = s as f64 / 32768.0;
+            v * v
+        })
+        .sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+/// Number of samples at the i16 extremes
+fn count_clipped(samples: &[i16]) -> usize {
+    samples
+        .iter()
+        .filter(|&&s| s == i16::MAX || s == i16::MIN)
+        .count()
+}
+
+/// Largest absolute sample, normalised to 0.0 - 1.0
+fn peak(samples: &[i16]) -> f32 {
+    let max = samples.iter().map(|&s| s.unsigned_abs()).max().unwrap_or(0);
+    (max as f32 / 32768.0).min(1.0)
+}
+
+/// Down-sample `samples` into `n_points` RMS values; groups past the end are zero
+fn waveform(samples: &[i16], n_points: usize) -> Vec<f32> {
+    (0..n_points)
+        .map(|i| {
+            let start = i * samples.len() / n_points;
+            let end = (i + 1) * samples.len() / n_points;
+            rms(&samples[start..end])
+        })
+        .collect()
+}
+
+/// Ask the OS whether the app may use the microphone
+#[cfg(target_os = "macos")]
+fn microphone_permission_error() -> Option<String> {
+    use cocoa::base::id;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: id;
+    }
+
+    // AVAuthorizationStatus: 0 not determined, 1 restricted, 2 denied, 3 authorized
+    let status: isize = unsafe {
+        msg_send![
+            class!(AVCaptureDevice),
+            authorizationStatusForMediaType: AVMediaTypeAudio
+        ]
+    };
+    match status {
+        1 => Some("Microphone access is restricted on this Mac".to_string()),
+        2 => Some(
+            "Microphone access was denied; allow Speaky in System Settings > Privacy & Security > Microphone"
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+/// Ask the OS whether the app may use the microphone
+#[cfg(target_os = "windows")]
+fn microphone_permission_error() -> Option<String> {
+    use windows::core::HSTRING;
+    use windows::Security::Authorization::AppCapabilityAccess::{
+        AppCapability, AppCapabilityAccessStatus,
+    };
+
+    let status = AppCapability::Create(&HSTRING::from("microphone"))
+        .and_then(|capability| capability.CheckAccess());
+    match status {
+        Ok(AppCapabilityAccessStatus::DeniedByUser) => Some(
+            "Microphone access was denied; allow desktop apps in Settings > Privacy & security > Microphone"
+                .to_string(),
+        ),
+        Ok(AppCapabilityAccessStatus::DeniedBySystem) => {
+            Some("Microphone access is blocked by system policy".to_string())
+        }
+        Ok(_) => None,
+        Err(e) => {
+            // Older Windows 10 builds lack the API; let cpal report any failure
+            warn!("Failed to check microphone access: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn microphone_permission_error() -> Option<String> {
+    None
+}
+
+/// Use the requested buffer size when the device supports it
+fn buffer_size(device: &Device, requested: Option<u32>) -> cpal::BufferSize {
+    let Some(frames) = requested else {
+        return cpal::BufferSize::Default;
+    };
+
+    let supported = device.supported_input_configs().is_ok_and(|mut ranges| {
+        ranges.any(|range| match *range.buffer_size() {
+            cpal::SupportedBufferSize::Range { min, max } => (min..=max).contains(&frames),
+            cpal::SupportedBufferSize::Unknown => false,
+        })
+    });
+    if supported {
+        info!("Buffer size: {} frames", frames);
+        cpal::BufferSize::Fixed(frames)
+    } else {
+        warn!(
+            "Device does not support a buffer of {} frames, using the default",
+            frames
+        );
+        cpal::BufferSize::Default
+    }
+}
+
+/// The common sample rates that fall within any of the `(min, max)` ranges
+fn common_rates_within(ranges: &[(u32, u32)]) -> Vec<u32> {
+    COMMON_SAMPLE_RATES
+        .iter()
+        .copied()
+        .filter(|rate| ranges.iter().any(|&(min, max)| (min..=max).contains(rate)))
+        .collect()
+}
+
+/// Pick the supported input config whose sample rate is closest to 16 kHz
+fn negotiate_config(device: &Device) -> Result<StreamConfig, String> {
+    let ranges = device
+        .supported_input_configs()
+        .map_err(|e| format!("Failed to query input configs: {}", e))?;
+
+    let distance = |rate: u32| rate.abs_diff(SAMPLE_RATE);
+    let best = ranges
+        .map(|range| {
+            let rate = SAMPLE_RATE.clamp(range.min_sample_rate().0, range.max_sample_rate().0);
+            (range.channels(), rate)
+        })
+        .min_by_key(|&(channels, rate)| (distance(rate), channels))
+        .ok_or_else(|| "Device reports no supported input configs".to_string())?;
+
+    Ok(StreamConfig {
+        channels: best.0,
+        sample_rate: SampleRate(best.1),
+        buffer_size: cpal::BufferSize::Default,
+    })
+}
+
+/// Resample with linear interpolation between neighbouring samples
+fn resample_linear(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let out_len = (samples.len() as u64 * to_rate as u64 / from_rate as u64) as usize;
+    let step = from_rate as f64 / to_rate as f64;
+
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let index = pos as usize;
+            let frac = pos - index as f64;
+            let a = samples[index] as f64;
+            let b = *samples.get(index + 1).unwrap_or(&samples[index]) as f64;
+            (a + (b - a) * frac).round() as i16
+        })
+        .collect()
+}
+
+/// Average interleaved channels into a single mono channel
+fn downmix_to_mono(data: &[i16], channels: u16) -> Vec<i16> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+
+    data.chunks_exact(channels as usize)
+        .map(|frame| {
+            let sum: i32 = frame.iter().map(|&s| s as i32).sum();
+            (sum / channels as i32) as i16
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downmix_stereo_halves_length() {
+        let stereo: Vec<i16> = vec![100, 300, -200, -400, 32767, 32767, -32768, -32768];
+        let mono = downmix_to_mono(&stereo, 2);
+
+        assert_eq!(mono.len(), stereo.len() / 2);
+        assert_eq!(mono, vec![200, -300, 32767, -32768]);
+    }
+
+    #[test]
+    fn downmix_mono_is_passthrough() {
+        let samples: Vec<i16> = vec![1, 2, 3];
+        assert_eq!(downmix_to_mono(&samples, 1), samples);
+    }
+
+    #[test]
+    fn waveform_groups_samples() {
+        let mut samples = vec![0i16; 100];
+        samples.extend(vec![16384i16; 100]);
+        let points = waveform(&samples, 4);
+
+        assert_eq!(points.len(), 4);
+        assert_eq!(&points[..2], &[0.0, 0.0]);
+        assert!(points[2..].iter().all(|&p| (p - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn common_rates_are_expanded_from_ranges() {
+        assert_eq!(
+            common_rates_within(&[(44100, 48000), (8000, 8000)]),
+            vec![8000, 44100, 48000]
+        );
+        assert_eq!(
+            common_rates_within(&[(4000, 192000)]),
+            COMMON_SAMPLE_RATES.to_vec()
+        );
+        assert!(common_rates_within(&[]).is_empty());
+    }
+
+    #[test]
+    fn peak_is_largest_absolute_sample() {
+        assert_eq!(peak(&[]), 0.0);
+        assert_eq!(peak(&[100, -16384, 8192]), 0.5);
+        assert_eq!(peak(&[i16::MIN]), 1.0);
+    }
+
+    #[test]
+    fn waveform_of_empty_recording_is_zero() {
+        assert_eq!(waveform(&[], 3), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn clipping_above_five_percent_is_an_event() {
+        let mut samples = vec![1000i16; 95];
+        samples.extend([i16::MAX, i16::MIN, i16::MAX, i16::MIN, 32766]);
+        let chunk = ChunkClipping {
+            chunk_index: 0,
+            samples: samples.len(),
+            clipped: count_clipped(&samples),
+        };
+        assert_eq!(chunk.clipped, 4);
+        assert!(!chunk.is_clip_event());
+
+        let chunk = ChunkClipping {
+            clipped: 6,
+            ..chunk
+        };
+        let mut stats = RecorderStats::default();
+        stats.record(&chunk);
+        assert_eq!(stats.clipped_samples, 6);
+        assert_eq!(stats.clip_events, 1);
+    }
+}
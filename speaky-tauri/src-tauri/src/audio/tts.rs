@@ -0,0 +1,275 @@
+//! Reading recognized text back aloud. Every engine produces WAV, played
+//! through rodio so `stop_tts` can cut any of them short.
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rodio::{Decoder, OutputStream, Sink};
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::processing::encode_wav;
+use crate::config::{TtsConfig, TtsEngine};
+use crate::APP_STATE;
+
+const ELEVENLABS_URL: &str = "https://api.elevenlabs.io/v1/text-to-speech";
+/// Rate of the raw PCM requested from ElevenLabs
+const ELEVENLABS_SAMPLE_RATE: u32 = 16000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sink of the speech being played, so it can be stopped
+static CURRENT_SINK: Lazy<Mutex<Option<Arc<Sink>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Speak `text` with the configured engine; returns once playback ends or is stopped
+pub fn text_to_speech(text: &str, cfg: &TtsConfig) -> Result<(), String> {
+    info!(
+        "Speaking {} chars with {:?}",
+        text.chars().count(),
+        cfg.engine
+    );
+    let wav = match cfg.engine {
+        TtsEngine::SystemTts => system_tts(text, &cfg.voice)?,
+        TtsEngine::ElevenLabs => elevenlabs(text, cfg)?,
+        TtsEngine::Coqui => coqui(text, cfg)?,
+    };
+    play(wav)
+}
+
+/// Read a recognition result back on a background thread, if TTS is configured
+pub fn speak_result(text: &str) {
+    let Some(cfg) = APP_STATE.config.read().appearance.tts.clone() else {
+        return;
+    };
+    let text = text.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = text_to_speech(&text, &cfg) {
+            warn!("Failed to read the result back: {}", e);
+        }
+    });
+}
+
+/// Stop the speech being played, if any
+pub fn stop_tts() {
+    if let Some(sink) = CURRENT_SINK.lock().take() {
+        sink.stop();
+    }
+}
+
+/// Play WAV data, replacing any speech still playing, and wait for it to finish
+fn play(wav: Vec<u8>) -> Result<(), String> {
+    stop_tts();
+
+    let (_stream, handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+    let sink = Arc::new(Sink::try_new(&handle).map_err(|e| e.to_string())?);
+    let source = Decoder::new(Cursor::new(wav)).map_err(|e| e.to_string())?;
+    sink.append(source);
+    *CURRENT_SINK.lock() = Some(Arc::clone(&sink));
+    sink.sleep_until_end();
+
+    // Leave the sink of newer speech in place
+    let mut current = CURRENT_SINK.lock();
+    if current.as_ref().is_some_and(|c| Arc::ptr_eq(c, &sink)) {
+        *current = None;
+    }
+    Ok(())
+}
+
+/// Run `command` with `text` on stdin, returning its stdout
+fn run_with_stdin(command: &mut Command, text: &str) -> Result<Vec<u8>, String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {:?}: {}", command.get_program(), e))?;
+    // Write on another thread while wait_with_output drains stdout and stderr,
+    // or long text fills the pipes and both sides block
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = text.as_bytes().to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let written = writer
+        .join()
+        .unwrap_or_else(|_| Err(std::io::Error::other("writer thread panicked")));
+    if !output.status.success() {
+        return Err(format!(
+            "{:?} failed: {}",
+            command.get_program(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    written.map_err(|e| format!("Failed to write text: {}", e))?;
+    Ok(output.stdout)
+}
+
+#[cfg(target_os = "linux")]
+fn system_tts(text: &str, voice: &str) -> Result<Vec<u8>, String> {
+    let mut command = Command::new("espeak");
+    command.arg("--stdout");
+    if !voice.is_empty() {
+        command.args(["-v", voice]);
+    }
+    // espeak cannot seek stdout, so its header does not give the real lengths
+    let mut wav = run_with_stdin(&mut command, text)?;
+    fix_wav_lengths(&mut wav);
+    Ok(wav)
+}
+
+#[cfg(target_os = "macos")]
+fn system_tts(text: &str, voice: &str) -> Result<Vec<u8>, String> {
+    let path = temp_wav_path();
+    let mut command = Command::new("say");
+    command
+        .args([
+            "--file-format=WAVE",
+            "--data-format=LEI16@22050",
+            "-f",
+            "-",
+            "-o",
+        ])
+        .arg(&path);
+    if !voice.is_empty() {
+        command.args(["-v", voice]);
+    }
+    run_with_stdin(&mut command, text)?;
+    read_temp_wav(&path)
+}
+
+#[cfg(target_os = "windows")]
+fn system_tts(text: &str, voice: &str) -> Result<Vec<u8>, String> {
+    const SCRIPT: &str = "Add-Type -AssemblyName System.Speech; \
+        $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+        if ($env:SPEAKY_TTS_VOICE) { $s.SelectVoice($env:SPEAKY_TTS_VOICE) }; \
+        $s.SetOutputToWaveFile($env:SPEAKY_TTS_OUT); \
+        $s.Speak([Console]::In.ReadToEnd()); $s.Dispose()";
+
+    let path = temp_wav_path();
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("SPEAKY_TTS_VOICE", voice)
+        .env("SPEAKY_TTS_OUT", &path);
+    run_with_stdin(&mut command, text)?;
+    read_temp_wav(&path)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn system_tts(_text: &str, _voice: &str) -> Result<Vec<u8>, String> {
+    Err("System TTS is not supported on this platform".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn temp_wav_path() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("speaky-tts-{}.wav", uuid::Uuid::new_v4()))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn read_temp_wav(path: &std::path::Path) -> Result<Vec<u8>, String> {
+    let wav = std::fs::read(path).map_err(|e| format!("Failed to read speech: {}", e));
+    let _ = std::fs::remove_file(path);
+    wav
+}
+
+fn elevenlabs(text: &str, cfg: &TtsConfig) -> Result<Vec<u8>, String> {
+    let url = format!(
+        "{}/{}?output_format=pcm_{}",
+        ELEVENLABS_URL, cfg.voice, ELEVENLABS_SAMPLE_RATE
+    );
+    let response = http_client()?
+        .post(&url)
+        .header("xi-api-key", &cfg.api_key)
+        .json(&serde_json::json!({ "text": text }))
+        .send()
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let pcm = response_bytes(response)?;
+
+    let samples: Vec<i16> = pcm
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    Ok(encode_wav(&samples, ELEVENLABS_SAMPLE_RATE))
+}
+
+/// Synthesize with a Coqui `tts-server`, which answers with WAV
+fn coqui(text: &str, cfg: &TtsConfig) -> Result<Vec<u8>, String> {
+    let url = format!("{}/api/tts", cfg.server_url.trim_end_matches('/'));
+    let response = http_client()?
+        .get(&url)
+        .query(&[("text", text), ("speaker_id", cfg.voice.as_str())])
+        .send()
+        .map_err(|e| format!("Request failed: {}", e))?;
+    response_bytes(response)
+}
+
+fn http_client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn response_bytes(response: reqwest::blocking::Response) -> Result<Vec<u8>, String> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().unwrap_or_default();
+        return Err(format!("TTS error: {} - {}", status, text));
+    }
+    response
+        .bytes()
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Failed to read speech: {}", e))
+}
+
+/// Set the RIFF and `data` chunk sizes of a streamed WAV to the actual lengths
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn fix_wav_lengths(wav: &mut [u8]) {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return;
+    }
+    let riff_len = (wav.len() - 8) as u32;
+    wav[4..8].copy_from_slice(&riff_len.to_le_bytes());
+
+    let mut offset = 12;
+    while offset + 8 <= wav.len() {
+        let body_start = offset + 8;
+        if &wav[offset..offset + 4] == b"data" {
+            let data_len = (wav.len() - body_start) as u32;
+            wav[offset + 4..body_start].copy_from_slice(&data_len.to_le_bytes());
+            return;
+        }
+        let chunk_len = u32::from_le_bytes([
+            wav[offset + 4],
+            wav[offset + 5],
+            wav[offset + 6],
+            wav[offset + 7],
+        ]) as usize;
+        offset = body_start + chunk_len + (chunk_len & 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streamed_wav_lengths_are_fixed() {
+        let mut wav = encode_wav(&[1, 2, 3, 4], 22050);
+        wav[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        wav[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        fix_wav_lengths(&mut wav);
+        assert_eq!(wav, encode_wav(&[1, 2, 3, 4], 22050));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn text_larger_than_a_pipe_buffer_does_not_block() {
+        // Far past the 64 KiB Linux pipe buffer on both stdin and stdout
+        let text = "word ".repeat(100_000);
+        let output = run_with_stdin(&mut Command::new("cat"), &text).unwrap();
+        assert_eq!(output, text.as_bytes());
+    }
+}
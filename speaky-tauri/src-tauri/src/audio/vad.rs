@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+
+/// Energy-based voice activity detector that signals when the speaker has gone quiet
+pub struct VadDetector {
+    threshold: f32,
+    silence_timeout: Duration,
+    last_voice: Instant,
+}
+
+impl VadDetector {
+    /// `threshold` is the RMS level (0.0 - 1.0) below which input counts as silence
+    pub fn new(threshold: f32, silence_timeout: Duration) -> Self {
+        Self {
+            threshold,
+            silence_timeout,
+            last_voice: Instant::now(),
+        }
+    }
+
+    /// Feed the current input level; returns true once silence has lasted
+    /// longer than the timeout
+    pub fn update(&mut self, level: f32) -> bool {
+        let now = Instant::now();
+        if level >= self.threshold {
+            self.last_voice = now;
+            return false;
+        }
+
+        now.duration_since(self.last_voice) >= self.silence_timeout
+    }
+}
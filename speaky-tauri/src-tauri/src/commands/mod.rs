@@ -1,10 +1,23 @@
 use log::info;
-use tauri::{command, AppHandle, Manager};
+use std::path::PathBuf;
+use tauri::{command, AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
 
-use crate::audio::AudioRecorder;
-use crate::config::Config;
-use crate::engines;
+use crate::audio::{
+    self, AudioDeviceInfo, AudioLevels, AudioRecorder, DeviceBenchmark, RecorderStats,
+};
+use crate::config::{
+    self, AppPasteOverride, AppVocabHint, Config, HotkeyProfile, ProfanityFilterConfig,
+    TextTransform, TriggerMode,
+};
+use crate::engines::{self, EngineCapabilities};
+use crate::history::recent_apps::AppUsageRecord;
+use crate::history::TranscriptionRecord;
+use crate::hotkey::{self, HotkeyInfo};
 use crate::input;
+use crate::startup::StartupIssue;
+use crate::system::SystemInfo;
+use crate::window_info::WindowInfo;
 use crate::APP_STATE;
 
 /// Get current configuration
@@ -18,28 +31,210 @@ pub fn get_config() -> Config {
 pub fn save_config(config: Config) -> Result<(), String> {
     info!("Saving configuration");
 
+    config.validate().map_err(|errs| errs.join("; "))?;
+
     // Save to file
     config.save().map_err(|e| e.to_string())?;
 
-    // Update in-memory config
-    *APP_STATE.config.write() = config.clone();
+    // Update in-memory config, engine, recorder and hotkeys
+    APP_STATE.apply_config(config);
 
-    // Recreate engine with new config
-    let engine = engines::create_engine(&config);
-    *APP_STATE.engine.write() = engine;
+    info!("Configuration saved successfully");
+    Ok(())
+}
 
-    // Update recorder settings
-    if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
-        // Recreate recorder with new settings
-        let new_recorder = AudioRecorder::new(
-            config.core.asr.audio_device,
-            config.core.asr.audio_gain,
-        );
-        *recorder = new_recorder;
+/// Replace the configuration with the defaults and write them to disk
+#[command]
+pub fn reset_config(app: AppHandle) -> Result<Config, String> {
+    info!("Resetting configuration to defaults");
+
+    let config = Config::default();
+    config.save().map_err(|e| e.to_string())?;
+
+    // Update in-memory config, engine, recorder and hotkeys
+    APP_STATE.apply_config(config.clone());
+
+    let _ = app.emit("config-reset", &config);
+    Ok(config)
+}
+
+/// Export configuration as pretty-printed JSON; asks for a path when none is given
+#[command]
+pub async fn export_config(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => app
+                .dialog()
+                .file()
+                .add_filter("JSON", &["json"])
+                .set_file_name("speaky-config.json")
+                .blocking_save_file()
+                .ok_or_else(|| "No file selected".to_string())?
+                .into_path()
+                .map_err(|e| e.to_string())?,
+        };
+        info!("Exporting configuration to {:?}", path);
+
+        let content =
+            serde_json::to_string_pretty(&*APP_STATE.config.read()).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Import configuration from a JSON file; asks for a path when none is given
+#[command]
+pub async fn import_config(app: AppHandle, path: Option<String>) -> Result<Config, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => app
+                .dialog()
+                .file()
+                .add_filter("JSON", &["json"])
+                .blocking_pick_file()
+                .ok_or_else(|| "No file selected".to_string())?
+                .into_path()
+                .map_err(|e| e.to_string())?,
+        };
+        info!("Importing configuration from {:?}", path);
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let config: Config =
+            serde_json::from_str(&content).map_err(|e| format!("Invalid config file: {}", e))?;
+        config.validate().map_err(|errs| errs.join("; "))?;
+
+        save_config(config.clone())?;
+        let _ = app.emit("config-imported", &config);
+        Ok(config)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Save the active configuration as a named profile
+#[command]
+pub fn save_profile(name: String) -> Result<(), String> {
+    info!("Saving profile {:?}", name);
+    config::save_profile(&APP_STATE.config.read(), &name)
+}
+
+/// Make a saved profile the active configuration
+#[command]
+pub fn load_profile(app: AppHandle, name: String) -> Result<Config, String> {
+    info!("Loading profile {:?}", name);
+
+    let config = config::load_profile(&name)?;
+    save_config(config.clone())?;
+    let _ = app.emit("profile-loaded", &name);
+    Ok(config)
+}
+
+/// Names of the saved profiles
+#[command]
+pub fn list_profiles() -> Vec<String> {
+    config::list_profiles()
+}
+
+/// Delete a saved profile
+#[command]
+pub fn delete_profile(name: String) -> Result<(), String> {
+    info!("Deleting profile {:?}", name);
+    config::delete_profile(&name)
+}
+
+/// Get per-application vocabulary hints
+#[command]
+pub fn get_vocab_hints() -> Vec<AppVocabHint> {
+    APP_STATE.config.read().app_vocab_hints.clone()
+}
+
+/// Save per-application vocabulary hints
+#[command]
+pub fn save_vocab_hints(hints: Vec<AppVocabHint>) -> Result<(), String> {
+    info!("Saving {} vocabulary hint entries", hints.len());
+
+    let mut config = APP_STATE.config.write();
+    config.app_vocab_hints = hints;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Get per-application paste strategy overrides
+#[command]
+pub fn get_paste_overrides() -> Vec<AppPasteOverride> {
+    APP_STATE.config.read().app_paste_overrides.clone()
+}
+
+/// Save per-application paste strategy overrides
+#[command]
+pub fn save_paste_overrides(overrides: Vec<AppPasteOverride>) -> Result<(), String> {
+    info!("Saving {} paste overrides", overrides.len());
+
+    for entry in &overrides {
+        regex::Regex::new(&entry.wm_class_pattern)
+            .map_err(|e| format!("Invalid pattern {:?}: {}", entry.wm_class_pattern, e))?;
     }
 
-    info!("Configuration saved successfully");
-    Ok(())
+    let mut config = APP_STATE.config.write();
+    config.app_paste_overrides = overrides;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Get the text transforms applied before pasting
+#[command]
+pub fn get_transforms() -> Vec<TextTransform> {
+    APP_STATE.config.read().core.transforms.clone()
+}
+
+/// Save the text transforms applied before pasting
+#[command]
+pub fn save_transforms(transforms: Vec<TextTransform>) -> Result<(), String> {
+    info!("Saving {} text transforms", transforms.len());
+
+    for transform in &transforms {
+        if let TextTransform::RegexReplace { pattern, .. } = transform {
+            regex::Regex::new(pattern)
+                .map_err(|e| format!("Invalid pattern {:?}: {}", pattern, e))?;
+        }
+    }
+
+    let mut config = APP_STATE.config.write();
+    config.core.transforms = transforms;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Get the words masked by the profanity filter
+#[command]
+pub fn get_profanity_list() -> Vec<String> {
+    APP_STATE
+        .config
+        .read()
+        .core
+        .profanity_filter
+        .as_ref()
+        .map(|filter| filter.wordlist.clone())
+        .unwrap_or_default()
+}
+
+/// Set the words masked by the profanity filter; an empty list turns it off
+#[command]
+pub fn set_profanity_list(words: Vec<String>) -> Result<(), String> {
+    info!("Saving {} profanity filter words", words.len());
+
+    let mut config = APP_STATE.config.write();
+    if words.is_empty() {
+        config.core.profanity_filter = None;
+    } else {
+        config
+            .core
+            .profanity_filter
+            .get_or_insert_with(ProfanityFilterConfig::default)
+            .wordlist = words;
+    }
+    config.save().map_err(|e| e.to_string())
 }
 
 /// Start audio recording
@@ -48,7 +243,10 @@ pub fn start_recording() -> Result<(), String> {
     info!("Starting recording via command");
 
     if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
-        recorder.start()
+        recorder.start()?;
+        APP_STATE.start_extra_recorders();
+        *APP_STATE.recording_start_time.lock() = Some(std::time::Instant::now());
+        Ok(())
     } else {
         Err("Recorder not initialized".to_string())
     }
@@ -59,11 +257,271 @@ pub fn start_recording() -> Result<(), String> {
 pub fn stop_recording() -> Result<Vec<u8>, String> {
     info!("Stopping recording via command");
 
-    if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
-        Ok(recorder.stop())
-    } else {
-        Err("Recorder not initialized".to_string())
+    *APP_STATE.recording_start_time.lock() = None;
+    let audio_data = match *APP_STATE.recorder.write() {
+        Some(ref mut recorder) => recorder.stop(),
+        None => return Err("Recorder not initialized".to_string()),
+    };
+    Ok(APP_STATE.stop_extra_recorders(audio_data))
+}
+
+/// Take the audio captured since the last flush as WAV, without stopping,
+/// so the frontend can transcribe a long recording in chunks
+#[command]
+pub fn flush_audio_buffer() -> Result<Vec<u8>, String> {
+    match *APP_STATE.recorder.read() {
+        Some(ref recorder) => Ok(recorder.drain_wav()),
+        None => Err("Recorder not initialized".to_string()),
+    }
+}
+
+/// Pause recording, keeping the audio captured so far
+#[command]
+pub fn pause_recording(app: AppHandle) -> Result<(), String> {
+    info!("Pausing recording via command");
+
+    match *APP_STATE.recorder.read() {
+        Some(ref recorder) => recorder.pause()?,
+        None => return Err("Recorder not initialized".to_string()),
     }
+
+    let _ = app.emit(
+        "recording-state",
+        serde_json::json!({
+            "state": "paused"
+        }),
+    );
+    Ok(())
+}
+
+/// Resume a paused recording
+#[command]
+pub fn resume_recording(app: AppHandle) -> Result<(), String> {
+    info!("Resuming recording via command");
+
+    match *APP_STATE.recorder.read() {
+        Some(ref recorder) => recorder.resume()?,
+        None => return Err("Recorder not initialized".to_string()),
+    }
+
+    let _ = app.emit(
+        "recording-state",
+        serde_json::json!({
+            "state": "resumed"
+        }),
+    );
+    Ok(())
+}
+
+/// Maximum size of a file accepted by `transcribe_file`
+const MAX_TRANSCRIBE_FILE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Transcribe a WAV file from disk with the active engine
+#[command]
+pub async fn transcribe_file(app: AppHandle, path: String) -> Result<String, String> {
+    info!("Transcribing file: {}", path);
+
+    let size = std::fs::metadata(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?
+        .len();
+    if size > MAX_TRANSCRIBE_FILE_BYTES {
+        return Err(format!(
+            "File is too large ({} MB), the limit is {} MB",
+            size / 1024 / 1024,
+            MAX_TRANSCRIBE_FILE_BYTES / 1024 / 1024
+        ));
+    }
+
+    let audio_data = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let sample_rate = engines::parse_wav_sample_rate(&audio_data)
+        .ok_or_else(|| format!("{} is not a WAV file", path))?;
+    info!("File sample rate: {}", sample_rate);
+
+    let language = APP_STATE.config.read().core.asr.language.clone();
+
+    // Engines block on network I/O (and may start their own runtime)
+    tauri::async_runtime::spawn_blocking(move || {
+        let engine = APP_STATE.engine.read();
+        let engine = engine
+            .as_ref()
+            .ok_or_else(|| "No engine configured".to_string())?;
+
+        if engine.supports_streaming() {
+            let progress = Box::new(move |percent: f32| {
+                let _ = app.emit(
+                    "transcription-progress",
+                    serde_json::json!({
+                        "percent": percent
+                    }),
+                );
+            });
+            engine.transcribe_with_progress(&audio_data, &language, progress)
+        } else {
+            engine.transcribe(&audio_data, &language)
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Transcribe audio copied to the clipboard (WAV, MP3 or Ogg/Vorbis) with the active engine
+#[command]
+pub async fn transcribe_clipboard_audio(app: AppHandle) -> Result<String, String> {
+    let started_at_ms = crate::unix_time_ms();
+    let language = APP_STATE.config.read().core.asr.language.clone();
+
+    let text = tauri::async_runtime::spawn_blocking(move || {
+        let audio_data = input::read_clipboard_audio()?;
+        info!("Transcribing {} bytes of clipboard audio", audio_data.len());
+        let audio_data = audio::decode::to_wav(audio_data)?;
+
+        let engine = APP_STATE.engine.read();
+        let engine = engine
+            .as_ref()
+            .ok_or_else(|| "No engine configured".to_string())?;
+        engine.transcribe(&audio_data, &language)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let _ = app.emit(
+        "final-result",
+        crate::final_result_payload(&text, Some(started_at_ms)),
+    );
+    Ok(text)
+}
+
+/// One second of 16 kHz mono silence used by `test_engine`
+const TEST_ENGINE_WAV: &[u8] = include_bytes!("../../assets/silence.wav");
+
+/// Get the capabilities of the active engine
+#[command]
+pub fn get_engine_capabilities() -> Option<EngineCapabilities> {
+    APP_STATE
+        .engine
+        .read()
+        .as_ref()
+        .map(|engine| engine.capabilities())
+}
+
+/// Send a short silent clip to the active engine to check credentials and connectivity
+#[command]
+pub async fn test_engine() -> Result<String, String> {
+    info!("Testing engine connection");
+
+    let language = APP_STATE.config.read().core.asr.language.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let engine = APP_STATE.engine.read();
+        let engine = engine
+            .as_ref()
+            .ok_or_else(|| "No engine configured".to_string())?;
+
+        let started = std::time::Instant::now();
+        engine.transcribe(TEST_ENGINE_WAV, &language)?;
+        let latency_ms = started.elapsed().as_millis();
+
+        info!("{} responded in {} ms", engine.name(), latency_ms);
+        Ok(format!("{} responded in {} ms", engine.name(), latency_ms))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Pipe `text` through the configured post-process command and return its output
+#[command]
+pub async fn test_post_process_command(text: String) -> Result<String, String> {
+    let command = APP_STATE
+        .config
+        .read()
+        .core
+        .post_process_command
+        .clone()
+        .ok_or("No post-process command is configured")?;
+
+    tauri::async_runtime::spawn_blocking(move || crate::text::run_post_process(&command, &text))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Check that `engine_name` is reachable with the configured credentials,
+/// returning the round-trip time in milliseconds
+#[command]
+pub async fn check_engine_connectivity(engine_name: String) -> Result<u64, String> {
+    let config = APP_STATE.config.read().clone();
+
+    tauri::async_runtime::spawn_blocking(move || engines::check_connectivity(&config, &engine_name))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Length of the ambient sample recorded by `calibrate_gain`
+const CALIBRATION_DURATION: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Fraction of full scale the calibrated gain should bring the peak to
+const CALIBRATION_TARGET_PEAK: f64 = 0.7;
+
+/// Record ambient sound and pick a gain that brings its peak to 70% of full scale
+#[command]
+pub async fn calibrate_gain() -> Result<f64, String> {
+    info!("Calibrating microphone gain");
+
+    tauri::async_runtime::spawn_blocking(|| {
+        let current_gain = {
+            let mut recorder = APP_STATE.recorder.write();
+            let recorder = recorder
+                .as_mut()
+                .ok_or_else(|| "Recorder not initialized".to_string())?;
+            if recorder.is_recording() {
+                return Err("Cannot calibrate while recording".to_string());
+            }
+
+            // Measure the unfiltered signal
+            recorder.set_noise_gate_threshold(0.0);
+            recorder.start()?;
+            recorder.gain()
+        };
+
+        std::thread::sleep(CALIBRATION_DURATION);
+
+        let peak = {
+            let mut recorder = APP_STATE.recorder.write();
+            let recorder = recorder
+                .as_mut()
+                .ok_or_else(|| "Recorder not initialized".to_string())?;
+            recorder.stop();
+            recorder.peak_amplitude()
+        };
+
+        if peak == 0 {
+            return Err("No audio detected, please check your microphone".to_string());
+        }
+
+        // Peak as the device delivered it, before the current gain was applied
+        let raw_peak = peak as f64 / current_gain;
+        let gain = (CALIBRATION_TARGET_PEAK * i16::MAX as f64 / raw_peak).clamp(0.1, 5.0);
+        let gain = (gain * 10.0).round() / 10.0;
+        info!(
+            "Measured peak {} at gain {}, new gain {}",
+            peak, current_gain, gain
+        );
+
+        let asr = {
+            let mut config = APP_STATE.config.write();
+            config.core.asr.audio_gain = gain;
+            config.save().map_err(|e| e.to_string())?;
+            config.core.asr.clone()
+        };
+
+        // Recreate recorder to apply the new gain and restore the noise gate
+        if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
+            *recorder = AudioRecorder::from_config(&asr);
+        }
+
+        Ok(gain)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 /// Get list of audio input devices
@@ -72,28 +530,355 @@ pub fn get_audio_devices() -> Vec<(u32, String)> {
     AudioRecorder::get_devices()
 }
 
+/// Get the sample rates and channel count of the input device at `index`
+#[command]
+pub fn get_audio_device_info(index: u32) -> Result<AudioDeviceInfo, String> {
+    AudioRecorder::device_info(index)
+}
+
+/// Measure stream latency of every input device, fastest first
+#[command]
+pub async fn benchmark_devices() -> Result<Vec<DeviceBenchmark>, String> {
+    info!("Benchmarking audio input devices");
+
+    // Devices are opened one after another, which takes a while
+    tauri::async_runtime::spawn_blocking(AudioRecorder::benchmark_devices)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get every hotkey name the listener accepts, sorted by category then name
+#[command]
+pub fn get_supported_hotkeys() -> Vec<HotkeyInfo> {
+    hotkey::supported_hotkeys()
+}
+
+/// Whether global hotkeys can work; only macOS asks for Accessibility access
+#[command]
+pub fn check_accessibility_permission() -> bool {
+    crate::permissions::accessibility_trusted()
+}
+
+/// Show the macOS dialog that leads to the Accessibility settings; the app
+/// must be restarted once access is granted
+#[command]
+pub fn request_accessibility_permission() -> bool {
+    crate::permissions::request_accessibility()
+}
+
+/// Permissions reported through `permission-required` so far, for windows
+/// opened after it fired
+#[command]
+pub fn get_required_permissions() -> Vec<crate::permissions::RequiredPermission> {
+    APP_STATE.required_permissions.read().clone()
+}
+
 /// Update hotkey settings
 #[command]
-pub fn set_hotkey(app: AppHandle, hotkey: String, hold_time: f64) -> Result<(), String> {
-    info!("Setting hotkey: {} with hold time: {}", hotkey, hold_time);
+pub fn set_hotkey(
+    app: AppHandle,
+    hotkey: String,
+    trigger_mode: TriggerMode,
+) -> Result<(), String> {
+    info!(
+        "Setting hotkey: {} with trigger mode: {:?}",
+        hotkey, trigger_mode
+    );
 
     // Update config
     {
         let mut config = APP_STATE.config.write();
+        if let Some(name) = config::detect_hotkey_conflict(&hotkey, &config.core.extra_hotkeys) {
+            return Err(format!(
+                "Hotkey {} is already used by profile {:?}",
+                hotkey, name
+            ));
+        }
         config.core.asr.hotkey = hotkey.clone();
-        config.core.asr.hotkey_hold_time = hold_time;
+        config.core.asr.trigger_mode = trigger_mode;
         config.save().map_err(|e| e.to_string())?;
     }
 
-    // Update hotkey manager
-    if let Some(ref mut manager) = *APP_STATE.hotkey_manager.write() {
-        manager.update_hotkey(&hotkey);
-        manager.update_hold_time(hold_time);
+    // Rebuild every manager, since the trigger mode is shared by all profiles
+    hotkey::reload_hotkeys(app);
+
+    Ok(())
+}
+
+/// Add an extra hotkey that records in its own language
+#[command]
+pub fn add_hotkey_profile(app: AppHandle, profile: HotkeyProfile) -> Result<(), String> {
+    info!(
+        "Adding hotkey profile: {} -> {}",
+        profile.hotkey, profile.language
+    );
+
+    {
+        let mut config = APP_STATE.config.write();
+        if config.core.asr.hotkey.eq_ignore_ascii_case(&profile.hotkey) {
+            return Err(format!(
+                "Hotkey {} is already the main hotkey",
+                profile.hotkey
+            ));
+        }
+        if let Some(name) =
+            config::detect_hotkey_conflict(&profile.hotkey, &config.core.extra_hotkeys)
+        {
+            return Err(format!(
+                "Hotkey {} is already used by profile {:?}",
+                profile.hotkey, name
+            ));
+        }
+        config.core.extra_hotkeys.push(profile);
+        config.save().map_err(|e| e.to_string())?;
+    }
+
+    hotkey::reload_hotkeys(app);
+    Ok(())
+}
+
+/// Remove an extra hotkey profile by its hotkey
+#[command]
+pub fn remove_hotkey_profile(app: AppHandle, hotkey: String) -> Result<(), String> {
+    info!("Removing hotkey profile: {}", hotkey);
+
+    {
+        let mut config = APP_STATE.config.write();
+        let before = config.core.extra_hotkeys.len();
+        config.core.extra_hotkeys.retain(|p| p.hotkey != hotkey);
+        if config.core.extra_hotkeys.len() == before {
+            return Err(format!("No hotkey profile for {}", hotkey));
+        }
+        config.save().map_err(|e| e.to_string())?;
+    }
+
+    hotkey::reload_hotkeys(app);
+    Ok(())
+}
+
+/// Get the current input noise level (RMS of the last 512 samples)
+#[command]
+pub fn get_noise_level() -> f32 {
+    APP_STATE
+        .recorder
+        .read()
+        .as_ref()
+        .map(|recorder| recorder.get_noise_level())
+        .unwrap_or(0.0)
+}
+
+/// Speak `text` with the configured TTS settings, or the system voice if none are set
+#[command]
+pub async fn preview_tts(text: String) -> Result<(), String> {
+    let cfg = APP_STATE
+        .config
+        .read()
+        .appearance
+        .tts
+        .clone()
+        .unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || crate::audio::tts::text_to_speech(&text, &cfg))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stop the speech being played, if any
+#[command]
+pub fn stop_tts() {
+    crate::audio::tts::stop_tts();
+}
+
+/// Get RMS and peak of the last 512 recorded samples
+#[command]
+pub fn get_levels() -> AudioLevels {
+    APP_STATE
+        .recorder
+        .read()
+        .as_ref()
+        .map(|recorder| recorder.get_levels())
+        .unwrap_or_default()
+}
+
+/// Get the last recording down-sampled to `n_points` RMS values for a static waveform
+#[command]
+pub fn get_waveform_snapshot(n_points: u32) -> Vec<f32> {
+    match *APP_STATE.recorder.read() {
+        Some(ref recorder) => recorder.waveform(n_points as usize),
+        None => vec![0.0; n_points as usize],
+    }
+}
+
+/// Get the input levels of the current recording, oldest first
+#[command]
+pub fn get_level_history() -> Vec<f32> {
+    APP_STATE.level_history.lock().iter().copied().collect()
+}
+
+/// Get how long the current recording has been running, in seconds
+#[command]
+pub fn get_recording_duration() -> Option<f64> {
+    APP_STATE
+        .recording_start_time
+        .lock()
+        .map(|start| start.elapsed().as_secs_f64())
+}
+
+/// Get clipping totals across recordings since startup
+#[command]
+pub fn get_recorder_stats() -> RecorderStats {
+    APP_STATE.recorder_stats.lock().clone()
+}
+
+/// Get transcription history, oldest first
+#[command]
+pub fn get_history() -> Vec<TranscriptionRecord> {
+    APP_STATE.history.lock().iter().cloned().collect()
+}
+
+/// Clear transcription history
+#[command]
+pub fn clear_history() {
+    info!("Clearing transcription history");
+    APP_STATE.history.lock().clear();
+}
+
+/// Get up to `limit` applications text was pasted into, most used first
+#[command]
+pub fn get_recent_apps(limit: u32) -> Vec<AppUsageRecord> {
+    crate::history::recent_apps::most_used(&APP_STATE.recent_apps.lock(), limit as usize)
+}
+
+/// Forget the applications text was pasted into
+#[command]
+pub fn clear_recent_apps() {
+    info!("Clearing recent apps");
+    APP_STATE.recent_apps.lock().clear();
+}
+
+/// Problems found by the startup checks, as sent in `startup-status`
+#[command]
+pub fn get_startup_issues() -> Vec<StartupIssue> {
+    APP_STATE.startup_issues.read().clone()
+}
+
+/// OS, audio host and app version, for support requests
+#[command]
+pub fn get_system_info() -> SystemInfo {
+    crate::system::system_info()
+}
+
+/// Path of today's log file
+#[command]
+pub fn get_log_path() -> String {
+    crate::logging::log_path().to_string_lossy().into_owned()
+}
+
+/// Last `lines` lines of today's log file
+#[command]
+pub fn get_recent_logs(lines: u32) -> Vec<String> {
+    crate::logging::recent_lines(lines as usize)
+}
+
+/// Start the LAN server from the saved `lan_server` settings, restarting it if running
+#[command]
+pub fn start_lan_server() -> Result<(), String> {
+    let lan_config = APP_STATE
+        .config
+        .read()
+        .lan_server
+        .clone()
+        .ok_or("The LAN server is not configured")?;
+
+    let mut running = APP_STATE.lan_server.lock();
+    if let Some(server) = running.take() {
+        server.stop();
+    }
+    *running = Some(crate::server::LanServer::start(&lan_config)?);
+    Ok(())
+}
+
+/// Stop the LAN server if it is running
+#[command]
+pub fn stop_lan_server() -> Result<(), String> {
+    if let Some(server) = APP_STATE.lan_server.lock().take() {
+        server.stop();
     }
+    Ok(())
+}
 
-    // Note: Full hotkey re-registration would require unregistering old and registering new
-    // This simplified implementation updates the manager settings
+/// Start the WebSocket server from the saved `ws_server` settings, restarting it if running
+#[command]
+pub fn start_ws_server(app: AppHandle) -> Result<(), String> {
+    crate::server::restart_ws_server(&app)
+}
 
+/// Stop the WebSocket server if it is running
+#[command]
+pub fn stop_ws_server() -> Result<(), String> {
+    if let Some(server) = APP_STATE.ws_server.lock().take() {
+        server.stop();
+    }
+    Ok(())
+}
+
+/// Start watching the saved `watch_directory`, restarting the watch if running
+#[command]
+pub fn start_watch() -> Result<(), String> {
+    let watch_config = APP_STATE
+        .config
+        .read()
+        .watch_directory
+        .clone()
+        .ok_or("The watch directory is not configured")?;
+
+    let mut running = APP_STATE.directory_watcher.lock();
+    running.take();
+    *running = Some(crate::watch::DirectoryWatcher::start(&watch_config)?);
+    Ok(())
+}
+
+/// Stop watching the directory if a watch is running
+#[command]
+pub fn stop_watch() -> Result<(), String> {
+    APP_STATE.directory_watcher.lock().take();
+    Ok(())
+}
+
+/// Window that currently has focus, with its icon as a data URL
+#[command]
+pub fn get_focused_window() -> Option<WindowInfo> {
+    crate::window_info::get_focused_window_info()
+}
+
+/// Drop all cached app icons so they are read from disk again
+#[command]
+pub fn clear_icon_cache() {
+    let mut cache = APP_STATE.icon_cache.write();
+    info!("Clearing {} cached icons", cache.len());
+    cache.clear();
+}
+
+/// Add the tray icon, e.g. after starting with `run_in_background`
+#[command]
+pub fn show_tray_icon(app: AppHandle) -> Result<(), String> {
+    crate::create_tray(&app).map_err(|e| e.to_string())
+}
+
+/// Remove the tray icon until `show_tray_icon` or the next start
+#[command]
+pub fn hide_tray_icon(app: AppHandle) {
+    crate::remove_tray(&app);
+}
+
+/// Go back to the built-in tray icons
+#[command]
+pub fn reset_tray_icons(app: AppHandle) -> Result<(), String> {
+    {
+        let mut config = APP_STATE.config.write();
+        config.appearance.tray_icon_set = config::TrayIconSet::default();
+        config.save().map_err(|e| e.to_string())?;
+    }
+    crate::set_tray_state(&app, crate::TrayState::Idle);
     Ok(())
 }
 
@@ -122,3 +907,16 @@ pub fn paste_text(app: AppHandle, text: String) -> Result<(), String> {
     info!("Pasting text via command");
     input::paste_text(&app, &text)
 }
+
+/// Paste text held back by preview mode
+#[command]
+pub fn confirm_paste(app: AppHandle, id: String) -> Result<(), String> {
+    let text = APP_STATE
+        .pending_previews
+        .lock()
+        .remove(&id)
+        .ok_or_else(|| format!("No pending preview {:?}", id))?;
+
+    info!("Pasting confirmed preview {}", id);
+    input::paste_text(&app, &text)
+}
@@ -0,0 +1,263 @@
+use log::info;
+use serde_yaml::value::{Tag, TaggedValue};
+use serde_yaml::{Mapping, Value};
+
+/// Migration steps in order; entry `n` upgrades version `n + 1` to `n + 2`
+const MIGRATIONS: &[fn(&mut Value)] = &[migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// Schema version written by this build
+pub const CURRENT_CONFIG_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+/// Run every migration from `version` up to the current schema, in order
+pub fn migrate(doc: &mut Value, version: u32) {
+    let first = version.max(1) as usize - 1;
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(first) {
+        step(doc);
+        info!("Migrated config to version {}", i + 2);
+    }
+}
+
+/// v1 is the Python app's config (also read from ~/.config/speaky). It had
+/// `whisper_remote` and `whisper` engines plus AI-key/LLM/MCP sections that
+/// the desktop app does not use.
+fn migrate_v1_to_v2(doc: &mut Value) {
+    if let Some(engine) = doc.get_mut("engine").and_then(Value::as_mapping_mut) {
+        // An OpenAI-compatible server is just the OpenAI engine with another base URL
+        if let Some(remote) = engine.remove("whisper_remote") {
+            if engine.get("current").and_then(Value::as_str) == Some("whisper_remote") {
+                let server_url = remote
+                    .get("server_url")
+                    .and_then(Value::as_str)
+                    .unwrap_or("http://localhost:8000")
+                    .trim_end_matches('/');
+
+                let mut openai = Mapping::new();
+                openai.insert("base_url".into(), format!("{}/v1", server_url).into());
+                for key in ["api_key", "model"] {
+                    if let Some(value) = remote.get(key) {
+                        openai.insert(key.into(), value.clone());
+                    }
+                }
+                engine.insert("openai".into(), Value::Mapping(openai));
+                engine.insert("current".into(), "openai".into());
+            }
+        }
+
+        // The local model moved to whisper.cpp, which needs a GGML file path
+        // instead of a model size, so only the device choice carries over
+        if let Some(whisper) = engine.remove("whisper") {
+            let use_gpu = whisper.get("device").and_then(Value::as_str) == Some("cuda");
+            let mut local = Mapping::new();
+            local.insert("use_gpu".into(), use_gpu.into());
+            engine.insert("local_whisper".into(), Value::Mapping(local));
+
+            if engine.get("current").and_then(Value::as_str) == Some("whisper") {
+                engine.insert("current".into(), "local_whisper".into());
+            }
+        }
+
+        engine.remove("volcengine");
+        if let Some(volc) = engine
+            .get_mut("volc_bigmodel")
+            .and_then(Value::as_mapping_mut)
+        {
+            volc.remove("model");
+        }
+    }
+
+    if let Some(core) = doc.get_mut("core").and_then(Value::as_mapping_mut) {
+        core.remove("ai");
+    }
+    if let Some(root) = doc.as_mapping_mut() {
+        for key in ["llm_agent", "llm", "mcp"] {
+            root.remove(key);
+        }
+    }
+}
+
+/// v3 replaced `hotkey_hold_time` with `trigger_mode`, which also allows double-tap
+fn migrate_v2_to_v3(doc: &mut Value) {
+    let asr = doc
+        .get_mut("core")
+        .and_then(|core| core.get_mut("asr"))
+        .and_then(Value::as_mapping_mut);
+
+    if let Some(asr) = asr {
+        if let Some(hold_time) = asr.remove("hotkey_hold_time") {
+            let mut hold = Mapping::new();
+            hold.insert("hold_time_secs".into(), hold_time);
+            // Enum variants are YAML tags, as serde_yaml writes them
+            let trigger_mode = TaggedValue {
+                tag: Tag::new("hold"),
+                value: Value::Mapping(hold),
+            };
+            asr.insert("trigger_mode".into(), Value::Tagged(Box::new(trigger_mode)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Config, TriggerMode};
+    use super::*;
+
+    /// Config as written by the Python app, using a self-hosted Whisper server
+    const V1_PYTHON_WHISPER_REMOTE: &str = r#"
+core:
+  asr:
+    hotkey: alt
+    hotkey_hold_time: 0.5
+    language: en
+    streaming_mode: false
+  ai:
+    enabled: true
+    hotkey: alt
+    url: https://chatgpt.com
+llm_agent:
+  enabled: false
+  hotkey: tab
+llm:
+  openai:
+    api_key: sk-llm
+engine:
+  current: whisper_remote
+  volc_bigmodel:
+    app_key: ""
+    access_key: ""
+    model: bigmodel
+  volcengine:
+    app_id: ""
+    access_key: ""
+    secret_key: ""
+  whisper_remote:
+    server_url: http://10.0.0.2:8000/
+    model: large-v3
+    api_key: secret
+  whisper:
+    model: base
+    device: auto
+appearance:
+  theme: dark
+"#;
+
+    /// Python app config using the local Whisper model on CUDA
+    const V1_PYTHON_LOCAL_WHISPER: &str = r#"
+core:
+  asr:
+    hotkey: ctrl
+engine:
+  current: whisper
+  whisper:
+    model: small
+    device: cuda
+"#;
+
+    /// Desktop app config written before the version field existed
+    const V1_UNVERSIONED: &str = r#"
+core:
+  asr:
+    hotkey: ctrl
+    language: zh
+engine:
+  current: volc_bigmodel
+  volc_bigmodel:
+    app_key: app
+    access_key: access
+"#;
+
+    const V2: &str = r#"
+version: 2
+core:
+  asr:
+    hotkey: alt
+    hotkey_hold_time: 0.3
+"#;
+
+    const V3: &str = r#"
+version: 3
+engine:
+  current: openai
+  openai:
+    api_key: sk-test
+"#;
+
+    #[test]
+    fn migrates_python_whisper_remote_to_openai() {
+        let (config, migrated) = Config::parse(V1_PYTHON_WHISPER_REMOTE).unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.engine.current, "openai");
+        assert_eq!(config.engine.openai.base_url, "http://10.0.0.2:8000/v1");
+        assert_eq!(config.engine.openai.model, "large-v3");
+        assert_eq!(config.engine.openai.api_key, "secret");
+        assert_eq!(config.core.asr.hotkey, "alt");
+        assert_eq!(config.core.asr.language, "en");
+        assert!(!config.core.asr.streaming_mode);
+        assert_eq!(config.appearance.theme, "dark");
+        assert_eq!(
+            config.core.asr.trigger_mode,
+            TriggerMode::Hold {
+                hold_time_secs: 0.5
+            }
+        );
+    }
+
+    #[test]
+    fn drops_python_only_sections() {
+        let mut doc: Value = serde_yaml::from_str(V1_PYTHON_WHISPER_REMOTE).unwrap();
+        migrate(&mut doc, 1);
+
+        assert!(doc.get("llm").is_none());
+        assert!(doc.get("llm_agent").is_none());
+        assert!(doc["core"].get("ai").is_none());
+        assert!(doc["engine"].get("volcengine").is_none());
+        assert!(doc["engine"].get("whisper_remote").is_none());
+        assert!(doc["engine"]["volc_bigmodel"].get("model").is_none());
+    }
+
+    #[test]
+    fn migrates_python_local_whisper() {
+        let (config, migrated) = Config::parse(V1_PYTHON_LOCAL_WHISPER).unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.engine.current, "local_whisper");
+        assert!(config.engine.local_whisper.use_gpu);
+        assert!(config.engine.local_whisper.model_path.is_empty());
+    }
+
+    #[test]
+    fn unversioned_desktop_config_keeps_settings() {
+        let (config, migrated) = Config::parse(V1_UNVERSIONED).unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.engine.current, "volc_bigmodel");
+        assert_eq!(config.engine.volc_bigmodel.app_key, "app");
+        assert_eq!(config.engine.volc_bigmodel.access_key, "access");
+    }
+
+    #[test]
+    fn hold_time_becomes_trigger_mode() {
+        let (config, migrated) = Config::parse(V2).unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.core.asr.hotkey, "alt");
+        assert_eq!(
+            config.core.asr.trigger_mode,
+            TriggerMode::Hold {
+                hold_time_secs: 0.3
+            }
+        );
+    }
+
+    #[test]
+    fn current_version_is_not_migrated() {
+        let (config, migrated) = Config::parse(V3).unwrap();
+
+        assert!(!migrated);
+        assert_eq!(config.version, 3);
+        assert_eq!(config.engine.openai.api_key, "sk-test");
+    }
+}
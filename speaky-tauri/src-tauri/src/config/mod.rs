@@ -1,33 +1,109 @@
-use log::info;
+mod migrate;
+mod profiles;
+mod watcher;
+
+pub use migrate::CURRENT_CONFIG_VERSION;
+pub use profiles::{delete_profile, list_profiles, load_profile, save_profile};
+pub use watcher::spawn_config_watcher;
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::RegexBuilder;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Modification time of the config file right after our last save, used to
+/// tell our own writes apart from external edits. `save` holds the lock while
+/// writing, so the watcher cannot check an mtime before it is recorded.
+static LAST_SAVED_MTIME: Lazy<Mutex<Option<SystemTime>>> = Lazy::new(|| Mutex::new(None));
+
+/// Hash of the default config's YAML, which changes whenever a field is added,
+/// removed or renamed; it only has to be stable within one build
+static SCHEMA_HASH: Lazy<u64> = Lazy::new(|| {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    serde_yaml::to_string(&Config::default())
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+});
 
 /// ASR configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AsrConfig {
     #[serde(default = "default_hotkey")]
     pub hotkey: String,
-    #[serde(default = "default_hold_time")]
-    pub hotkey_hold_time: f64,
+    #[serde(default)]
+    pub trigger_mode: TriggerMode,
     #[serde(default = "default_language")]
     pub language: String,
+    /// Languages spoken interchangeably; when set, engines detect the language
+    /// of each segment instead of using `language`
+    #[serde(default)]
+    pub multi_language: Option<Vec<String>>,
     #[serde(default = "default_streaming_mode")]
     pub streaming_mode: bool,
     #[serde(default)]
     pub audio_device: Option<u32>,
+    /// Further input devices recorded alongside `audio_device` and mixed in
+    #[serde(default)]
+    pub extra_audio_devices: Vec<u32>,
     #[serde(default = "default_audio_gain")]
     pub audio_gain: f64,
     #[serde(default = "default_sound_notification")]
     pub sound_notification: bool,
+    #[serde(default = "default_noise_gate_threshold")]
+    pub noise_gate_threshold: f32,
+    /// Frames per input callback; small values cut latency on ALSA, whose
+    /// default buffer can hold 4096 frames or more
+    #[serde(default)]
+    pub audio_buffer_size: Option<u32>,
+    /// How long after a recording the input device is closed, which matters on
+    /// ALSA where an idle device still holds its PCM handles; 0 keeps it open
+    #[serde(default = "default_idle_release_delay_ms")]
+    pub idle_release_delay_ms: u64,
+    /// Pause between showing the window and starting the recorder
+    #[serde(default = "default_pre_recording_delay_ms")]
+    pub pre_recording_delay_ms: u64,
+    /// Clipboard settle delay before the paste keystroke, unless an override sets one
+    #[serde(default = "default_paste_delay_ms")]
+    pub paste_delay_ms: u64,
+    /// Cancel speaker audio picked up by the microphone on Linux and Windows; see
+    /// `audio::echo_cancel` for the limitations
+    #[serde(default)]
+    pub echo_cancellation: bool,
+    #[serde(default)]
+    pub vad_mode: bool,
+    #[serde(default = "default_vad_silence_ms")]
+    pub vad_silence_ms: u64,
+    #[serde(default)]
+    pub audio_compression: AudioCompression,
+    #[serde(default)]
+    pub paste_mode: PasteMode,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    /// Paste non-ASCII text through the clipboard even where typing is configured,
+    /// since xdotool mangles CJK without ibus or fcitx support; terminals, which
+    /// ignore Ctrl+V, keep typing
+    #[serde(default = "default_force_clipboard_for_unicode")]
+    pub force_clipboard_for_unicode: bool,
+    /// Emit `preview-result` and wait for `confirm_paste` instead of pasting
+    #[serde(default)]
+    pub preview_mode: bool,
+    /// Write numbers, dates and the like as digits (inverse text normalization)
+    #[serde(default = "default_itn_enabled")]
+    pub itn_enabled: bool,
+    #[serde(default = "default_punctuation_enabled")]
+    pub punctuation_enabled: bool,
 }
 
 fn default_hotkey() -> String {
     "ctrl".to_string()
 }
-fn default_hold_time() -> f64 {
-    1.0
-}
 fn default_language() -> String {
     "zh".to_string()
 }
@@ -40,17 +116,198 @@ fn default_audio_gain() -> f64 {
 fn default_sound_notification() -> bool {
     true
 }
+fn default_noise_gate_threshold() -> f32 {
+    0.01
+}
+fn default_idle_release_delay_ms() -> u64 {
+    30000
+}
+fn default_pre_recording_delay_ms() -> u64 {
+    50
+}
+fn default_vad_silence_ms() -> u64 {
+    1500
+}
+fn default_force_clipboard_for_unicode() -> bool {
+    true
+}
+fn default_itn_enabled() -> bool {
+    true
+}
+fn default_punctuation_enabled() -> bool {
+    true
+}
 
 impl Default for AsrConfig {
     fn default() -> Self {
         Self {
             hotkey: default_hotkey(),
-            hotkey_hold_time: default_hold_time(),
+            trigger_mode: TriggerMode::default(),
             language: default_language(),
+            multi_language: None,
             streaming_mode: default_streaming_mode(),
             audio_device: None,
+            extra_audio_devices: Vec::new(),
             audio_gain: default_audio_gain(),
             sound_notification: default_sound_notification(),
+            noise_gate_threshold: default_noise_gate_threshold(),
+            audio_buffer_size: None,
+            idle_release_delay_ms: default_idle_release_delay_ms(),
+            pre_recording_delay_ms: default_pre_recording_delay_ms(),
+            paste_delay_ms: default_paste_delay_ms(),
+            echo_cancellation: false,
+            vad_mode: false,
+            vad_silence_ms: default_vad_silence_ms(),
+            audio_compression: AudioCompression::default(),
+            paste_mode: PasteMode::default(),
+            output_mode: OutputMode::default(),
+            force_clipboard_for_unicode: default_force_clipboard_for_unicode(),
+            preview_mode: false,
+            itn_enabled: default_itn_enabled(),
+            punctuation_enabled: default_punctuation_enabled(),
+        }
+    }
+}
+
+/// Codec recordings are compressed with before upload to engines that accept it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioCompression {
+    /// Send WAV
+    #[default]
+    None,
+    /// Ogg/Opus; needs the `opus` feature
+    Opus,
+    /// Not implemented yet; recordings are sent as WAV
+    Mp3,
+}
+
+/// What pasted text does to a selection in the focused application
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMode {
+    /// Paste over the selection, if any
+    #[default]
+    Replace,
+    /// Press End to drop the selection first, and restore the clipboard afterwards
+    InsertAtCursor,
+}
+
+/// What happens to recognized text once it is on the clipboard
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// Paste into the focused application right away
+    #[default]
+    PasteImmediately,
+    /// Only put the text on the clipboard
+    CopyOnly,
+    /// Wait `delay_ms` before pasting, so the user can switch focus
+    PasteAfterDelay { delay_ms: u64 },
+}
+
+/// How presses of the hotkey start and stop recording
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerMode {
+    /// Record once the key has been held for `hold_time_secs`, until it is released
+    Hold { hold_time_secs: f64 },
+    /// Two presses within `max_interval_ms` start recording; two more stop it
+    DoubleTap { max_interval_ms: u64 },
+    /// Record from the moment the key is pressed until it is released
+    PushToTalk,
+}
+
+impl Default for TriggerMode {
+    fn default() -> Self {
+        TriggerMode::Hold {
+            hold_time_secs: 1.0,
+        }
+    }
+}
+
+/// Additional hotkey that records in a specific language
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyProfile {
+    pub hotkey: String,
+    pub language: String,
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Get the label (or hotkey, when unlabeled) of the profile in `existing_profiles`
+/// already bound to `hotkey`
+pub fn detect_hotkey_conflict(hotkey: &str, existing_profiles: &[HotkeyProfile]) -> Option<String> {
+    existing_profiles
+        .iter()
+        .find(|profile| profile.hotkey.eq_ignore_ascii_case(hotkey))
+        .map(|profile| {
+            if profile.label.is_empty() {
+                profile.hotkey.clone()
+            } else {
+                profile.label.clone()
+            }
+        })
+}
+
+/// Step applied to recognized text before it is pasted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextTransform {
+    Lowercase,
+    Uppercase,
+    TitleCase,
+    Trim,
+    CollapseWhitespace,
+    RegexReplace {
+        pattern: String,
+        replacement: String,
+    },
+}
+
+/// Layout of entries appended to transcript files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    PlainText,
+    Markdown,
+    Csv,
+}
+
+/// Append every recognized text to a dated transcript file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoExportConfig {
+    pub directory: PathBuf,
+    /// File name; `{date}` is replaced with the local date (YYYY-MM-DD)
+    #[serde(default = "default_export_filename_pattern")]
+    pub filename_pattern: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+fn default_export_filename_pattern() -> String {
+    "speaky_{date}.txt".to_string()
+}
+
+/// Words masked in recognized text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfanityFilterConfig {
+    #[serde(default)]
+    pub wordlist: Vec<String>,
+    #[serde(default = "default_profanity_replacement")]
+    pub replacement: String,
+}
+
+fn default_profanity_replacement() -> String {
+    "***".to_string()
+}
+
+impl Default for ProfanityFilterConfig {
+    fn default() -> Self {
+        Self {
+            wordlist: Vec::new(),
+            replacement: default_profanity_replacement(),
         }
     }
 }
@@ -60,15 +317,62 @@ impl Default for AsrConfig {
 pub struct CoreConfig {
     #[serde(default)]
     pub asr: AsrConfig,
+    #[serde(default)]
+    pub extra_hotkeys: Vec<HotkeyProfile>,
+    #[serde(default)]
+    pub transforms: Vec<TextTransform>,
+    #[serde(default)]
+    pub auto_export: Option<AutoExportConfig>,
+    #[serde(default)]
+    pub profanity_filter: Option<ProfanityFilterConfig>,
+    /// Shell command that recognized text is piped through after the transforms
+    #[serde(default)]
+    pub post_process_command: Option<String>,
 }
 
 /// Volcengine BigModel configuration
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolcBigModelConfig {
     #[serde(default)]
     pub app_key: String,
     #[serde(default)]
     pub access_key: String,
+    /// Audio sent per WebSocket message; larger segments suit slow connections
+    #[serde(default = "default_volc_segment_duration_ms")]
+    pub volc_segment_duration_ms: u32,
+    /// "bigmodel", "bigmodel_flash" (faster, cheaper) or "bigmodel_pro" (more accurate)
+    #[serde(default = "default_volc_model")]
+    pub volc_model: String,
+    /// WebSocket endpoint of a private deployment, instead of the public one
+    #[serde(default)]
+    pub volc_ws_url: Option<String>,
+    /// Ask for speaker labels and emit them in `verbose-result` events
+    #[serde(default)]
+    pub speaker_labels: bool,
+    /// Ask for the top interpretations and emit them in `nbest-result` events
+    #[serde(default)]
+    pub show_alternatives: bool,
+}
+
+fn default_volc_segment_duration_ms() -> u32 {
+    200
+}
+fn default_volc_model() -> String {
+    "bigmodel".to_string()
+}
+
+impl Default for VolcBigModelConfig {
+    fn default() -> Self {
+        Self {
+            app_key: String::new(),
+            access_key: String::new(),
+            volc_segment_duration_ms: default_volc_segment_duration_ms(),
+            volc_model: default_volc_model(),
+            volc_ws_url: None,
+            speaker_labels: false,
+            show_alternatives: false,
+        }
+    }
 }
 
 /// OpenAI configuration
@@ -80,6 +384,9 @@ pub struct OpenAIConfig {
     pub model: String,
     #[serde(default = "default_openai_base_url")]
     pub base_url: String,
+    /// Request word timestamps (`verbose_json`); only `whisper-1` returns them
+    #[serde(default)]
+    pub include_word_timestamps: bool,
 }
 
 fn default_openai_model() -> String {
@@ -95,6 +402,128 @@ impl Default for OpenAIConfig {
             api_key: String::new(),
             model: default_openai_model(),
             base_url: default_openai_base_url(),
+            include_word_timestamps: false,
+        }
+    }
+}
+
+/// Google Cloud Speech-to-Text configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleSpeechConfig {
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_google_model")]
+    pub model: String,
+}
+
+fn default_google_model() -> String {
+    "default".to_string()
+}
+
+impl Default for GoogleSpeechConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model: default_google_model(),
+        }
+    }
+}
+
+/// Azure Cognitive Services Speech configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AzureSpeechConfig {
+    #[serde(default)]
+    pub subscription_key: String,
+    #[serde(default)]
+    pub region: String,
+}
+
+/// Deepgram configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepgramConfig {
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_deepgram_model")]
+    pub model: String,
+    #[serde(default)]
+    pub tier: String,
+}
+
+fn default_deepgram_model() -> String {
+    "nova-2".to_string()
+}
+
+impl Default for DeepgramConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model: default_deepgram_model(),
+            tier: String::new(),
+        }
+    }
+}
+
+/// Local Whisper (whisper.cpp) configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalWhisperConfig {
+    #[serde(default)]
+    pub model_path: String,
+    #[serde(default)]
+    pub use_gpu: bool,
+    #[serde(default = "default_whisper_threads")]
+    pub n_threads: u32,
+}
+
+fn default_whisper_threads() -> u32 {
+    4
+}
+
+impl Default for LocalWhisperConfig {
+    fn default() -> Self {
+        Self {
+            model_path: String::new(),
+            use_gpu: false,
+            n_threads: default_whisper_threads(),
+        }
+    }
+}
+
+/// Vosk offline engine configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoskConfig {
+    /// Unpacked model directory, e.g. vosk-model-small-en-us-0.15
+    #[serde(default)]
+    pub model_path: String,
+}
+
+/// Retry policy for transient engine errors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total attempts including the first one
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_retry_initial_delay_ms")]
+    pub initial_delay_ms: u64,
+    #[serde(default = "default_retry_backoff_factor")]
+    pub backoff_factor: f64,
+}
+
+fn default_retry_max_attempts() -> u32 {
+    3
+}
+fn default_retry_initial_delay_ms() -> u64 {
+    500
+}
+fn default_retry_backoff_factor() -> f64 {
+    2.0
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            initial_delay_ms: default_retry_initial_delay_ms(),
+            backoff_factor: default_retry_backoff_factor(),
         }
     }
 }
@@ -108,6 +537,49 @@ pub struct EngineConfig {
     pub volc_bigmodel: VolcBigModelConfig,
     #[serde(default)]
     pub openai: OpenAIConfig,
+    #[serde(default)]
+    pub local_whisper: LocalWhisperConfig,
+    #[serde(default)]
+    pub vosk: VoskConfig,
+    #[serde(default)]
+    pub google_speech: GoogleSpeechConfig,
+    #[serde(default)]
+    pub azure_speech: AzureSpeechConfig,
+    #[serde(default)]
+    pub deepgram: DeepgramConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Proxy for the cloud engines
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    /// Recognitions starting sooner than this after the previous one are skipped
+    #[serde(default = "default_min_interval_between_calls_ms")]
+    pub min_interval_between_calls_ms: u64,
+    /// Answer with fixed text instead of calling `current`; needs the `dry-run` feature
+    #[serde(default)]
+    pub dry_run: bool,
+    /// How long the dry-run engine takes to answer
+    #[serde(default = "default_fake_latency_ms")]
+    pub fake_latency_ms: u64,
+}
+
+/// HTTP proxy used to reach the cloud engines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// e.g. `http://proxy.example.com:8080`
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+fn default_min_interval_between_calls_ms() -> u64 {
+    500
+}
+
+fn default_fake_latency_ms() -> u64 {
+    500
 }
 
 fn default_engine() -> String {
@@ -120,6 +592,16 @@ impl Default for EngineConfig {
             current: default_engine(),
             volc_bigmodel: VolcBigModelConfig::default(),
             openai: OpenAIConfig::default(),
+            local_whisper: LocalWhisperConfig::default(),
+            vosk: VoskConfig::default(),
+            google_speech: GoogleSpeechConfig::default(),
+            azure_speech: AzureSpeechConfig::default(),
+            deepgram: DeepgramConfig::default(),
+            retry: RetryConfig::default(),
+            proxy: None,
+            min_interval_between_calls_ms: default_min_interval_between_calls_ms(),
+            dry_run: false,
+            fake_latency_ms: default_fake_latency_ms(),
         }
     }
 }
@@ -135,6 +617,73 @@ pub struct AppearanceConfig {
     pub show_waveform: bool,
     #[serde(default = "default_window_opacity")]
     pub window_opacity: f64,
+    /// WAV files replacing the built-in chimes, keyed by "start_recording",
+    /// "stop_recording" or "error"
+    #[serde(default)]
+    pub notification_sounds: Option<HashMap<String, PathBuf>>,
+    /// Start without a tray icon, for kiosk setups driven through commands
+    #[serde(default)]
+    pub run_in_background: bool,
+    /// Read recognized text back aloud
+    #[serde(default)]
+    pub tts: Option<TtsConfig>,
+    #[serde(default)]
+    pub tray_icon_set: TrayIconSet,
+}
+
+/// PNG tray icons for each recognition state; an empty path uses the built-in icon
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrayIconSet {
+    #[serde(default)]
+    pub idle: PathBuf,
+    #[serde(default)]
+    pub recording: PathBuf,
+    #[serde(default)]
+    pub processing: PathBuf,
+}
+
+/// Speech synthesizer used to read text back
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsEngine {
+    /// espeak on Linux, `say` on macOS, System.Speech on Windows
+    #[default]
+    SystemTts,
+    ElevenLabs,
+    /// A Coqui `tts-server`
+    Coqui,
+}
+
+/// Text-to-speech settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsConfig {
+    #[serde(default)]
+    pub engine: TtsEngine,
+    /// Voice name for the system synthesizer, voice ID for ElevenLabs or
+    /// speaker ID for Coqui; empty uses the default voice
+    #[serde(default)]
+    pub voice: String,
+    /// ElevenLabs API key
+    #[serde(default)]
+    pub api_key: String,
+    /// Base URL of the Coqui server
+    #[serde(default = "default_coqui_url")]
+    pub server_url: String,
+}
+
+fn default_coqui_url() -> String {
+    "http://localhost:5002".to_string()
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            engine: TtsEngine::default(),
+            voice: String::new(),
+            api_key: String::new(),
+            server_url: default_coqui_url(),
+        }
+    }
 }
 
 fn default_theme() -> String {
@@ -157,19 +706,155 @@ impl Default for AppearanceConfig {
             ui_language: default_ui_language(),
             show_waveform: default_show_waveform(),
             window_opacity: default_window_opacity(),
+            notification_sounds: None,
+            run_in_background: false,
+            tts: None,
+            tray_icon_set: TrayIconSet::default(),
         }
     }
 }
 
-/// Main configuration struct
+/// Vocabulary hints applied when dictating into a specific application
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppVocabHint {
+    #[serde(default)]
+    pub app_wm_class: String,
+    #[serde(default)]
+    pub vocab: Vec<String>,
+}
+
+/// How recognized text is delivered to the focused application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteStrategy {
+    /// Copy to the clipboard and press Ctrl+V
+    ClipboardCtrlV,
+    /// Copy to the clipboard and press Cmd+V (Super+V outside macOS)
+    ClipboardCmdV,
+    /// Type the text with `xdotool type` (X11 only)
+    XdotoolType,
+    /// Type the text as synthetic key events without touching the clipboard
+    DirectInput,
+}
+
+impl Default for PasteStrategy {
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            PasteStrategy::ClipboardCmdV
+        } else {
+            PasteStrategy::ClipboardCtrlV
+        }
+    }
+}
+
+/// Paste strategy for applications whose WM_CLASS matches a regex
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppPasteOverride {
+    /// Case-insensitive regex matched against WM_CLASS and WM_INSTANCE
+    pub wm_class_pattern: String,
+    #[serde(default)]
+    pub strategy: PasteStrategy,
+    /// Delay between writing the clipboard and pressing the paste keys
+    #[serde(default = "default_paste_delay_ms")]
+    pub delay_ms: u64,
+}
+
+/// Default clipboard settle delay
+pub const DEFAULT_PASTE_DELAY_MS: u64 = 50;
+
+fn default_paste_delay_ms() -> u64 {
+    DEFAULT_PASTE_DELAY_MS
+}
+
+/// HTTP endpoint that transcribes WAV uploads from other devices on the LAN.
+/// Only this machine can reach it until `bind_addr` is set to e.g. `0.0.0.0`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanServerConfig {
+    #[serde(default = "default_lan_bind_addr")]
+    pub bind_addr: String,
+    #[serde(default = "default_lan_port")]
+    pub port: u16,
+    /// Clients send it as `Authorization: Bearer {auth_token}`
+    pub auth_token: String,
+}
+
+fn default_lan_bind_addr() -> String {
+    "127.0.0.1".to_string()
+}
+fn default_lan_port() -> u16 {
+    8765
+}
+
+/// WebSocket endpoint that streams recognition events to programmatic clients
+/// and lets them start and stop recording; authenticates with `lan_server.auth_token`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsServerConfig {
+    #[serde(default = "default_lan_bind_addr")]
+    pub bind_addr: String,
+    #[serde(default = "default_ws_port")]
+    pub port: u16,
+}
+
+fn default_ws_port() -> u16 {
+    8766
+}
+
+/// Directory whose new audio files are transcribed into `output_dir`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchDirConfig {
+    pub path: PathBuf,
+    /// File name glob; `*` and `?` are supported
+    #[serde(default = "default_watch_pattern")]
+    pub pattern: String,
+    pub output_dir: PathBuf,
+}
+
+fn default_watch_pattern() -> String {
+    "*.wav".to_string()
+}
+
+/// Main configuration struct
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Schema version; files written before versioning are treated as 1
+    #[serde(default = "default_config_version")]
+    pub version: u32,
     #[serde(default)]
     pub core: CoreConfig,
     #[serde(default)]
     pub engine: EngineConfig,
     #[serde(default)]
     pub appearance: AppearanceConfig,
+    #[serde(default)]
+    pub app_vocab_hints: Vec<AppVocabHint>,
+    #[serde(default)]
+    pub app_paste_overrides: Vec<AppPasteOverride>,
+    #[serde(default)]
+    pub lan_server: Option<LanServerConfig>,
+    #[serde(default)]
+    pub ws_server: Option<WsServerConfig>,
+    #[serde(default)]
+    pub watch_directory: Option<WatchDirConfig>,
+}
+
+fn default_config_version() -> u32 {
+    1
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_CONFIG_VERSION,
+            core: CoreConfig::default(),
+            engine: EngineConfig::default(),
+            appearance: AppearanceConfig::default(),
+            app_vocab_hints: Vec::new(),
+            app_paste_overrides: Vec::new(),
+            lan_server: None,
+            ws_server: None,
+            watch_directory: None,
+        }
+    }
 }
 
 impl Config {
@@ -185,7 +870,12 @@ impl Config {
         Self::config_dir().join("config.yaml")
     }
 
-    /// Load configuration from file
+    /// Get the path of the binary cache of the config file
+    pub fn binary_cache_path() -> PathBuf {
+        Self::config_dir().join("config.bin")
+    }
+
+    /// Load configuration from file, using the binary cache when it is newer
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let path = Self::config_path();
         info!("Loading config from {:?}", path);
@@ -195,12 +885,347 @@ impl Config {
             return Ok(Self::default());
         }
 
+        if Self::binary_cache_is_fresh(&path) {
+            match Self::load_binary() {
+                Ok(config) => {
+                    info!("Config loaded from the binary cache");
+                    return Ok(config);
+                }
+                Err(e) => warn!("Ignoring the config cache: {}", e),
+            }
+        }
+
         let content = fs::read_to_string(&path)?;
-        let config: Config = serde_yaml::from_str(&content)?;
+        let (config, migrated) = Self::parse(&content)?;
         info!("Config loaded successfully");
+
+        // Write the upgraded file back so stale fields don't linger on disk
+        if migrated {
+            config.save()?;
+        } else if let Err(e) = config.save_binary() {
+            warn!("Failed to write the config cache: {}", e);
+        }
         Ok(config)
     }
 
+    /// Whether the binary cache was written after the YAML file was last changed
+    fn binary_cache_is_fresh(yaml_path: &std::path::Path) -> bool {
+        let modified = |path: &std::path::Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        match (modified(&Self::binary_cache_path()), modified(yaml_path)) {
+            (Some(cache), Some(yaml)) => cache > yaml,
+            _ => false,
+        }
+    }
+
+    /// Load the binary cache written by `save_binary`
+    pub fn load_binary() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::decode_binary(&fs::read(Self::binary_cache_path())?)
+    }
+
+    /// Write the binary cache read by `load` instead of parsing YAML
+    pub fn save_binary(&self) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(Self::config_dir())?;
+        fs::write(Self::binary_cache_path(), self.encode_binary()?)?;
+        Ok(())
+    }
+
+    /// Delete the binary cache, so the next load parses the YAML file
+    pub fn reset_binary_cache() -> std::io::Result<()> {
+        match fs::remove_file(Self::binary_cache_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// bincode is not self-describing, so the cache is tagged with the config
+    /// version and schema hash and ignored when another layout wrote it
+    fn encode_binary(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(bincode::serialize(&(
+            (CURRENT_CONFIG_VERSION, *SCHEMA_HASH),
+            self,
+        ))?)
+    }
+
+    fn decode_binary(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let (version, schema): (u32, u64) = bincode::deserialize(data)?;
+        if version != CURRENT_CONFIG_VERSION || schema != *SCHEMA_HASH {
+            return Err(format!(
+                "cache was written for config version {} with schema {:x}",
+                version, schema
+            )
+            .into());
+        }
+        let (_, config): ((u32, u64), Self) = bincode::deserialize(data)?;
+        Ok(config)
+    }
+
+    /// Parse YAML, migrating older schema versions; also reports whether a
+    /// migration ran
+    pub fn parse(content: &str) -> Result<(Self, bool), Box<dyn std::error::Error>> {
+        let mut doc: serde_yaml::Value = serde_yaml::from_str(content)?;
+        if doc.is_null() {
+            doc = serde_yaml::Value::Mapping(Default::default());
+        }
+
+        let version = doc
+            .get("version")
+            .and_then(serde_yaml::Value::as_u64)
+            .map(|v| v as u32)
+            .unwrap_or_else(default_config_version);
+
+        let migrated = version < CURRENT_CONFIG_VERSION;
+        if migrated {
+            info!(
+                "Migrating config from version {} to {}",
+                version, CURRENT_CONFIG_VERSION
+            );
+            migrate::migrate(&mut doc, version);
+        }
+
+        let mut config: Config = serde_yaml::from_value(doc)?;
+        if migrated {
+            config.version = CURRENT_CONFIG_VERSION;
+        }
+        Ok((config, migrated))
+    }
+
+    /// Check value ranges and that the active engine has its credentials,
+    /// returning every violation found
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let asr = &self.core.asr;
+
+        match asr.trigger_mode {
+            TriggerMode::Hold { hold_time_secs } => {
+                if !(0.1..=10.0).contains(&hold_time_secs) {
+                    errors.push(format!(
+                        "hold_time_secs must be between 0.1 and 10.0, got {}",
+                        hold_time_secs
+                    ));
+                }
+            }
+            TriggerMode::DoubleTap { max_interval_ms } => {
+                if !(100..=2000).contains(&max_interval_ms) {
+                    errors.push(format!(
+                        "max_interval_ms must be between 100 and 2000, got {}",
+                        max_interval_ms
+                    ));
+                }
+            }
+            TriggerMode::PushToTalk => {}
+        }
+        if let OutputMode::PasteAfterDelay { delay_ms } = asr.output_mode {
+            if delay_ms > 10_000 {
+                errors.push(format!(
+                    "output_mode delay_ms must be at most 10000, got {}",
+                    delay_ms
+                ));
+            }
+        }
+        if !(0.1..=5.0).contains(&asr.audio_gain) {
+            errors.push(format!(
+                "audio_gain must be between 0.1 and 5.0, got {}",
+                asr.audio_gain
+            ));
+        }
+        if asr.pre_recording_delay_ms > 2000 {
+            errors.push(format!(
+                "pre_recording_delay_ms must be at most 2000, got {}",
+                asr.pre_recording_delay_ms
+            ));
+        }
+        if asr.paste_delay_ms > 500 {
+            errors.push(format!(
+                "paste_delay_ms must be at most 500, got {}",
+                asr.paste_delay_ms
+            ));
+        }
+        if let Some(size) = asr.audio_buffer_size {
+            if !size.is_power_of_two() || !(64..=8192).contains(&size) {
+                errors.push(format!(
+                    "audio_buffer_size must be a power of two between 64 and 8192, got {}",
+                    size
+                ));
+            }
+        }
+        if !(0.1..=1.0).contains(&self.appearance.window_opacity) {
+            errors.push(format!(
+                "window_opacity must be between 0.1 and 1.0, got {}",
+                self.appearance.window_opacity
+            ));
+        }
+        if self.engine.retry.max_attempts == 0 {
+            errors.push("retry.max_attempts must be at least 1".to_string());
+        }
+        if self.engine.retry.backoff_factor < 1.0 {
+            errors.push(format!(
+                "retry.backoff_factor must be at least 1.0, got {}",
+                self.engine.retry.backoff_factor
+            ));
+        }
+        let segment_ms = self.engine.volc_bigmodel.volc_segment_duration_ms;
+        if !(50..=2000).contains(&segment_ms) {
+            errors.push(format!(
+                "volc_segment_duration_ms must be between 50 and 2000, got {}",
+                segment_ms
+            ));
+        }
+        let volc_model = &self.engine.volc_bigmodel.volc_model;
+        if !crate::engines::VOLC_MODELS.contains(&volc_model.as_str()) {
+            errors.push(format!(
+                "volc_model must be one of {}, got {:?}",
+                crate::engines::VOLC_MODELS.join(", "),
+                volc_model
+            ));
+        }
+        if let Some(ref url) = self.engine.volc_bigmodel.volc_ws_url {
+            match reqwest::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "ws" | "wss") => {}
+                Ok(parsed) => errors.push(format!(
+                    "volc_ws_url must be a ws:// or wss:// URL, got {}://",
+                    parsed.scheme()
+                )),
+                Err(e) => errors.push(format!("volc_ws_url {:?} is invalid: {}", url, e)),
+            }
+        }
+        if let Some(ref tts) = self.appearance.tts {
+            if tts.engine == TtsEngine::ElevenLabs
+                && (tts.api_key.is_empty() || tts.voice.is_empty())
+            {
+                errors.push("ElevenLabs TTS needs an api_key and a voice ID".to_string());
+            }
+        }
+        if let Some(ref server) = self.lan_server {
+            if server.auth_token.is_empty() {
+                errors.push("lan_server.auth_token must not be empty".to_string());
+            }
+        }
+        let has_auth_token = self
+            .lan_server
+            .as_ref()
+            .is_some_and(|server| !server.auth_token.is_empty());
+        if self.ws_server.is_some() && !has_auth_token {
+            errors.push("ws_server requires lan_server.auth_token to be set".to_string());
+        }
+        if let Some(ref watch) = self.watch_directory {
+            if watch.pattern.is_empty() {
+                errors.push("watch_directory.pattern must not be empty".to_string());
+            }
+        }
+        if let Some(ref proxy) = self.engine.proxy {
+            if let Err(e) = reqwest::Url::parse(&proxy.url) {
+                errors.push(format!("proxy.url {:?} is invalid: {}", proxy.url, e));
+            }
+        }
+        if let Some(name) = detect_hotkey_conflict(&asr.hotkey, &self.core.extra_hotkeys) {
+            errors.push(format!(
+                "hotkey {} is also used by profile {:?}",
+                asr.hotkey, name
+            ));
+        }
+        for (i, profile) in self.core.extra_hotkeys.iter().enumerate() {
+            let earlier = &self.core.extra_hotkeys[..i];
+            if let Some(name) = detect_hotkey_conflict(&profile.hotkey, earlier) {
+                errors.push(format!(
+                    "hotkey {} of profile {:?} is also used by profile {:?}",
+                    profile.hotkey, profile.label, name
+                ));
+            }
+        }
+        if asr.language.trim().is_empty() {
+            errors.push("language must not be empty".to_string());
+        }
+        for entry in &self.app_paste_overrides {
+            if let Err(e) = regex::Regex::new(&entry.wm_class_pattern) {
+                errors.push(format!(
+                    "invalid paste override pattern {:?}: {}",
+                    entry.wm_class_pattern, e
+                ));
+            }
+        }
+        for transform in &self.core.transforms {
+            if let TextTransform::RegexReplace { pattern, .. } = transform {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    errors.push(format!("invalid transform pattern {:?}: {}", pattern, e));
+                }
+            }
+        }
+
+        let engine = &self.engine;
+        let required: Vec<(&str, &str)> = match engine.current.as_str() {
+            "volc_bigmodel" => vec![
+                ("app_key", &engine.volc_bigmodel.app_key),
+                ("access_key", &engine.volc_bigmodel.access_key),
+            ],
+            "openai" => vec![("api_key", &engine.openai.api_key)],
+            "google_speech" => vec![("api_key", &engine.google_speech.api_key)],
+            "azure_speech" => vec![
+                ("subscription_key", &engine.azure_speech.subscription_key),
+                ("region", &engine.azure_speech.region),
+            ],
+            "deepgram" => vec![("api_key", &engine.deepgram.api_key)],
+            "local_whisper" => vec![("model_path", &engine.local_whisper.model_path)],
+            "vosk" => vec![("model_path", &engine.vosk.model_path)],
+            other => {
+                errors.push(format!("unknown engine: {}", other));
+                Vec::new()
+            }
+        };
+        for (field, value) in required {
+            if value.trim().is_empty() {
+                errors.push(format!("engine {} requires {}", engine.current, field));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Get the config file's mtime if it differs from the one left by our last save
+    pub fn modified_externally() -> Option<SystemTime> {
+        let last_saved = LAST_SAVED_MTIME.lock();
+        let mtime = fs::metadata(Self::config_path())
+            .and_then(|m| m.modified())
+            .ok()?;
+        if *last_saved == Some(mtime) {
+            None
+        } else {
+            Some(mtime)
+        }
+    }
+
+    /// Get the vocabulary hints for a window, matched case-insensitively on WM_CLASS
+    pub fn vocab_hints_for(&self, wm_class: &str, wm_instance: &str) -> Vec<String> {
+        self.app_vocab_hints
+            .iter()
+            .filter(|hint| {
+                hint.app_wm_class.eq_ignore_ascii_case(wm_class)
+                    || hint.app_wm_class.eq_ignore_ascii_case(wm_instance)
+            })
+            .flat_map(|hint| hint.vocab.iter().cloned())
+            .collect()
+    }
+
+    /// Get the first paste override whose pattern matches the window's WM_CLASS or WM_INSTANCE
+    pub fn paste_override_for(
+        &self,
+        wm_class: &str,
+        wm_instance: &str,
+    ) -> Option<&AppPasteOverride> {
+        self.app_paste_overrides.iter().find(|entry| {
+            match RegexBuilder::new(&entry.wm_class_pattern)
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(re) => re.is_match(wm_class) || re.is_match(wm_instance),
+                Err(_) => false,
+            }
+        })
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let dir = Self::config_dir();
@@ -212,8 +1237,104 @@ impl Config {
         info!("Saving config to {:?}", path);
 
         let content = serde_yaml::to_string(self)?;
-        fs::write(&path, content)?;
+        {
+            let mut last_saved = LAST_SAVED_MTIME.lock();
+            fs::write(&path, content)?;
+            *last_saved = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        }
+        if let Err(e) = self.save_binary() {
+            warn!("Failed to write the config cache: {}", e);
+        }
         info!("Config saved successfully");
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(hotkey: &str, label: &str) -> HotkeyProfile {
+        HotkeyProfile {
+            hotkey: hotkey.to_string(),
+            language: "en".to_string(),
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn hotkey_conflict_names_the_profile() {
+        let profiles = vec![profile("f8", "English"), profile("alt_r", "")];
+
+        assert_eq!(
+            detect_hotkey_conflict("F8", &profiles),
+            Some("English".to_string())
+        );
+        assert_eq!(
+            detect_hotkey_conflict("alt_r", &profiles),
+            Some("alt_r".to_string())
+        );
+        assert_eq!(detect_hotkey_conflict("ctrl", &profiles), None);
+    }
+
+    #[test]
+    fn validate_rejects_primary_hotkey_used_by_profile() {
+        let mut config = Config::default();
+        config.engine.current = "openai".to_string();
+        config.engine.openai.api_key = "sk-test".to_string();
+        assert!(config.validate().is_ok());
+
+        config.core.extra_hotkeys.push(profile("ctrl", "Work"));
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors, vec!["hotkey ctrl is also used by profile \"Work\""]);
+    }
+
+    #[test]
+    fn validate_rejects_http_volc_ws_url() {
+        let mut config = Config::default();
+        config.engine.volc_bigmodel.app_key = "app".to_string();
+        config.engine.volc_bigmodel.access_key = "key".to_string();
+        config.engine.volc_bigmodel.volc_ws_url = Some("wss://asr.example.com/ws".to_string());
+        assert!(config.validate().is_ok());
+
+        config.engine.volc_bigmodel.volc_ws_url = Some("http://asr.example.com/ws".to_string());
+        let errors = config.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["volc_ws_url must be a ws:// or wss:// URL, got http://"]
+        );
+    }
+
+    #[test]
+    fn validate_rejects_odd_audio_buffer_size() {
+        let mut config = Config::default();
+        config.engine.current = "openai".to_string();
+        config.engine.openai.api_key = "sk-test".to_string();
+        config.core.asr.audio_buffer_size = Some(256);
+        assert!(config.validate().is_ok());
+
+        for size in [32, 300, 16384] {
+            config.core.asr.audio_buffer_size = Some(size);
+            assert!(config.validate().is_err(), "{} should be rejected", size);
+        }
+    }
+
+    #[test]
+    fn binary_cache_round_trips() {
+        let mut config = Config::default();
+        config.core.asr.language = "en".to_string();
+        config.core.asr.multi_language = Some(vec!["en".to_string(), "zh".to_string()]);
+
+        let decoded = Config::decode_binary(&config.encode_binary().unwrap()).unwrap();
+        assert_eq!(
+            serde_yaml::to_string(&decoded).unwrap(),
+            serde_yaml::to_string(&config).unwrap()
+        );
+
+        let old_version =
+            bincode::serialize(&((CURRENT_CONFIG_VERSION - 1, *SCHEMA_HASH), &config));
+        assert!(Config::decode_binary(&old_version.unwrap()).is_err());
+        let old_schema = bincode::serialize(&((CURRENT_CONFIG_VERSION, !*SCHEMA_HASH), &config));
+        assert!(Config::decode_binary(&old_schema.unwrap()).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Named copies of the configuration, stored next to config.yaml as
+//! `profile_{name}.yaml`.
+
+use log::info;
+use std::fs;
+use std::path::PathBuf;
+
+use super::Config;
+
+const PROFILE_PREFIX: &str = "profile_";
+const PROFILE_SUFFIX: &str = ".yaml";
+
+/// Save `config` as the profile `name`, replacing an existing one
+pub fn save_profile(config: &Config, name: &str) -> Result<(), String> {
+    let path = profile_path(name)?;
+    fs::create_dir_all(Config::config_dir()).map_err(|e| e.to_string())?;
+
+    let content = serde_yaml::to_string(config).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    info!("Saved profile {:?} to {:?}", name, path);
+    Ok(())
+}
+
+/// Read the profile `name`, migrating it if it was saved by an older version
+pub fn load_profile(name: &str) -> Result<Config, String> {
+    let path = profile_path(name)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read profile {:?}: {}", name, e))?;
+    let (config, _) =
+        Config::parse(&content).map_err(|e| format!("Invalid profile {:?}: {}", name, e))?;
+    Ok(config)
+}
+
+/// Names of all saved profiles, sorted
+pub fn list_profiles() -> Vec<String> {
+    let entries = match fs::read_dir(Config::config_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name().into_string().ok()?;
+            let name = file_name
+                .strip_prefix(PROFILE_PREFIX)?
+                .strip_suffix(PROFILE_SUFFIX)?;
+            is_valid_name(name).then(|| name.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Remove the profile `name`
+pub fn delete_profile(name: &str) -> Result<(), String> {
+    let path = profile_path(name)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete profile {:?}: {}", name, e))?;
+    info!("Deleted profile {:?}", name);
+    Ok(())
+}
+
+fn profile_path(name: &str) -> Result<PathBuf, String> {
+    if !is_valid_name(name) {
+        return Err(format!(
+            "Invalid profile name {:?}: use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(Config::config_dir().join(format!("{}{}{}", PROFILE_PREFIX, name, PROFILE_SUFFIX)))
+}
+
+/// Names end up in file names, so keep them to a portable character set
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_names_cannot_escape_config_dir() {
+        assert!(is_valid_name("work"));
+        assert!(is_valid_name("meeting-notes_2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../config"));
+        assert!(!is_valid_name("a/b"));
+        assert!(profile_path("..").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use log::{error, info, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+use super::Config;
+
+/// Watch the config file and apply external edits while the app is running.
+///
+/// The returned watcher must be kept alive for events to be delivered.
+pub fn spawn_config_watcher() -> Option<RecommendedWatcher> {
+    let dir = Config::config_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("Failed to create config dir {:?}: {}", dir, e);
+        return None;
+    }
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            error!("Failed to create config watcher: {}", e);
+            return None;
+        }
+    };
+
+    // Watch the directory rather than the file so editors that replace the
+    // file on save are still picked up
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        error!("Failed to watch config dir {:?}: {}", dir, e);
+        return None;
+    }
+
+    std::thread::spawn(move || {
+        let path = Config::config_path();
+        let mut last_seen: Option<SystemTime> = None;
+
+        for event in rx {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Config watcher error: {}", e);
+                    continue;
+                }
+            };
+
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_))
+                || !event.paths.iter().any(|p| p == &path)
+            {
+                continue;
+            }
+
+            // Skip our own saves and duplicate events for the same write
+            let Some(mtime) = Config::modified_externally() else {
+                continue;
+            };
+            if last_seen == Some(mtime) {
+                continue;
+            }
+            last_seen = Some(mtime);
+
+            match Config::load() {
+                Ok(config) => {
+                    if let Err(errors) = config.validate() {
+                        warn!("Ignoring invalid config change: {}", errors.join("; "));
+                        continue;
+                    }
+                    info!("Config file changed on disk, reloading");
+                    crate::APP_STATE.apply_config(config);
+                    crate::APP_STATE.emit("config-reloaded", ());
+                }
+                // Editors may trigger events mid-write; the next event retries
+                Err(e) => warn!("Ignoring unreadable config change: {}", e),
+            }
+        }
+    });
+
+    info!("Watching config file for changes");
+    Some(watcher)
+}
@@ -0,0 +1,109 @@
+use super::Engine;
+use log::{error, info};
+
+use crate::config::ProxyConfig;
+
+/// Azure Cognitive Services Speech engine (short audio REST API)
+pub struct AzureSpeechEngine {
+    subscription_key: String,
+    region: String,
+    proxy: Option<ProxyConfig>,
+}
+
+impl AzureSpeechEngine {
+    pub fn new(subscription_key: &str, region: &str) -> Self {
+        Self {
+            subscription_key: subscription_key.to_string(),
+            region: region.trim().to_string(),
+            proxy: None,
+        }
+    }
+
+    /// Send requests through an HTTP proxy
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+impl Engine for AzureSpeechEngine {
+    fn name(&self) -> &str {
+        "Azure Speech"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.subscription_key.is_empty() && !self.region.is_empty()
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        info!(
+            "Starting Azure Speech transcription, region={}",
+            self.region
+        );
+
+        let url = format!(
+            "https://{}.stt.speech.microsoft.com/speech/recognition/conversation/cognitiveservices/v1",
+            self.region
+        );
+        let sample_rate = super::parse_wav_sample_rate(audio_data).unwrap_or(16000);
+        let locale = super::locale_for_language(language);
+
+        let client = super::proxy::blocking_client(self.proxy.as_ref())?;
+        let response = client
+            .post(&url)
+            .query(&[("language", locale.as_str()), ("format", "simple")])
+            .header("Ocp-Apim-Subscription-Key", &self.subscription_key)
+            .header(
+                "Content-Type",
+                format!("audio/wav; codecs=audio/pcm; samplerate={}", sample_rate),
+            )
+            .body(audio_data.to_vec())
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().unwrap_or_default();
+            error!("Azure Speech API error: {} - {}", status, text);
+            return Err(format!("API error: {} - {}", status, text));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        // "NoMatch" means no speech was recognised, which is an empty result
+        let status = json["RecognitionStatus"].as_str().unwrap_or_default();
+        if status != "Success" && status != "NoMatch" {
+            return Err(format!("Recognition failed: {}", status));
+        }
+
+        let text = json["DisplayText"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        info!("Transcription complete: {}", text);
+        Ok(text)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            supported_languages: super::COMMON_LANGUAGES
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            supports_language_detection: false,
+            // The short-audio REST API rejects clips over 60 s
+            max_audio_duration_secs: Some(60),
+            supported_models: Vec::new(),
+        }
+    }
+}
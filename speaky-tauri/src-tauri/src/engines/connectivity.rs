@@ -0,0 +1,117 @@
+//! Quick reachability checks, so credentials can be tried before they are saved.
+
+use log::info;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const VOLCENGINE_HOST: &str = "openspeech.bytedance.com:443";
+
+/// Make the cheapest request `engine_name` accepts with the credentials in
+/// `config` and return the round-trip time in milliseconds
+pub fn check_connectivity(config: &Config, engine_name: &str) -> Result<u64, String> {
+    let started = Instant::now();
+
+    match engine_name {
+        // The streaming API only speaks WebSocket, so a TCP connect is as far
+        // as we go; behind a proxy, any HTTPS response shows the tunnel works
+        "volc_bigmodel" => {
+            let host = volcengine_host(config)?;
+            match config.engine.proxy {
+                Some(_) => send(client(config)?.head(format!("https://{}", host)))?,
+                None => connect_tcp(&host)?,
+            }
+        }
+        "openai" => {
+            let openai = &config.engine.openai;
+            let url = format!("{}/models", openai.base_url.trim_end_matches('/'));
+            send(client(config)?.get(url).bearer_auth(&openai.api_key))?;
+        }
+        "google_speech" => {
+            let key = &config.engine.google_speech.api_key;
+            send(
+                client(config)?
+                    .get("https://speech.googleapis.com/v1/operations")
+                    .query(&[("key", key)]),
+            )?;
+        }
+        "azure_speech" => {
+            let region = &config.engine.azure_speech.region;
+            if region.is_empty() {
+                return Err("Azure region is not set".to_string());
+            }
+            // Exchanging the key for a token is the cheapest authenticated call
+            let url = format!(
+                "https://{}.api.cognitive.microsoft.com/sts/v1.0/issueToken",
+                region
+            );
+            let key = &config.engine.azure_speech.subscription_key;
+            send(
+                client(config)?
+                    .post(url)
+                    .header("Ocp-Apim-Subscription-Key", key)
+                    .header("Content-Length", "0"),
+            )?;
+        }
+        "deepgram" => {
+            let auth = format!("Token {}", config.engine.deepgram.api_key);
+            send(
+                client(config)?
+                    .get("https://api.deepgram.com/v1/projects")
+                    .header("Authorization", auth),
+            )?;
+        }
+        "local_whisper" => return Err("Local Whisper does not use the network".to_string()),
+        "vosk" => return Err("Vosk does not use the network".to_string()),
+        other => return Err(format!("Unknown engine: {}", other)),
+    }
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("{} is reachable ({} ms)", engine_name, elapsed_ms);
+    Ok(elapsed_ms)
+}
+
+/// `host:port` of the configured private deployment, or the public endpoint
+fn volcengine_host(config: &Config) -> Result<String, String> {
+    let Some(ref url) = config.engine.volc_bigmodel.volc_ws_url else {
+        return Ok(VOLCENGINE_HOST.to_string());
+    };
+    let url = reqwest::Url::parse(url).map_err(|e| format!("Invalid volc_ws_url: {}", e))?;
+    let host = url.host_str().ok_or("volc_ws_url has no host")?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    Ok(format!("{}:{}", host, port))
+}
+
+fn connect_tcp(host: &str) -> Result<(), String> {
+    let addr = host
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("No address found for {}", host))?;
+    TcpStream::connect_timeout(&addr, CONNECTIVITY_TIMEOUT)
+        .map_err(|e| format!("Failed to connect to {}: {}", host, e))?;
+    Ok(())
+}
+
+fn client(config: &Config) -> Result<reqwest::blocking::Client, String> {
+    super::proxy::blocking_client_builder(config.engine.proxy.as_ref())?
+        .timeout(CONNECTIVITY_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Any response proves the host is reachable; only authentication failures
+/// are reported, since the endpoints may answer other 4xx codes
+fn send(request: reqwest::blocking::RequestBuilder) -> Result<(), String> {
+    let response = request
+        .send()
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(format!("Credentials rejected: {}", status));
+    }
+    Ok(())
+}
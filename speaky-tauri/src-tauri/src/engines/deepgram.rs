@@ -0,0 +1,227 @@
+use super::Engine;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, error, info};
+use tokio::runtime::Runtime;
+use tokio_tungstenite::{
+    client_async_tls, connect_async,
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, Message},
+};
+
+use crate::config::ProxyConfig;
+
+const DEEPGRAM_HOST: &str = "api.deepgram.com";
+
+/// Size of each PCM chunk sent over the streaming socket (100 ms at 16 kHz)
+const STREAM_CHUNK_BYTES: usize = 3200;
+
+/// Deepgram engine: pre-recorded REST API, or live WebSocket for partial results
+pub struct DeepgramEngine {
+    api_key: String,
+    model: String,
+    tier: String,
+    proxy: Option<ProxyConfig>,
+}
+
+impl DeepgramEngine {
+    pub fn new(api_key: &str, model: &str, tier: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            tier: tier.to_string(),
+            proxy: None,
+        }
+    }
+
+    /// Send requests through an HTTP proxy
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    fn query_params(&self, language: &str) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("model", self.model.clone()),
+            ("language", language.to_string()),
+            ("punctuate", "true".to_string()),
+        ];
+        if !self.tier.is_empty() {
+            params.push(("tier", self.tier.clone()));
+        }
+        params
+    }
+
+    /// Deepgram API keys are sent with the "Token" scheme; "Bearer" is only
+    /// accepted for short-lived access tokens
+    fn auth_header(&self) -> String {
+        format!("Token {}", self.api_key)
+    }
+
+    async fn transcribe_stream(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        callback: super::PartialResultCallback,
+    ) -> Result<String, String> {
+        info!(
+            "Starting Deepgram streaming transcription, model={}",
+            self.model
+        );
+
+        let sample_rate = super::parse_wav_sample_rate(audio_data).unwrap_or(16000);
+        let pcm = super::wav_pcm_data(audio_data).ok_or("Audio is not a WAV file")?;
+
+        let mut params = self.query_params(language);
+        params.push(("encoding", "linear16".to_string()));
+        params.push(("sample_rate", sample_rate.to_string()));
+        params.push(("channels", "1".to_string()));
+        params.push(("interim_results", "true".to_string()));
+        let query = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let mut request = format!("wss://{}/v1/listen?{}", DEEPGRAM_HOST, query)
+            .into_client_request()
+            .map_err(|e| e.to_string())?;
+        request.headers_mut().insert(
+            "Authorization",
+            HeaderValue::from_str(&self.auth_header()).map_err(|e| e.to_string())?,
+        );
+
+        let (ws, _) = match self.proxy {
+            Some(ref proxy) => {
+                let stream = super::proxy::connect_tunnel(proxy, DEEPGRAM_HOST, 443).await?;
+                client_async_tls(request, stream).await
+            }
+            None => connect_async(request).await,
+        }
+        .map_err(|e| format!("Failed to connect: {}", e))?;
+        let (mut sink, mut stream) = ws.split();
+
+        info!("Connected to Deepgram WebSocket");
+
+        // Audio is already recorded, so send it all and then ask the server to flush
+        for chunk in pcm.chunks(STREAM_CHUNK_BYTES) {
+            sink.send(Message::Binary(chunk.to_vec()))
+                .await
+                .map_err(|e| format!("Failed to send audio: {}", e))?;
+        }
+        sink.send(Message::Text(r#"{"type":"CloseStream"}"#.to_string()))
+            .await
+            .map_err(|e| format!("Failed to close stream: {}", e))?;
+
+        // Finalized segments accumulate; interim results replace the tail
+        let mut final_text = String::new();
+
+        while let Some(msg) = stream.next().await {
+            let msg = msg.map_err(|e| format!("Failed to receive: {}", e))?;
+            let Message::Text(data) = msg else {
+                continue;
+            };
+
+            let json: serde_json::Value = match serde_json::from_str(&data) {
+                Ok(json) => json,
+                Err(_) => continue,
+            };
+            if json["type"] != "Results" {
+                debug!("Deepgram message: {}", json["type"]);
+                continue;
+            }
+
+            let transcript = json["channel"]["alternatives"][0]["transcript"]
+                .as_str()
+                .unwrap_or_default();
+            if transcript.is_empty() {
+                continue;
+            }
+
+            if json["is_final"].as_bool().unwrap_or(false) {
+                if !final_text.is_empty() {
+                    final_text.push(' ');
+                }
+                final_text.push_str(transcript);
+                callback(&final_text);
+            } else if final_text.is_empty() {
+                callback(transcript);
+            } else {
+                callback(&format!("{} {}", final_text, transcript));
+            }
+        }
+
+        info!("Transcription complete: {}", final_text);
+        Ok(final_text.trim().to_string())
+    }
+}
+
+impl Engine for DeepgramEngine {
+    fn name(&self) -> &str {
+        "Deepgram"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        info!("Starting Deepgram transcription, model={}", self.model);
+
+        let client = super::proxy::blocking_client(self.proxy.as_ref())?;
+        let response = client
+            .post(format!("https://{}/v1/listen", DEEPGRAM_HOST))
+            .query(&self.query_params(language))
+            .header("Authorization", self.auth_header())
+            .header("Content-Type", "audio/wav")
+            .body(audio_data.to_vec())
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().unwrap_or_default();
+            error!("Deepgram API error: {} - {}", status, text);
+            return Err(format!("API error: {} - {}", status, text));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        let text = json["results"]["channels"][0]["alternatives"][0]["transcript"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        info!("Transcription complete: {}", text);
+        Ok(text)
+    }
+
+    fn transcribe_with_callback(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        callback: super::PartialResultCallback,
+    ) -> Result<String, String> {
+        let rt = Runtime::new().map_err(|e| e.to_string())?;
+        rt.block_on(self.transcribe_stream(audio_data, language, callback))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            supported_languages: super::COMMON_LANGUAGES
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            supports_language_detection: false,
+            max_audio_duration_secs: None,
+            supported_models: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Engine that answers every request with fixed text, for working on the UI
+//! without API keys or network access.
+
+use std::time::Duration;
+
+use super::{Engine, EngineCapabilities};
+
+/// Text returned for every transcription
+pub const DRY_RUN_TEXT: &str = "[dry run transcription]";
+
+/// Returns `DRY_RUN_TEXT` after a fixed delay standing in for network latency
+pub struct DryRunEngine {
+    latency: Duration,
+}
+
+impl DryRunEngine {
+    pub fn new(latency: Duration) -> Self {
+        Self { latency }
+    }
+}
+
+impl Engine for DryRunEngine {
+    fn name(&self) -> &str {
+        "Dry run"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio_data: &[u8], _language: &str) -> Result<String, String> {
+        log::info!(
+            "Dry run: ignoring {} bytes, answering in {:?}",
+            audio_data.len(),
+            self.latency
+        );
+        std::thread::sleep(self.latency);
+        Ok(DRY_RUN_TEXT.to_string())
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn capabilities(&self) -> EngineCapabilities {
+        EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: false,
+            supported_languages: Vec::new(),
+            supports_language_detection: false,
+            max_audio_duration_secs: None,
+            supported_models: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::Engine;
+use base64::Engine as _;
+use log::{error, info};
+
+use crate::config::ProxyConfig;
+
+/// Google Cloud Speech-to-Text (REST v1) engine
+pub struct GoogleSpeechEngine {
+    api_key: String,
+    model: String,
+    url: String,
+    proxy: Option<ProxyConfig>,
+}
+
+impl GoogleSpeechEngine {
+    pub fn new(api_key: &str, model: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            url: "https://speech.googleapis.com/v1/speech:recognize".to_string(),
+            proxy: None,
+        }
+    }
+
+    /// Send requests through an HTTP proxy
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    fn recognize(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+    ) -> Result<String, String> {
+        info!("Starting Google Speech transcription, model={}", self.model);
+
+        let sample_rate = super::parse_wav_sample_rate(audio_data).unwrap_or(16000);
+
+        let mut config = serde_json::json!({
+            "encoding": "LINEAR16",
+            "sampleRateHertz": sample_rate,
+            "languageCode": super::locale_for_language(language),
+            "model": self.model,
+            "enableAutomaticPunctuation": true,
+        });
+        if !hints.is_empty() {
+            config["speechContexts"] = serde_json::json!([{ "phrases": hints }]);
+        }
+
+        let body = serde_json::json!({
+            "config": config,
+            "audio": {
+                "content": base64::engine::general_purpose::STANDARD.encode(audio_data),
+            },
+        });
+
+        let client = super::proxy::blocking_client(self.proxy.as_ref())?;
+        let response = client
+            .post(&self.url)
+            .query(&[("key", &self.api_key)])
+            .json(&body)
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().unwrap_or_default();
+            error!("Google Speech API error: {} - {}", status, text);
+            return Err(format!("API error: {} - {}", status, text));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        // An empty body means no speech was recognised
+        let text = json["results"][0]["alternatives"][0]["transcript"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        info!("Transcription complete: {}", text);
+        Ok(text)
+    }
+}
+
+impl Engine for GoogleSpeechEngine {
+    fn name(&self) -> &str {
+        "Google Speech"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        self.recognize(audio_data, language, &[])
+    }
+
+    fn transcribe_with_hints(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+        _callback: super::PartialResultCallback,
+    ) -> Result<String, String> {
+        self.recognize(audio_data, language, hints)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            supported_languages: super::COMMON_LANGUAGES
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            supports_language_detection: false,
+            // Synchronous recognize is limited to 60 s of audio
+            max_audio_duration_secs: Some(60),
+            supported_models: Vec::new(),
+        }
+    }
+}
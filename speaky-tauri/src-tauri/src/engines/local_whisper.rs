@@ -0,0 +1,119 @@
+use super::Engine;
+use log::{error, info};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+/// Local Whisper engine running GGML models through whisper.cpp
+pub struct LocalWhisperEngine {
+    model_path: PathBuf,
+    use_gpu: bool,
+    n_threads: u32,
+    context: OnceLock<Result<WhisperContext, String>>,
+}
+
+impl LocalWhisperEngine {
+    pub fn new(model_path: PathBuf, use_gpu: bool, n_threads: u32) -> Self {
+        Self {
+            model_path,
+            use_gpu,
+            n_threads: n_threads.max(1),
+            context: OnceLock::new(),
+        }
+    }
+
+    /// Load the model on first use; loading takes seconds and hundreds of MB
+    fn context(&self) -> Result<&WhisperContext, String> {
+        self.context
+            .get_or_init(|| {
+                info!("Loading Whisper model from {:?}", self.model_path);
+                let path = self
+                    .model_path
+                    .to_str()
+                    .ok_or_else(|| "Invalid model path".to_string())?;
+
+                let mut params = WhisperContextParameters::default();
+                params.use_gpu(self.use_gpu);
+
+                WhisperContext::new_with_params(path, params).map_err(|e| {
+                    error!("Failed to load Whisper model: {}", e);
+                    format!("Failed to load model: {}", e)
+                })
+            })
+            .as_ref()
+            .map_err(|e| e.clone())
+    }
+}
+
+impl Engine for LocalWhisperEngine {
+    fn name(&self) -> &str {
+        "Local Whisper"
+    }
+
+    fn is_available(&self) -> bool {
+        self.model_path.is_file()
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        info!(
+            "Starting local Whisper transcription, threads={}, gpu={}",
+            self.n_threads, self.use_gpu
+        );
+
+        let samples: Vec<f32> = super::wav_pcm_data(audio_data)
+            .ok_or("Audio is not a WAV file")?
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect();
+        let context = self.context()?;
+        let mut state = context
+            .create_state()
+            .map_err(|e| format!("Failed to create state: {}", e))?;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_n_threads(self.n_threads as i32);
+        params.set_language(Some(language));
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+
+        state
+            .full(params, &samples)
+            .map_err(|e| format!("Transcription failed: {}", e))?;
+
+        let n_segments = state
+            .full_n_segments()
+            .map_err(|e| format!("Failed to read segments: {}", e))?;
+
+        let mut text = String::new();
+        for i in 0..n_segments {
+            let segment = state
+                .full_get_segment_text(i)
+                .map_err(|e| format!("Failed to read segment {}: {}", i, e))?;
+            text.push_str(&segment);
+        }
+
+        info!("Transcription complete: {}", text.trim());
+        Ok(text.trim().to_string())
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            supported_languages: super::COMMON_LANGUAGES
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            // whisper.cpp detects the language itself when given "auto"
+            supports_language_detection: true,
+            max_audio_duration_secs: None,
+            supported_models: Vec::new(),
+        }
+    }
+}
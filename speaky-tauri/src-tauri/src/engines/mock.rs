@@ -0,0 +1,195 @@
+//! Engine returning preset results, for testing code that drives engines
+//! without calling a real API.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::{
+    Engine, EngineCapabilities, PartialResultCallback, VerboseEngine, VerboseTranscription,
+};
+
+/// Returns the queued responses in order, one per transcription
+pub struct MockEngine {
+    responses: Mutex<VecDeque<Result<String, String>>>,
+    call_count: Arc<AtomicUsize>,
+    streaming: bool,
+    alternatives: bool,
+    verbose: bool,
+}
+
+impl MockEngine {
+    pub fn new(responses: impl IntoIterator<Item = Result<String, String>>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into_iter().collect()),
+            call_count: Arc::new(AtomicUsize::new(0)),
+            streaming: false,
+            alternatives: false,
+            verbose: false,
+        }
+    }
+
+    /// Report support for live streaming sessions
+    pub fn with_streaming(mut self) -> Self {
+        self.streaming = true;
+        self
+    }
+
+    /// Report that `transcribe_nbest` returns alternatives
+    pub fn with_alternatives(mut self) -> Self {
+        self.alternatives = true;
+        self
+    }
+
+    /// Act as a `VerboseEngine`, returning each response without timestamps
+    pub fn with_verbose(mut self) -> Self {
+        self.verbose = true;
+        self
+    }
+
+    /// Counter of transcriptions made, shared so it can be read after the
+    /// engine is moved into a `Box<dyn Engine>`
+    pub fn call_count(&self) -> Arc<AtomicUsize> {
+        self.call_count.clone()
+    }
+}
+
+impl Engine for MockEngine {
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        self.transcribe_with_callback(audio_data, language, Box::new(|_| {}))
+    }
+
+    /// Reports each word-by-word prefix of a successful response as a partial result
+    fn transcribe_with_callback(
+        &self,
+        _audio_data: &[u8],
+        _language: &str,
+        callback: PartialResultCallback,
+    ) -> Result<String, String> {
+        self.call_count.fetch_add(1, Ordering::SeqCst);
+        let text = self
+            .responses
+            .lock()
+            .pop_front()
+            .unwrap_or_else(|| Err("No mock response queued".to_string()))?;
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        for i in 1..words.len() {
+            callback(&words[..i].join(" "));
+        }
+        Ok(text)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    fn supports_alternatives(&self) -> bool {
+        self.alternatives
+    }
+
+    fn as_verbose(&self) -> Option<&dyn VerboseEngine> {
+        if self.verbose {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn capabilities(&self) -> EngineCapabilities {
+        EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.streaming,
+            supported_languages: Vec::new(),
+            supports_language_detection: false,
+            max_audio_duration_secs: None,
+            supported_models: Vec::new(),
+        }
+    }
+}
+
+impl VerboseEngine for MockEngine {
+    fn transcribe_verbose(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        _hints: &[String],
+    ) -> Result<VerboseTranscription, String> {
+        self.transcribe(audio_data, language)
+            .map(|text| VerboseTranscription {
+                text,
+                ..Default::default()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryConfig;
+    use crate::engines::retry_with_backoff;
+
+    fn collect_partials() -> (Arc<Mutex<Vec<String>>>, PartialResultCallback) {
+        let partials = Arc::new(Mutex::new(Vec::new()));
+        let sink = partials.clone();
+        let callback: PartialResultCallback = Box::new(move |text| sink.lock().push(text.into()));
+        (partials, callback)
+    }
+
+    #[test]
+    fn callback_receives_partials_before_final_result() {
+        let engine = MockEngine::new([Ok("hello big world".to_string())]);
+        let (partials, callback) = collect_partials();
+
+        let result = engine.transcribe_with_callback(&[], "en", callback);
+
+        assert_eq!(result.unwrap(), "hello big world");
+        assert_eq!(*partials.lock(), ["hello", "hello big"]);
+    }
+
+    #[test]
+    fn responses_are_returned_in_order() {
+        let engine: Box<dyn Engine> = Box::new(MockEngine::new([
+            Ok("first".to_string()),
+            Err("API error: 401 Unauthorized".to_string()),
+        ]));
+
+        assert_eq!(engine.transcribe(&[], "en").unwrap(), "first");
+        let (partials, callback) = collect_partials();
+        let error = engine.transcribe_with_callback(&[], "en", callback);
+        assert_eq!(error.unwrap_err(), "API error: 401 Unauthorized");
+        assert!(partials.lock().is_empty());
+        assert!(engine.transcribe(&[], "en").is_err());
+    }
+
+    #[test]
+    fn transient_failures_are_retried() {
+        let engine = MockEngine::new([
+            Err("Request failed: connection timed out".to_string()),
+            Err("API error: 503 Service Unavailable".to_string()),
+            Ok("done".to_string()),
+        ]);
+        let calls = engine.call_count();
+        let retry = RetryConfig {
+            max_attempts: 3,
+            initial_delay_ms: 0,
+            backoff_factor: 1.0,
+        };
+
+        let result = retry_with_backoff(&retry, || {
+            engine.transcribe_with_callback(&[], "en", Box::new(|_| {}))
+        });
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,14 +1,148 @@
+mod azure_speech;
+mod connectivity;
+mod deepgram;
+#[cfg(feature = "dry-run")]
+mod dry_run;
+mod google_speech;
+#[cfg(feature = "local-whisper")]
+mod local_whisper;
+#[cfg(test)]
+mod mock;
 mod openai;
+mod proxy;
 mod volcengine;
+#[cfg(feature = "vosk")]
+mod vosk_engine;
 
+pub use azure_speech::AzureSpeechEngine;
+pub use connectivity::check_connectivity;
+pub use deepgram::DeepgramEngine;
+#[cfg(feature = "dry-run")]
+pub use dry_run::DryRunEngine;
+pub use google_speech::GoogleSpeechEngine;
+#[cfg(feature = "local-whisper")]
+pub use local_whisper::LocalWhisperEngine;
+#[cfg(test)]
+pub use mock::MockEngine;
 pub use openai::OpenAIEngine;
-pub use volcengine::VolcBigModelEngine;
+pub use volcengine::{VolcBigModelEngine, VOLC_MODELS};
+#[cfg(feature = "vosk")]
+pub use vosk_engine::VoskEngine;
 
-use crate::config::Config;
+pub use crate::audio::processing::{parse_wav_sample_rate, verify_wav_integrity, wav_pcm_data};
+use crate::config::{Config, RetryConfig};
+use serde::{Deserialize, Serialize};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::mpsc::UnboundedSender;
 
 /// Callback type for partial results
 pub type PartialResultCallback = Box<dyn Fn(&str) + Send + Sync>;
 
+/// Callback type for progress updates (percentage, 0.0 - 100.0)
+pub type ProgressCallback = Box<dyn Fn(f32) + Send + Sync>;
+
+/// App handles an engine may use to report more than its return value
+#[derive(Clone, Default)]
+pub struct EngineContext {
+    /// Used to emit events such as `upload-progress`; `None` outside the app
+    pub app_handle: Option<AppHandle>,
+}
+
+/// `language` value that asks the engine to detect the spoken language
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Language codes accepted by the multilingual cloud and Whisper engines
+pub const COMMON_LANGUAGES: &[&str] = &["zh", "en", "ja", "ko", "de", "fr", "es", "pt", "ru"];
+
+/// What an engine can do, for the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineCapabilities {
+    pub name: String,
+    pub supports_streaming: bool,
+    pub supported_languages: Vec<String>,
+    /// Whether `AUTO_LANGUAGE` is accepted and the language detected from the audio
+    pub supports_language_detection: bool,
+    /// Longest recording the engine accepts in one request, if limited
+    pub max_audio_duration_secs: Option<u32>,
+    /// Model identifiers that can be configured; empty when not selectable
+    pub supported_models: Vec<String>,
+}
+
+/// Word with where it was spoken in the recording, in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTimestamp {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Stretch of speech attributed to one speaker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Utterance {
+    /// Speaker label as reported by the engine; empty when unknown
+    pub speaker: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Language detected for this stretch, when several were allowed
+    pub language: Option<String>,
+}
+
+/// Transcript with per-word timestamps or speaker-labelled utterances,
+/// whichever the engine reports
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerboseTranscription {
+    pub text: String,
+    pub words: Vec<WordTimestamp>,
+    pub utterances: Vec<Utterance>,
+    /// Interpretations of the whole recording, best first, when requested
+    pub alternatives: Vec<String>,
+}
+
+/// Engines that can report when each word was spoken, or by whom
+pub trait VerboseEngine: Engine {
+    fn transcribe_verbose(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+    ) -> Result<VerboseTranscription, String>;
+}
+
+/// Live recognition session fed with audio while recording is still in progress
+pub struct StreamingSession {
+    sender: UnboundedSender<Vec<u8>>,
+    worker: JoinHandle<Result<String, String>>,
+}
+
+impl StreamingSession {
+    /// Wrap the audio channel and worker thread of an engine-specific session
+    pub fn new(
+        sender: UnboundedSender<Vec<u8>>,
+        worker: JoinHandle<Result<String, String>>,
+    ) -> Self {
+        Self { sender, worker }
+    }
+
+    /// Queue a chunk of 16 kHz mono 16-bit little-endian PCM
+    pub fn push_chunk(&mut self, pcm: &[u8]) {
+        if self.sender.send(pcm.to_vec()).is_err() {
+            log::debug!("Streaming session closed, dropping {} bytes", pcm.len());
+        }
+    }
+
+    /// Signal the end of audio and wait for the final transcript
+    pub fn finish(self) -> Result<String, String> {
+        let Self { sender, worker } = self;
+        drop(sender);
+        worker
+            .join()
+            .map_err(|_| "Streaming session panicked".to_string())?
+    }
+}
+
 /// Trait for ASR engines
 pub trait Engine: Send + Sync {
     /// Get engine name
@@ -32,20 +166,137 @@ pub trait Engine: Send + Sync {
         self.transcribe(audio_data, language)
     }
 
+    /// Transcribe with vocabulary hints biasing recognition toward given terms
+    fn transcribe_with_hints(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+        callback: PartialResultCallback,
+    ) -> Result<String, String> {
+        // Default implementation ignores hints
+        let _ = hints;
+        self.transcribe_with_callback(audio_data, language, callback)
+    }
+
+    /// Transcribe with progress reporting as audio is sent
+    fn transcribe_with_progress(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        progress: ProgressCallback,
+    ) -> Result<String, String> {
+        // Default implementation sends audio in one request, so there is no progress
+        let _ = progress;
+        self.transcribe(audio_data, language)
+    }
+
+    /// Transcribe, returning the top interpretations best first; engines
+    /// without alternatives return just the transcript
+    fn transcribe_nbest(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+    ) -> Result<Vec<String>, String> {
+        self.transcribe_with_hints(audio_data, language, hints, Box::new(|_| {}))
+            .map(|text| vec![text])
+    }
+
+    /// Whether `transcribe_nbest` is set up to return more than one interpretation
+    fn supports_alternatives(&self) -> bool {
+        false
+    }
+
     /// Check if engine supports streaming
     fn supports_streaming(&self) -> bool {
         false
     }
+
+    /// Describe streaming support, accepted languages and limits
+    fn capabilities(&self) -> EngineCapabilities;
+
+    /// Whether uploads may be Ogg/Opus (see `audio::compression`) instead of WAV
+    fn accepts_opus(&self) -> bool {
+        false
+    }
+
+    /// This engine as a `VerboseEngine`, when it is set up to report word timestamps
+    fn as_verbose(&self) -> Option<&dyn VerboseEngine> {
+        None
+    }
+
+    /// Language detected by the last transcription made with `AUTO_LANGUAGE`,
+    /// for engines that report it
+    fn detected_language(&self) -> Option<String> {
+        None
+    }
+
+    /// Open a session that recognizes audio as it is pushed, reporting partial results
+    fn start_streaming_session(
+        &self,
+        language: &str,
+        callback: PartialResultCallback,
+    ) -> Result<StreamingSession, String> {
+        let _ = (language, callback);
+        Err(format!("{} does not support live streaming", self.name()))
+    }
+}
+
+/// Expand a short language code ("zh") to the locale cloud APIs expect ("zh-CN")
+pub fn locale_for_language(language: &str) -> String {
+    match language {
+        "zh" => "zh-CN",
+        "en" => "en-US",
+        "ja" => "ja-JP",
+        "ko" => "ko-KR",
+        "de" => "de-DE",
+        "fr" => "fr-FR",
+        "es" => "es-ES",
+        "pt" => "pt-BR",
+        "ru" => "ru-RU",
+        other => other,
+    }
+    .to_string()
 }
 
 /// Create engine based on configuration
-pub fn create_engine(config: &Config) -> Option<Box<dyn Engine + Send + Sync>> {
+pub fn create_engine(
+    config: &Config,
+    context: &EngineContext,
+) -> Option<Box<dyn Engine + Send + Sync>> {
+    if config.engine.dry_run {
+        #[cfg(feature = "dry-run")]
+        return Some(Box::new(DryRunEngine::new(Duration::from_millis(
+            config.engine.fake_latency_ms,
+        ))));
+        #[cfg(not(feature = "dry-run"))]
+        log::warn!(
+            "dry_run requires the `dry-run` feature, using {}",
+            config.engine.current
+        );
+    }
+
     match config.engine.current.as_str() {
         "volc_bigmodel" => {
-            let engine = VolcBigModelEngine::new(
-                &config.engine.volc_bigmodel.app_key,
-                &config.engine.volc_bigmodel.access_key,
-            );
+            let volc = &config.engine.volc_bigmodel;
+            let mut engine = VolcBigModelEngine::new(
+                &volc.app_key,
+                &volc.access_key,
+                volc.volc_segment_duration_ms,
+            )
+            .with_formatting(
+                config.core.asr.itn_enabled,
+                config.core.asr.punctuation_enabled,
+            )
+            .with_model(&volc.volc_model)
+            .with_speaker_labels(volc.speaker_labels)
+            .with_alternatives(volc.show_alternatives)
+            .with_languages(config.core.asr.multi_language.clone().unwrap_or_default())
+            .with_proxy(config.engine.proxy.clone());
+            if let Some(ref url) = volc.volc_ws_url {
+                engine = engine.with_ws_url(url);
+            }
             if engine.is_available() {
                 Some(Box::new(engine))
             } else {
@@ -58,7 +309,12 @@ pub fn create_engine(config: &Config) -> Option<Box<dyn Engine + Send + Sync>> {
                 &config.engine.openai.api_key,
                 &config.engine.openai.model,
                 &config.engine.openai.base_url,
-            );
+            )
+            .with_word_timestamps(config.engine.openai.include_word_timestamps)
+            .with_punctuation(config.core.asr.punctuation_enabled)
+            .with_languages(config.core.asr.multi_language.is_some())
+            .with_proxy(config.engine.proxy.clone())
+            .with_context(context.clone());
             if engine.is_available() {
                 Some(Box::new(engine))
             } else {
@@ -66,9 +322,189 @@ pub fn create_engine(config: &Config) -> Option<Box<dyn Engine + Send + Sync>> {
                 None
             }
         }
+        "google_speech" => {
+            let engine = GoogleSpeechEngine::new(
+                &config.engine.google_speech.api_key,
+                &config.engine.google_speech.model,
+            )
+            .with_proxy(config.engine.proxy.clone());
+            if engine.is_available() {
+                Some(Box::new(engine))
+            } else {
+                log::warn!("Google Speech engine not configured");
+                None
+            }
+        }
+        "azure_speech" => {
+            let engine = AzureSpeechEngine::new(
+                &config.engine.azure_speech.subscription_key,
+                &config.engine.azure_speech.region,
+            )
+            .with_proxy(config.engine.proxy.clone());
+            if engine.is_available() {
+                Some(Box::new(engine))
+            } else {
+                log::warn!("Azure Speech engine not configured");
+                None
+            }
+        }
+        "deepgram" => {
+            let engine = DeepgramEngine::new(
+                &config.engine.deepgram.api_key,
+                &config.engine.deepgram.model,
+                &config.engine.deepgram.tier,
+            )
+            .with_proxy(config.engine.proxy.clone());
+            if engine.is_available() {
+                Some(Box::new(engine))
+            } else {
+                log::warn!("Deepgram engine not configured");
+                None
+            }
+        }
+        #[cfg(feature = "local-whisper")]
+        "local_whisper" => {
+            let engine = LocalWhisperEngine::new(
+                std::path::PathBuf::from(&config.engine.local_whisper.model_path),
+                config.engine.local_whisper.use_gpu,
+                config.engine.local_whisper.n_threads,
+            );
+            if engine.is_available() {
+                Some(Box::new(engine))
+            } else {
+                log::warn!("Local Whisper model not found");
+                None
+            }
+        }
+        #[cfg(not(feature = "local-whisper"))]
+        "local_whisper" => {
+            log::error!("Local Whisper engine requires the `local-whisper` feature");
+            None
+        }
+        #[cfg(feature = "vosk")]
+        "vosk" => match VoskEngine::new(&config.engine.vosk) {
+            Ok(engine) => Some(Box::new(engine)),
+            Err(e) => {
+                log::warn!("Vosk engine unavailable: {}", e);
+                None
+            }
+        },
+        #[cfg(not(feature = "vosk"))]
+        "vosk" => {
+            log::error!("Vosk engine requires the `vosk` feature");
+            None
+        }
         _ => {
             log::error!("Unknown engine: {}", config.engine.current);
             None
         }
     }
 }
+
+/// Run `op`, retrying transient failures with exponential backoff
+pub fn retry_with_backoff<T>(
+    config: &RetryConfig,
+    mut op: impl FnMut() -> Result<T, String>,
+) -> Result<T, String> {
+    let max_attempts = config.max_attempts.max(1);
+    let mut delay = Duration::from_millis(config.initial_delay_ms);
+    let mut attempt = 1;
+
+    loop {
+        match op() {
+            Err(e) if attempt < max_attempts && is_transient_error(&e) => {
+                log::warn!(
+                    "Attempt {}/{} failed, retrying in {:?}: {}",
+                    attempt,
+                    max_attempts,
+                    delay,
+                    e
+                );
+                std::thread::sleep(delay);
+                delay = delay.mul_f64(config.backoff_factor.max(1.0));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Timeouts, connection failures and 5xx responses are worth retrying;
+/// 4xx responses (bad credentials, invalid requests) are not
+fn is_transient_error(message: &str) -> bool {
+    match http_status(message) {
+        Some(status) if (400..500).contains(&status) => false,
+        Some(status) if (500..600).contains(&status) => true,
+        _ => {
+            let lower = message.to_lowercase();
+            lower.contains("timeout") || lower.contains("timed out") || lower.contains("connect")
+        }
+    }
+}
+
+/// The HTTP status in an engine error, from the `API error: NNN` the HTTP
+/// engines produce or tungstenite's `HTTP error: NNN`
+fn http_status(message: &str) -> Option<u16> {
+    ["API error: ", "HTTP error: "].iter().find_map(|prefix| {
+        let start = message.find(prefix)? + prefix.len();
+        message.get(start..start + 3)?.parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_errors_are_classified() {
+        assert!(is_transient_error(
+            "API error: 503 Service Unavailable - busy"
+        ));
+        assert!(is_transient_error(
+            "Failed to connect: IO error: Connection refused"
+        ));
+        assert!(is_transient_error("Request failed: operation timed out"));
+        assert!(!is_transient_error("API error: 401 Unauthorized - bad key"));
+        assert!(!is_transient_error(
+            "Failed to connect: HTTP error: 403 Forbidden"
+        ));
+        assert!(!is_transient_error("Error response: code=45000001"));
+        // Ports and addresses are not status codes
+        assert!(is_transient_error(
+            "Failed to connect: IO error: Connection refused (openspeech.bytedance.com:443)"
+        ));
+        assert!(is_transient_error(
+            "Request failed: error sending request for url (http://10.0.0.404/): operation timed out"
+        ));
+    }
+
+    #[test]
+    fn retry_stops_after_max_attempts() {
+        let config = RetryConfig {
+            max_attempts: 3,
+            initial_delay_ms: 0,
+            backoff_factor: 2.0,
+        };
+        let mut calls = 0;
+        let result: Result<(), String> = retry_with_backoff(&config, || {
+            calls += 1;
+            Err("connection reset".to_string())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn retry_does_not_repeat_permanent_errors() {
+        let config = RetryConfig::default();
+        let mut calls = 0;
+        let result: Result<(), String> = retry_with_backoff(&config, || {
+            calls += 1;
+            Err("API error: 401 Unauthorized".to_string())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -1,12 +1,25 @@
-use super::Engine;
+use super::{Engine, EngineContext, VerboseEngine, VerboseTranscription, WordTimestamp};
+use crate::audio::compression::OGG_MAGIC;
+use crate::config::ProxyConfig;
 use log::{error, info};
+use parking_lot::Mutex;
 use reqwest::blocking::multipart;
+use std::io::{Cursor, Read};
+use tauri::Emitter;
+
+const NO_PUNCTUATION_PROMPT: &str = "[no punctuation]";
 
 /// OpenAI Whisper API engine
 pub struct OpenAIEngine {
     api_key: String,
     model: String,
     base_url: String,
+    word_timestamps: bool,
+    punctuation: bool,
+    multi_language: bool,
+    proxy: Option<ProxyConfig>,
+    context: EngineContext,
+    detected_language: Mutex<Option<String>>,
 }
 
 impl OpenAIEngine {
@@ -15,37 +28,111 @@ impl OpenAIEngine {
             api_key: api_key.to_string(),
             model: model.to_string(),
             base_url: base_url.trim_end_matches('/').to_string(),
+            word_timestamps: false,
+            punctuation: true,
+            multi_language: false,
+            proxy: None,
+            context: EngineContext::default(),
+            detected_language: Mutex::new(None),
         }
     }
-}
 
-impl Engine for OpenAIEngine {
-    fn name(&self) -> &str {
-        "OpenAI Whisper"
+    /// Send requests through an HTTP proxy
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
     }
 
-    fn is_available(&self) -> bool {
-        !self.api_key.is_empty()
+    /// Emit `upload-progress` events through the context's app handle
+    pub fn with_context(mut self, context: EngineContext) -> Self {
+        self.context = context;
+        self
     }
 
-    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+    /// Report word timestamps through `VerboseEngine`
+    pub fn with_word_timestamps(mut self, enabled: bool) -> Self {
+        self.word_timestamps = enabled;
+        self
+    }
+
+    /// Leave out the language so Whisper detects it, for users who switch languages
+    pub fn with_languages(mut self, multi_language: bool) -> Self {
+        self.multi_language = multi_language;
+        self
+    }
+
+    /// Whisper has no punctuation switch; when disabled, the prompt asks for none
+    pub fn with_punctuation(mut self, enabled: bool) -> Self {
+        self.punctuation = enabled;
+        self
+    }
+
+    fn transcribe_request(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+        word_timestamps: bool,
+    ) -> Result<VerboseTranscription, String> {
         info!("Starting OpenAI transcription, model={}", self.model);
+        if !audio_data.starts_with(OGG_MAGIC) {
+            super::verify_wav_integrity(audio_data)?;
+        }
 
         let url = format!("{}/audio/transcriptions", self.base_url);
 
         // Create multipart form
-        let part = multipart::Part::bytes(audio_data.to_vec())
-            .file_name("audio.wav")
-            .mime_str("audio/wav")
+        let (file_name, mime) = if audio_data.starts_with(OGG_MAGIC) {
+            ("audio.ogg", "audio/ogg")
+        } else {
+            ("audio.wav", "audio/wav")
+        };
+        let part = match self.context.app_handle {
+            Some(ref app) => {
+                let app = app.clone();
+                let reader = ProgressReader::new(audio_data.to_vec(), move |sent, total| {
+                    let _ = app.emit(
+                        "upload-progress",
+                        serde_json::json!({
+                            "sent_bytes": sent,
+                            "total_bytes": total
+                        }),
+                    );
+                });
+                multipart::Part::reader_with_length(reader, audio_data.len() as u64)
+            }
+            None => multipart::Part::bytes(audio_data.to_vec()),
+        };
+        let part = part
+            .file_name(file_name)
+            .mime_str(mime)
             .map_err(|e: reqwest::Error| e.to_string())?;
 
-        let form = multipart::Form::new()
+        // Without a language Whisper detects it; only Whisper models report
+        // which one it found, and only in verbose_json responses
+        let auto_detect = language == super::AUTO_LANGUAGE || self.multi_language;
+        let verbose = word_timestamps || (auto_detect && self.model.starts_with("whisper"));
+        *self.detected_language.lock() = None;
+
+        let mut form = multipart::Form::new()
             .part("file", part)
             .text("model", self.model.clone())
-            .text("language", language.to_string())
-            .text("response_format", "text");
+            .text(
+                "response_format",
+                if verbose { "verbose_json" } else { "text" },
+            );
+        if !auto_detect {
+            form = form.text("language", language.to_string());
+        }
+        if word_timestamps {
+            form = form.text("timestamp_granularities[]", "word");
+        }
+
+        if let Some(prompt) = build_prompt(hints, self.punctuation) {
+            form = form.text("prompt", prompt);
+        }
 
-        let client = reqwest::blocking::Client::new();
+        let client = super::proxy::blocking_client(self.proxy.as_ref())?;
         let response = client
             .post(&url)
             .bearer_auth(&self.api_key)
@@ -60,12 +147,211 @@ impl Engine for OpenAIEngine {
             return Err(format!("API error: {} - {}", status, text));
         }
 
-        let text = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
+        let body = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
+        let (text, words) = if verbose {
+            let json: serde_json::Value = serde_json::from_str(&body)
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            *self.detected_language.lock() = json["language"].as_str().map(language_code);
+            let words = parse_words(&json);
+            (json["text"].as_str().unwrap_or_default().to_string(), words)
+        } else {
+            (body, Vec::new())
+        };
         info!("Transcription complete: {}", text.trim());
-        Ok(text.trim().to_string())
+        Ok(VerboseTranscription {
+            text: text.trim().to_string(),
+            words,
+            ..Default::default()
+        })
+    }
+}
+
+/// Reads the upload body, reporting `(sent, total)` bytes at most once per percent
+struct ProgressReader<F> {
+    inner: Cursor<Vec<u8>>,
+    total: u64,
+    last_percent: Option<u64>,
+    on_progress: F,
+}
+
+impl<F: Fn(u64, u64)> ProgressReader<F> {
+    fn new(data: Vec<u8>, on_progress: F) -> Self {
+        Self {
+            total: data.len() as u64,
+            inner: Cursor::new(data),
+            last_percent: None,
+            on_progress,
+        }
+    }
+}
+
+impl<F: Fn(u64, u64)> Read for ProgressReader<F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let sent = self.inner.position();
+        let percent = sent * 100 / self.total.max(1);
+        if n > 0 && self.last_percent != Some(percent) {
+            self.last_percent = Some(percent);
+            (self.on_progress)(sent, self.total);
+        }
+        Ok(n)
+    }
+}
+
+/// Whisper treats the prompt as preceding context: unpunctuated context steers
+/// it away from punctuation, and listed terms bias it toward their spelling
+fn build_prompt(hints: &[String], punctuation: bool) -> Option<String> {
+    let mut parts = Vec::new();
+    if !punctuation {
+        parts.push(NO_PUNCTUATION_PROMPT.to_string());
+    }
+    if !hints.is_empty() {
+        parts.push(hints.join(", "));
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// Words of a `verbose_json` response; empty when timestamps were not requested
+fn parse_words(json: &serde_json::Value) -> Vec<WordTimestamp> {
+    json.get("words")
+        .and_then(|words| serde_json::from_value(words.clone()).ok())
+        .unwrap_or_default()
+}
+
+impl VerboseEngine for OpenAIEngine {
+    fn transcribe_verbose(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+    ) -> Result<VerboseTranscription, String> {
+        self.transcribe_request(audio_data, language, hints, true)
+    }
+}
+
+impl Engine for OpenAIEngine {
+    fn name(&self) -> &str {
+        "OpenAI Whisper"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        self.transcribe_request(audio_data, language, &[], false)
+            .map(|result| result.text)
+    }
+
+    fn transcribe_with_hints(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+        _callback: super::PartialResultCallback,
+    ) -> Result<String, String> {
+        self.transcribe_request(audio_data, language, hints, false)
+            .map(|result| result.text)
     }
 
     fn supports_streaming(&self) -> bool {
         false
     }
+
+    fn accepts_opus(&self) -> bool {
+        true
+    }
+
+    fn as_verbose(&self) -> Option<&dyn VerboseEngine> {
+        if self.word_timestamps {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn detected_language(&self) -> Option<String> {
+        self.detected_language.lock().clone()
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            supported_languages: super::COMMON_LANGUAGES
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            supports_language_detection: true,
+            // 25 MB upload limit, about 13 minutes of 16 kHz mono WAV
+            max_audio_duration_secs: Some(780),
+            supported_models: Vec::new(),
+        }
+    }
+}
+
+/// Whisper reports languages by English name ("chinese"); map the common ones to codes
+fn language_code(name: &str) -> String {
+    match name.to_lowercase().as_str() {
+        "chinese" => "zh",
+        "english" => "en",
+        "japanese" => "ja",
+        "korean" => "ko",
+        "german" => "de",
+        "french" => "fr",
+        "spanish" => "es",
+        "portuguese" => "pt",
+        "russian" => "ru",
+        _ => name,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_word_timestamps() {
+        let json = serde_json::json!({
+            "text": "Hello world",
+            "words": [
+                {"word": "Hello", "start": 0.0, "end": 0.42},
+                {"word": "world", "start": 0.5, "end": 0.9}
+            ]
+        });
+
+        let words = parse_words(&json);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[1].word, "world");
+        assert_eq!(words[1].end, 0.9);
+        assert!(parse_words(&serde_json::json!({"text": "Hello"})).is_empty());
+    }
+
+    #[test]
+    fn upload_progress_is_reported_per_percent() {
+        let reports = std::cell::RefCell::new(Vec::new());
+        let mut reader = ProgressReader::new(vec![0u8; 1000], |sent, total| {
+            reports.borrow_mut().push((sent, total));
+        });
+
+        let mut buf = [0u8; 3];
+        while reader.read(&mut buf).unwrap() > 0 {}
+
+        let reports = reports.into_inner();
+        assert_eq!(reports.len(), 101);
+        assert_eq!(reports.last(), Some(&(1000, 1000)));
+    }
+
+    #[test]
+    fn prompt_prefixes_no_punctuation_marker() {
+        let hints = vec!["Speaky".to_string(), "Tauri".to_string()];
+
+        assert_eq!(build_prompt(&[], true), None);
+        assert_eq!(build_prompt(&hints, true).as_deref(), Some("Speaky, Tauri"));
+        assert_eq!(
+            build_prompt(&hints, false).as_deref(),
+            Some("[no punctuation] Speaky, Tauri")
+        );
+    }
 }
@@ -0,0 +1,118 @@
+//! HTTP proxy support for the cloud engines.
+
+use base64::Engine as _;
+use reqwest::Url;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::config::ProxyConfig;
+
+/// Blocking HTTP client that goes through `proxy`, when set
+pub fn blocking_client(proxy: Option<&ProxyConfig>) -> Result<reqwest::blocking::Client, String> {
+    blocking_client_builder(proxy)?
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Client builder with `proxy` applied, for callers that set more options
+pub fn blocking_client_builder(
+    proxy: Option<&ProxyConfig>,
+) -> Result<reqwest::blocking::ClientBuilder, String> {
+    let mut builder = reqwest::blocking::Client::builder();
+    if let Some(proxy) = proxy {
+        let mut reqwest_proxy = reqwest::Proxy::all(&proxy.url)
+            .map_err(|e| format!("Invalid proxy {:?}: {}", proxy.url, e))?;
+        if let Some(ref username) = proxy.username {
+            reqwest_proxy =
+                reqwest_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or_default());
+        }
+        builder = builder.proxy(reqwest_proxy);
+    }
+    Ok(builder)
+}
+
+/// Open a TCP tunnel to `host:port` with an HTTP CONNECT request, for
+/// WebSocket clients that cannot use a proxy themselves
+pub async fn connect_tunnel(
+    proxy: &ProxyConfig,
+    host: &str,
+    port: u16,
+) -> Result<TcpStream, String> {
+    let url =
+        Url::parse(&proxy.url).map_err(|e| format!("Invalid proxy {:?}: {}", proxy.url, e))?;
+    if url.scheme() != "http" {
+        return Err(format!(
+            "Only http:// proxies can tunnel WebSocket connections, got {}",
+            url.scheme()
+        ));
+    }
+    let proxy_host = url.host_str().ok_or("Proxy URL has no host")?;
+    let proxy_port = url.port_or_known_default().unwrap_or(80);
+
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .await
+        .map_err(|e| format!("Failed to connect to proxy: {}", e))?;
+
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some(ref username) = proxy.username {
+        let credentials = format!(
+            "{}:{}",
+            username,
+            proxy.password.as_deref().unwrap_or_default()
+        );
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", encoded));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send CONNECT: {}", e))?;
+
+    // Read the proxy's response headers byte by byte so nothing of the
+    // tunnelled stream is consumed
+    let mut reader = BufReader::with_capacity(1, &mut stream);
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .await
+        .map_err(|e| format!("Failed to read proxy response: {}", e))?;
+    if !is_success_status(&status_line) {
+        return Err(format!("Proxy refused tunnel: {}", status_line.trim()));
+    }
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("Failed to read proxy response: {}", e))?;
+        if read == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+    }
+
+    Ok(stream)
+}
+
+/// Whether an HTTP status line ("HTTP/1.1 200 Connection established") is 2xx
+fn is_success_status(status_line: &str) -> bool {
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .is_some_and(|code| code.starts_with('2'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_successful_connect_responses() {
+        assert!(is_success_status("HTTP/1.1 200 Connection established\r\n"));
+        assert!(is_success_status("HTTP/1.0 200 OK\r\n"));
+        assert!(!is_success_status(
+            "HTTP/1.1 407 Proxy Authentication Required\r\n"
+        ));
+        assert!(!is_success_status(""));
+    }
+}
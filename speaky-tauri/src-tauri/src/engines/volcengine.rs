@@ -1,17 +1,24 @@
-use super::Engine;
+use super::{Engine, Utterance, VerboseEngine, VerboseTranscription};
+use crate::audio::compression::OGG_MAGIC;
 use byteorder::{BigEndian, WriteBytesExt};
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use futures_util::{SinkExt, StreamExt};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use std::io::Write;
+use std::time::Duration;
+use tokio::net::TcpStream;
 use tokio::runtime::Runtime;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 use tokio_tungstenite::{
-    connect_async,
+    client_async_tls, connect_async,
     tungstenite::{http::Request, Message},
+    MaybeTlsStream, WebSocketStream,
 };
 use uuid::Uuid;
 
+use crate::config::ProxyConfig;
+
 // Protocol constants
 const PROTOCOL_VERSION: u8 = 0b0001;
 const MESSAGE_TYPE_FULL_REQUEST: u8 = 0b0001;
@@ -19,29 +26,118 @@ const MESSAGE_TYPE_AUDIO_ONLY: u8 = 0b0010;
 const MESSAGE_TYPE_FULL_RESPONSE: u8 = 0b1001;
 const MESSAGE_TYPE_ERROR_RESPONSE: u8 = 0b1111;
 const FLAGS_POS_SEQUENCE: u8 = 0b0001;
+#[allow(dead_code)]
 const FLAGS_NEG_SEQUENCE: u8 = 0b0010;
 const FLAGS_NEG_WITH_SEQUENCE: u8 = 0b0011;
 const SERIALIZATION_JSON: u8 = 0b0001;
 const COMPRESSION_GZIP: u8 = 0b0001;
 
+/// Sample rate of the raw PCM pushed into a streaming session
+const STREAM_SAMPLE_RATE: u32 = 16000;
+
+/// Models the BigModel API accepts: the standard one, a faster and cheaper
+/// one, and a slower and more accurate one
+pub const VOLC_MODELS: &[&str] = &["bigmodel", "bigmodel_flash", "bigmodel_pro"];
+
+/// Interpretations requested when alternatives are enabled
+const NBEST: u32 = 3;
+
+/// Pause before reopening a dropped connection
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Why a recognition session ended early
+enum SessionError {
+    /// The connection broke; a new session may succeed
+    Disconnected(String),
+    /// The server refused the request
+    Rejected(String),
+}
+
 /// Volcengine BigModel ASR engine
+#[derive(Clone)]
 pub struct VolcBigModelEngine {
     app_key: String,
     access_key: String,
     ws_url: String,
     segment_duration_ms: u32,
+    model: String,
+    /// Reopen the session and resend the audio when the connection drops
+    auto_reconnect: bool,
+    max_reconnect_attempts: u32,
+    proxy: Option<ProxyConfig>,
+    itn_enabled: bool,
+    punctuation_enabled: bool,
+    speaker_labels: bool,
+    alternatives: bool,
+    languages: Vec<String>,
 }
 
 impl VolcBigModelEngine {
-    pub fn new(app_key: &str, access_key: &str) -> Self {
+    /// `segment_duration_ms` is how much audio goes in each WebSocket message
+    pub fn new(app_key: &str, access_key: &str, segment_duration_ms: u32) -> Self {
         Self {
             app_key: app_key.to_string(),
             access_key: access_key.to_string(),
             ws_url: "wss://openspeech.bytedance.com/api/v3/sauc/bigmodel_async".to_string(),
-            segment_duration_ms: 200,
+            segment_duration_ms,
+            model: VOLC_MODELS[0].to_string(),
+            auto_reconnect: true,
+            max_reconnect_attempts: 3,
+            proxy: None,
+            itn_enabled: true,
+            punctuation_enabled: true,
+            speaker_labels: false,
+            alternatives: false,
+            languages: Vec::new(),
         }
     }
 
+    /// Recognize with `model`, one of `VOLC_MODELS`
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    /// Request speaker labels, making the engine available through `as_verbose`
+    pub fn with_speaker_labels(mut self, enabled: bool) -> Self {
+        self.speaker_labels = enabled;
+        self
+    }
+
+    /// Let the server pick among `languages` per utterance, which also makes the
+    /// engine available through `as_verbose` to report them
+    pub fn with_languages(mut self, languages: Vec<String>) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Request the top interpretations, returned by `transcribe_nbest`
+    pub fn with_alternatives(mut self, enabled: bool) -> Self {
+        self.alternatives = enabled;
+        self
+    }
+
+    /// Use the WebSocket endpoint of a private deployment
+    pub fn with_ws_url(mut self, url: &str) -> Self {
+        self.ws_url = url.to_string();
+        self
+    }
+
+    /// Turn inverse text normalization and automatic punctuation on or off
+    pub fn with_formatting(mut self, itn_enabled: bool, punctuation_enabled: bool) -> Self {
+        self.itn_enabled = itn_enabled;
+        self.punctuation_enabled = punctuation_enabled;
+        self
+    }
+
+    /// Connect through an HTTP proxy
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
     fn build_header(
         message_type: u8,
         flags: u8,
@@ -56,7 +152,15 @@ impl VolcBigModelEngine {
         ]
     }
 
-    fn build_full_request(&self, seq: i32, sample_rate: u32) -> Vec<u8> {
+    fn build_full_request(
+        &self,
+        seq: i32,
+        format: &str,
+        codec: &str,
+        sample_rate: u32,
+        language: &str,
+        hints: &[String],
+    ) -> Vec<u8> {
         let header = Self::build_header(
             MESSAGE_TYPE_FULL_REQUEST,
             FLAGS_POS_SEQUENCE,
@@ -64,24 +168,49 @@ impl VolcBigModelEngine {
             COMPRESSION_GZIP,
         );
 
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "user": {"uid": "speaky"},
             "audio": {
-                "format": "wav",
-                "codec": "raw",
+                "format": format,
+                "codec": codec,
                 "rate": sample_rate,
                 "bits": 16,
                 "channel": 1,
             },
             "request": {
-                "model_name": "bigmodel",
-                "enable_itn": true,
-                "enable_punc": true,
+                "model_name": self.model,
+                "enable_itn": self.itn_enabled,
+                "enable_punc": self.punctuation_enabled,
                 "enable_ddc": true,
                 "show_utterances": true,
             },
         });
 
+        if self.speaker_labels {
+            payload["request"]["enable_speaker_info"] = serde_json::json!(true);
+        }
+        if self.alternatives {
+            payload["request"]["nbest"] = serde_json::json!(NBEST);
+        }
+
+        // An empty language makes the server detect it
+        if !self.languages.is_empty() {
+            payload["audio"]["languages"] = serde_json::json!(self.languages);
+        } else if language == super::AUTO_LANGUAGE {
+            payload["audio"]["language"] = serde_json::json!("");
+        }
+
+        // Context phrases are passed as hotwords in the corpus context, which
+        // the API expects as a JSON-encoded string
+        if !hints.is_empty() {
+            let hotwords: Vec<_> = hints
+                .iter()
+                .map(|word| serde_json::json!({ "word": word }))
+                .collect();
+            let context = serde_json::json!({ "hotwords": hotwords }).to_string();
+            payload["request"]["corpus"] = serde_json::json!({ "context": context });
+        }
+
         let payload_json = payload.to_string();
         let payload_compressed = gzip_compress(payload_json.as_bytes());
 
@@ -177,27 +306,34 @@ impl VolcBigModelEngine {
         result
     }
 
-    async fn transcribe_async(
-        &self,
-        audio_data: &[u8],
-        _language: &str,
-        partial_callback: Option<super::PartialResultCallback>,
-    ) -> Result<String, String> {
-        let request_id = Uuid::new_v4().to_string();
-        info!("Starting BigModel transcription, request_id={}", request_id);
+    /// Extract the transcript carried by a response, if any
+    fn response_text(resp: &ParsedResponse) -> Option<String> {
+        let result = resp.payload.as_ref()?.get("result")?;
+        let text = match result.as_array() {
+            Some(arr) => arr.first()?.get("text"),
+            None => result.get("text"),
+        };
+        text.and_then(|t| t.as_str()).map(|t| t.to_string())
+    }
 
-        // Parse WAV to get sample rate
-        let sample_rate = parse_wav_sample_rate(audio_data).unwrap_or(16000);
-        info!("Audio sample rate: {}", sample_rate);
+    /// Connect, send the full client request and wait for the server to accept it
+    async fn open(&self, request_id: &str, full_request: Vec<u8>) -> Result<WsStream, String> {
+        let url = reqwest::Url::parse(&self.ws_url).map_err(|e| e.to_string())?;
+        let host = url.host_str().unwrap_or_default();
+        // Url leaves out the port when it is the scheme's default
+        let host_header = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
 
         // Build WebSocket request with custom headers
         let request = Request::builder()
             .uri(&self.ws_url)
             .header("X-Api-Resource-Id", "volc.seedasr.sauc.duration")
-            .header("X-Api-Request-Id", &request_id)
+            .header("X-Api-Request-Id", request_id)
             .header("X-Api-Access-Key", &self.access_key)
             .header("X-Api-App-Key", &self.app_key)
-            .header("Host", "openspeech.bytedance.com")
+            .header("Host", host_header)
             .header("Upgrade", "websocket")
             .header("Connection", "Upgrade")
             .header(
@@ -208,15 +344,20 @@ impl VolcBigModelEngine {
             .body(())
             .map_err(|e: tokio_tungstenite::tungstenite::http::Error| e.to_string())?;
 
-        let (mut ws, _) = connect_async(request)
-            .await
-            .map_err(|e| format!("Failed to connect: {}", e))?;
+        let (mut ws, _) = match self.proxy {
+            Some(ref proxy) => {
+                let port = url.port_or_known_default().unwrap_or(443);
+                let stream = super::proxy::connect_tunnel(proxy, host, port).await?;
+                client_async_tls(request, stream).await
+            }
+            None => connect_async(request).await,
+        }
+        .map_err(|e| format!("Failed to connect: {}", e))?;
 
         info!("Connected to WebSocket");
 
         // Send full request
-        let full_request = self.build_full_request(1, sample_rate);
-        ws.send(Message::Binary(full_request.into()))
+        ws.send(Message::Binary(full_request))
             .await
             .map_err(|e| format!("Failed to send full request: {}", e))?;
 
@@ -232,20 +373,92 @@ impl VolcBigModelEngine {
             }
         }
 
+        Ok(ws)
+    }
+
+    async fn transcribe_async(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+        partial_callback: Option<super::PartialResultCallback>,
+        progress_callback: Option<super::ProgressCallback>,
+    ) -> Result<VerboseTranscription, String> {
+        let request_id = Uuid::new_v4().to_string();
+        info!("Starting BigModel transcription, request_id={}", request_id);
+        if !audio_data.starts_with(OGG_MAGIC) {
+            super::verify_wav_integrity(audio_data)?;
+        }
+
+        // Parse WAV to get sample rate
+        let sample_rate = super::parse_wav_sample_rate(audio_data).unwrap_or(16000);
+        info!("Audio sample rate: {}", sample_rate);
+
+        // Compressed recordings are Ogg/Opus at the rate they were captured at
+        let (format, codec) = if audio_data.starts_with(OGG_MAGIC) {
+            ("ogg", "opus")
+        } else {
+            ("wav", "raw")
+        };
+        let full_request = self.build_full_request(1, format, codec, sample_rate, language, hints);
+        let mut ws = self.open(&request_id, full_request.clone()).await?;
+
         // Send audio in segments
         let segment_size = (sample_rate * 2 * self.segment_duration_ms / 1000) as usize;
         let segments: Vec<_> = audio_data.chunks(segment_size).collect();
-        let total_segments = segments.len();
+        let mut reconnects = 0;
 
+        // A new session knows nothing of the audio sent on a dropped one, so
+        // every retry sends the whole recording again
+        let transcript = loop {
+            let error = match self
+                .send_segments(&mut ws, &segments, &progress_callback)
+                .await
+            {
+                Ok(()) => match Self::receive_transcript(&mut ws, &partial_callback).await {
+                    Ok(transcript) => break transcript,
+                    Err(SessionError::Disconnected(e)) => e,
+                    Err(SessionError::Rejected(e)) => return Err(e),
+                },
+                Err(e) => e,
+            };
+
+            let _ = ws.close(None).await;
+            ws = self
+                .reconnect(error, &mut reconnects, &full_request)
+                .await?;
+        };
+
+        let _ = ws.close(None).await;
+        info!("Transcription complete: {}", transcript.text);
+        Ok(VerboseTranscription {
+            text: transcript.text.trim().to_string(),
+            ..transcript
+        })
+    }
+
+    /// Send every segment, numbering them from the start of the session;
+    /// fails only when the connection breaks
+    async fn send_segments(
+        &self,
+        ws: &mut WsStream,
+        segments: &[&[u8]],
+        progress_callback: &Option<super::ProgressCallback>,
+    ) -> Result<(), String> {
+        let total_segments = segments.len();
         let mut seq = 2;
+
         for (i, segment) in segments.iter().enumerate() {
             let is_last = i == total_segments - 1;
             let audio_request = self.build_audio_request(seq, segment, is_last);
-            ws.send(Message::Binary(audio_request.into()))
+            ws.send(Message::Binary(audio_request))
                 .await
                 .map_err(|e| format!("Failed to send audio: {}", e))?;
 
             debug!("Sent segment {}/{}, last={}", i + 1, total_segments, is_last);
+            if let Some(ref callback) = progress_callback {
+                callback((i + 1) as f32 * 100.0 / total_segments as f32);
+            }
 
             if !is_last {
                 seq += 1;
@@ -256,11 +469,19 @@ impl VolcBigModelEngine {
             }
         }
 
-        // Receive responses
-        let mut result_text = String::new();
+        Ok(())
+    }
+
+    /// Read responses until the last one and return its transcript
+    async fn receive_transcript(
+        ws: &mut WsStream,
+        partial_callback: &Option<super::PartialResultCallback>,
+    ) -> Result<VerboseTranscription, SessionError> {
+        let mut transcript = VerboseTranscription::default();
 
         while let Some(msg) = ws.next().await {
-            let msg = msg.map_err(|e| format!("Failed to receive: {}", e))?;
+            let msg =
+                msg.map_err(|e| SessionError::Disconnected(format!("Failed to receive: {}", e)))?;
 
             if let Message::Binary(data) = msg {
                 let resp = Self::parse_response(&data);
@@ -270,32 +491,24 @@ impl VolcBigModelEngine {
                 );
 
                 if resp.code != 0 {
-                    return Err(format!("Error response: code={}", resp.code));
+                    return Err(SessionError::Rejected(format!(
+                        "Error response: code={}",
+                        resp.code
+                    )));
                 }
 
-                if let Some(payload) = &resp.payload {
-                    if let Some(result) = payload.get("result") {
-                        let mut new_text = None;
-                        if let Some(arr) = result.as_array() {
-                            if let Some(first) = arr.first() {
-                                if let Some(text) = first.get("text").and_then(|t| t.as_str()) {
-                                    new_text = Some(text.to_string());
-                                }
-                            }
-                        } else if let Some(text) = result.get("text").and_then(|t| t.as_str()) {
-                            new_text = Some(text.to_string());
-                        }
-
-                        if let Some(text) = new_text {
-                            result_text = text.clone();
-                            // Emit partial result if callback is provided
-                            if let Some(ref callback) = partial_callback {
-                                if !text.is_empty() {
-                                    callback(&text);
-                                }
-                            }
+                if let Some(text) = Self::response_text(&resp) {
+                    // Emit partial result if callback is provided
+                    if let Some(ref callback) = partial_callback {
+                        if !text.is_empty() {
+                            callback(&text);
                         }
                     }
+                    transcript.text = text;
+                    if let Some(ref payload) = resp.payload {
+                        transcript.utterances = parse_utterances(payload);
+                        transcript.alternatives = parse_alternatives(payload, &transcript.text);
+                    }
                 }
 
                 if resp.is_last {
@@ -305,8 +518,112 @@ impl VolcBigModelEngine {
             }
         }
 
-        let _ = ws.close(None).await;
-        info!("Transcription complete: {}", result_text);
+        Ok(transcript)
+    }
+
+    /// Open a new session after the connection dropped with `error`, giving up
+    /// once `max_reconnect_attempts` is reached
+    async fn reconnect(
+        &self,
+        mut error: String,
+        reconnects: &mut u32,
+        full_request: &[u8],
+    ) -> Result<WsStream, String> {
+        loop {
+            if !self.auto_reconnect || *reconnects >= self.max_reconnect_attempts {
+                return Err(error);
+            }
+            *reconnects += 1;
+            warn!(
+                "Connection lost ({}), reconnecting ({}/{})",
+                error, reconnects, self.max_reconnect_attempts
+            );
+            tokio::time::sleep(RECONNECT_DELAY).await;
+
+            let request_id = Uuid::new_v4().to_string();
+            match self.open(&request_id, full_request.to_vec()).await {
+                Ok(ws) => return Ok(ws),
+                Err(e) => error = e,
+            }
+        }
+    }
+
+    /// Send PCM from `audio` as it arrives and read results concurrently;
+    /// the last packet goes out once the sender side is dropped
+    async fn stream_async(
+        &self,
+        mut audio: UnboundedReceiver<Vec<u8>>,
+        language: &str,
+        callback: super::PartialResultCallback,
+    ) -> Result<String, String> {
+        let request_id = Uuid::new_v4().to_string();
+        info!(
+            "Starting BigModel streaming session, request_id={}",
+            request_id
+        );
+
+        let full_request =
+            self.build_full_request(1, "pcm", "raw", STREAM_SAMPLE_RATE, language, &[]);
+        let (mut sink, mut stream) = self.open(&request_id, full_request).await?.split();
+
+        let segment_size = (STREAM_SAMPLE_RATE * 2 * self.segment_duration_ms / 1000) as usize;
+        let mut buffer = Vec::with_capacity(segment_size * 2);
+        let mut seq = 2;
+        let mut audio_open = true;
+        let mut result_text = String::new();
+
+        loop {
+            tokio::select! {
+                chunk = audio.recv(), if audio_open => match chunk {
+                    Some(pcm) => {
+                        buffer.extend_from_slice(&pcm);
+                        while buffer.len() >= segment_size {
+                            let segment: Vec<u8> = buffer.drain(..segment_size).collect();
+                            let request = self.build_audio_request(seq, &segment, false);
+                            sink.send(Message::Binary(request))
+                                .await
+                                .map_err(|e| format!("Failed to send audio: {}", e))?;
+                            seq += 1;
+                        }
+                    }
+                    None => {
+                        let request = self.build_audio_request(seq, &buffer, true);
+                        sink.send(Message::Binary(request))
+                            .await
+                            .map_err(|e| format!("Failed to send audio: {}", e))?;
+                        debug!("Sent last segment, seq={}", seq);
+                        audio_open = false;
+                    }
+                },
+                msg = stream.next() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    let msg = msg.map_err(|e| format!("Failed to receive: {}", e))?;
+                    if let Message::Binary(data) = msg {
+                        let resp = Self::parse_response(&data);
+                        if resp.code != 0 {
+                            return Err(format!("Error response: code={}", resp.code));
+                        }
+
+                        if let Some(text) = Self::response_text(&resp) {
+                            if !text.is_empty() {
+                                callback(&text);
+                            }
+                            result_text = text;
+                        }
+
+                        if resp.is_last {
+                            info!("Received last response");
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        let _ = sink.close().await;
+        info!("Streaming transcription complete: {}", result_text);
         Ok(result_text.trim().to_string())
     }
 }
@@ -322,7 +639,8 @@ impl Engine for VolcBigModelEngine {
 
     fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
         let rt = Runtime::new().map_err(|e| e.to_string())?;
-        rt.block_on(self.transcribe_async(audio_data, language, None))
+        rt.block_on(self.transcribe_async(audio_data, language, &[], None, None))
+            .map(|transcript| transcript.text)
     }
 
     fn transcribe_with_callback(
@@ -332,12 +650,180 @@ impl Engine for VolcBigModelEngine {
         callback: super::PartialResultCallback,
     ) -> Result<String, String> {
         let rt = Runtime::new().map_err(|e| e.to_string())?;
-        rt.block_on(self.transcribe_async(audio_data, language, Some(callback)))
+        rt.block_on(self.transcribe_async(audio_data, language, &[], Some(callback), None))
+            .map(|transcript| transcript.text)
+    }
+
+    fn transcribe_with_hints(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+        callback: super::PartialResultCallback,
+    ) -> Result<String, String> {
+        let rt = Runtime::new().map_err(|e| e.to_string())?;
+        rt.block_on(self.transcribe_async(audio_data, language, hints, Some(callback), None))
+            .map(|transcript| transcript.text)
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        progress: super::ProgressCallback,
+    ) -> Result<String, String> {
+        let rt = Runtime::new().map_err(|e| e.to_string())?;
+        rt.block_on(self.transcribe_async(audio_data, language, &[], None, Some(progress)))
+            .map(|transcript| transcript.text)
+    }
+
+    fn transcribe_nbest(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+    ) -> Result<Vec<String>, String> {
+        let rt = Runtime::new().map_err(|e| e.to_string())?;
+        let transcript =
+            rt.block_on(self.transcribe_async(audio_data, language, hints, None, None))?;
+        if transcript.alternatives.is_empty() {
+            Ok(vec![transcript.text])
+        } else {
+            Ok(transcript.alternatives)
+        }
+    }
+
+    fn supports_alternatives(&self) -> bool {
+        self.alternatives
     }
 
     fn supports_streaming(&self) -> bool {
         true
     }
+
+    fn as_verbose(&self) -> Option<&dyn VerboseEngine> {
+        if self.speaker_labels || !self.languages.is_empty() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn accepts_opus(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            supported_languages: vec!["zh".to_string(), "en".to_string()],
+            supports_language_detection: true,
+            max_audio_duration_secs: None,
+            supported_models: VOLC_MODELS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn start_streaming_session(
+        &self,
+        language: &str,
+        callback: super::PartialResultCallback,
+    ) -> Result<super::StreamingSession, String> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let engine = self.clone();
+        let language = language.to_string();
+        let worker = std::thread::spawn(move || {
+            let rt = Runtime::new().map_err(|e| e.to_string())?;
+            rt.block_on(engine.stream_async(receiver, &language, callback))
+        });
+        Ok(super::StreamingSession::new(sender, worker))
+    }
+}
+
+impl VerboseEngine for VolcBigModelEngine {
+    fn transcribe_verbose(
+        &self,
+        audio_data: &[u8],
+        language: &str,
+        hints: &[String],
+    ) -> Result<VerboseTranscription, String> {
+        let rt = Runtime::new().map_err(|e| e.to_string())?;
+        rt.block_on(self.transcribe_async(audio_data, language, hints, None, None))
+    }
+}
+
+/// Interpretations in a response's `result.alternatives`, led by the
+/// transcript itself; empty when the server sent none
+fn parse_alternatives(payload: &serde_json::Value, text: &str) -> Vec<String> {
+    let result = match payload.get("result") {
+        Some(serde_json::Value::Array(results)) => results.first(),
+        other => other,
+    };
+    let Some(alternatives) = result
+        .and_then(|result| result.get("alternatives"))
+        .and_then(|alternatives| alternatives.as_array())
+    else {
+        return Vec::new();
+    };
+
+    let mut ranked = vec![text.to_string()];
+    for alternative in alternatives {
+        let candidate = alternative
+            .as_str()
+            .or_else(|| alternative.get("text").and_then(|text| text.as_str()));
+        if let Some(candidate) = candidate {
+            if !ranked.iter().any(|seen| seen == candidate) {
+                ranked.push(candidate.to_string());
+            }
+        }
+    }
+    ranked
+}
+
+/// Utterances of a response's `result`, which the server sends when
+/// `show_utterances` is on; speaker labels need `enable_speaker_info`
+fn parse_utterances(payload: &serde_json::Value) -> Vec<Utterance> {
+    let result = match payload.get("result") {
+        Some(serde_json::Value::Array(results)) => results.first(),
+        other => other,
+    };
+    let Some(utterances) = result
+        .and_then(|result| result.get("utterances"))
+        .and_then(|utterances| utterances.as_array())
+    else {
+        return Vec::new();
+    };
+
+    utterances
+        .iter()
+        .map(|utterance| {
+            let speaker = utterance
+                .get("speaker")
+                .or_else(|| utterance.pointer("/additions/speaker"))
+                .map(|speaker| match speaker {
+                    serde_json::Value::String(label) => label.clone(),
+                    other => other.to_string(),
+                })
+                .unwrap_or_default();
+            let time = |key: &str| utterance.get(key).and_then(|t| t.as_u64()).unwrap_or(0);
+            let language = utterance
+                .pointer("/additions/lid_lang")
+                .or_else(|| utterance.get("language"))
+                .and_then(|language| language.as_str())
+                .map(str::to_string);
+            Utterance {
+                speaker,
+                start_ms: time("start_time"),
+                end_ms: time("end_time"),
+                text: utterance
+                    .get("text")
+                    .and_then(|text| text.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                language,
+            }
+        })
+        .collect()
 }
 
 #[derive(Default)]
@@ -364,9 +850,111 @@ fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
     Ok(decompressed)
 }
 
-fn parse_wav_sample_rate(data: &[u8]) -> Option<u32> {
-    if data.len() < 28 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
-        return None;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_utterances_with_speakers() {
+        let payload = serde_json::json!({
+            "result": {
+                "text": "你好。在的。",
+                "utterances": [
+                    {"text": "你好。", "start_time": 0, "end_time": 820, "additions": {"speaker": "1"}},
+                    {"text": "在的。", "start_time": 900, "end_time": 1500, "speaker": 2, "additions": {"lid_lang": "zh"}}
+                ]
+            }
+        });
+
+        let utterances = parse_utterances(&payload);
+        assert_eq!(utterances.len(), 2);
+        assert_eq!(utterances[0].speaker, "1");
+        assert_eq!(utterances[0].end_ms, 820);
+        assert_eq!(utterances[1].speaker, "2");
+        assert_eq!(utterances[1].text, "在的。");
+        assert_eq!(utterances[0].language, None);
+        assert_eq!(utterances[1].language.as_deref(), Some("zh"));
+        assert!(parse_utterances(&serde_json::json!({"result": {"text": "x"}})).is_empty());
+    }
+
+    #[test]
+    fn alternatives_start_with_the_transcript() {
+        let payload = serde_json::json!({
+            "result": {
+                "text": "在的",
+                "alternatives": [{"text": "在的"}, {"text": "再的"}, "在得"]
+            }
+        });
+
+        assert_eq!(
+            parse_alternatives(&payload, "在的"),
+            vec!["在的", "再的", "在得"]
+        );
+        assert!(parse_alternatives(&serde_json::json!({"result": {"text": "x"}}), "x").is_empty());
+    }
+
+    /// A server response carrying `text`, optionally flagged as the last one
+    fn response(text: &str, is_last: bool) -> Message {
+        let flags = if is_last {
+            FLAGS_NEG_WITH_SEQUENCE
+        } else {
+            FLAGS_POS_SEQUENCE
+        };
+        let payload = serde_json::json!({"result": {"text": text}}).to_string();
+        let mut data = VolcBigModelEngine::build_header(
+            MESSAGE_TYPE_FULL_RESPONSE,
+            flags,
+            SERIALIZATION_JSON,
+            0,
+        );
+        data.write_i32::<BigEndian>(1).unwrap();
+        data.write_u32::<BigEndian>(payload.len() as u32).unwrap();
+        data.extend_from_slice(payload.as_bytes());
+        Message::Binary(data)
+    }
+
+    #[test]
+    fn reconnect_resends_the_whole_recording() {
+        let rt = Runtime::new().unwrap();
+        let listener = rt
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // The first session drops after two segments; the second reports how
+        // many segments it received
+        rt.spawn(async move {
+            for session in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                ws.next().await.unwrap().unwrap();
+                ws.send(response("", false)).await.unwrap();
+
+                let mut received = 0;
+                while let Some(Ok(Message::Binary(data))) = ws.next().await {
+                    received += 1;
+                    if session == 0 && received == 2 {
+                        ws.send(response("partial", false)).await.unwrap();
+                        break;
+                    }
+                    if data[1] & 0x0f == FLAGS_NEG_WITH_SEQUENCE {
+                        ws.send(response(&format!("{} segments", received), true))
+                            .await
+                            .unwrap();
+                        break;
+                    }
+                }
+            }
+        });
+
+        let wav = crate::audio::processing::encode_wav(&[0; 1600], 16000);
+        let segment_count = wav.len().div_ceil(320);
+        let engine = VolcBigModelEngine::new("app", "key", 10)
+            .with_ws_url(&format!("ws://127.0.0.1:{}", port));
+        let transcript = rt
+            .block_on(engine.transcribe_async(&wav, "zh-CN", &[], None, None))
+            .unwrap();
+
+        assert_eq!(transcript.text, format!("{} segments", segment_count));
     }
-    Some(u32::from_le_bytes([data[24], data[25], data[26], data[27]]))
 }
@@ -0,0 +1,160 @@
+//! Offline recognition with Vosk (Kaldi), for when the network is down.
+//!
+//! Uses the `vosk` bindings to libvosk, which must be installed where the
+//! linker finds it. Models are the unpacked directories from
+//! https://alphacephei.com/vosk/models and recognize a single language.
+
+use super::Engine;
+use crate::config::VoskConfig;
+use log::{debug, info};
+use std::path::Path;
+use vosk::{CompleteResult, DecodingState, LogLevel, Model, Recognizer};
+
+/// Samples fed to the recognizer per call
+const CHUNK_SAMPLES: usize = 4000;
+
+/// Offline engine running a Vosk model
+pub struct VoskEngine {
+    model: Model,
+}
+
+impl VoskEngine {
+    /// Load the model at `config.model_path`
+    pub fn new(config: &VoskConfig) -> Result<Self, String> {
+        if !Path::new(&config.model_path).is_dir() {
+            return Err(format!(
+                "Vosk model directory {:?} does not exist",
+                config.model_path
+            ));
+        }
+
+        info!("Loading Vosk model from {:?}", config.model_path);
+        // Kaldi logs every model and recognizer creation to stderr otherwise
+        vosk::set_log_level(LogLevel::Error);
+        let model = Model::new(config.model_path.as_str())
+            .ok_or_else(|| format!("{:?} is not a valid Vosk model", config.model_path))?;
+        Ok(Self { model })
+    }
+}
+
+impl Engine for VoskEngine {
+    fn name(&self) -> &str {
+        "Vosk"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn transcribe(&self, audio_data: &[u8], language: &str) -> Result<String, String> {
+        debug!("Vosk models have a fixed language, ignoring {}", language);
+        let sample_rate = super::parse_wav_sample_rate(audio_data).unwrap_or(16000);
+        let samples: Vec<i16> = super::wav_pcm_data(audio_data)
+            .ok_or("Audio is not a WAV file")?
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+
+        let mut recognizer = Recognizer::new(&self.model, sample_rate as f32)
+            .ok_or("Failed to create a Vosk recognizer")?;
+        // Completed utterances are only returned once, so collect them as they end
+        let mut utterances = Vec::new();
+        for chunk in samples.chunks(CHUNK_SAMPLES) {
+            match recognizer
+                .accept_waveform(chunk)
+                .map_err(|e| e.to_string())?
+            {
+                DecodingState::Finalized => utterances.push(text(recognizer.result())),
+                DecodingState::Running => {}
+                DecodingState::Failed => return Err("Vosk failed to process the audio".into()),
+            }
+        }
+        utterances.push(text(recognizer.final_result()));
+
+        let text = utterances
+            .iter()
+            .map(|u| u.trim())
+            .filter(|u| !u.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        info!("Transcription complete: {}", text);
+        Ok(text)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn capabilities(&self) -> super::EngineCapabilities {
+        super::EngineCapabilities {
+            name: self.name().to_string(),
+            supports_streaming: self.supports_streaming(),
+            // Whatever language the model was trained on
+            supported_languages: Vec::new(),
+            supports_language_detection: false,
+            max_audio_duration_secs: None,
+            supported_models: Vec::new(),
+        }
+    }
+}
+
+/// Text of a result; alternatives are not requested, so there is one
+fn text(result: CompleteResult) -> String {
+    result
+        .single()
+        .map(|result| result.text.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_model_is_rejected() {
+        let config = VoskConfig {
+            model_path: "/nonexistent/vosk-model".to_string(),
+        };
+        assert!(VoskEngine::new(&config).is_err());
+    }
+
+    /// Fixture downloaded by `scripts/fetch_vosk_fixture.sh`
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/vosk")
+            .join(name);
+        assert!(
+            path.exists(),
+            "{} is missing, run scripts/fetch_vosk_fixture.sh",
+            path.display()
+        );
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn transcribes_speech_with_a_model() {
+        let config = VoskConfig {
+            model_path: fixture("model"),
+        };
+        let engine = VoskEngine::new(&config).unwrap();
+
+        let speech = std::fs::read(fixture("test.wav")).unwrap();
+        let text = engine.transcribe(&speech, "en").unwrap();
+        assert!(
+            text.starts_with("one zero zero zero one"),
+            "unexpected transcript {:?}",
+            text
+        );
+
+        let silence = crate::audio::processing::encode_wav(&[0; 16000], 16000);
+        assert_eq!(engine.transcribe(&silence, "en"), Ok(String::new()));
+    }
+
+    #[test]
+    fn directory_without_a_model_is_rejected() {
+        let config = VoskConfig {
+            model_path: std::env::temp_dir().to_string_lossy().into_owned(),
+        };
+        assert!(VoskEngine::new(&config).is_err());
+    }
+}
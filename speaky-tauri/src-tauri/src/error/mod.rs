@@ -0,0 +1,37 @@
+//! Errors from background threads, reported to the UI instead of only logged.
+
+use log::error;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// Payload of the `app-error` event
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    /// Part of the app that failed, e.g. "paste" or "hotkey"
+    pub source: String,
+    pub message: String,
+    /// Whether the app keeps working normally after this error
+    pub recoverable: bool,
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+}
+
+/// Log an error and emit it as an `app-error` event
+pub fn report_error(app: &AppHandle, source: &str, message: &str, recoverable: bool) {
+    error!("[{}] {}", source, message);
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let payload = AppError {
+        source: source.to_string(),
+        message: message.to_string(),
+        recoverable,
+        timestamp,
+    };
+    if let Err(e) = app.emit("app-error", payload) {
+        error!("Failed to emit app-error: {}", e);
+    }
+}
@@ -0,0 +1,92 @@
+//! Transcription history kept in memory and persisted on exit.
+
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+pub mod recent_apps;
+
+/// Maximum number of records kept in history
+pub const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// A single recognised transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionRecord {
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+    pub text: String,
+    pub app_name: String,
+    pub engine: String,
+    /// Length of the recorded audio
+    pub duration_ms: u64,
+}
+
+impl TranscriptionRecord {
+    pub fn new(text: &str, app_name: &str, engine: &str, duration_ms: u64) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            timestamp,
+            text: text.to_string(),
+            app_name: app_name.to_string(),
+            engine: engine.to_string(),
+            duration_ms,
+        }
+    }
+}
+
+/// Get the history file path
+pub fn history_path() -> PathBuf {
+    Config::config_dir().join("history.json")
+}
+
+/// Append a record, dropping the oldest entries beyond the cap
+pub fn push(history: &Mutex<VecDeque<TranscriptionRecord>>, record: TranscriptionRecord) {
+    let mut history = history.lock();
+    history.push_back(record);
+    while history.len() > MAX_HISTORY_ENTRIES {
+        history.pop_front();
+    }
+}
+
+/// Load history from disk, returning an empty history if missing or unreadable
+pub fn load() -> VecDeque<TranscriptionRecord> {
+    let path = history_path();
+    if !path.exists() {
+        return VecDeque::new();
+    }
+
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(records) => records,
+        Err(e) => {
+            warn!("Failed to load history from {:?}: {}", path, e);
+            VecDeque::new()
+        }
+    }
+}
+
+/// Save history to disk
+pub fn save(history: &VecDeque<TranscriptionRecord>) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = Config::config_dir();
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+
+    let path = history_path();
+    let content = serde_json::to_string(history)?;
+    fs::write(&path, content)?;
+    info!("Saved {} history records to {:?}", history.len(), path);
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Applications text was pasted into, for suggestions in the app-info overlay.
+
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// Maximum number of applications remembered; the least recently used go first
+pub const MAX_RECENT_APPS: usize = 50;
+
+/// How often text was pasted into an application
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppUsageRecord {
+    pub wm_class: String,
+    pub app_name: String,
+    pub use_count: u32,
+    /// Unix timestamp in seconds
+    pub last_used: u64,
+}
+
+/// Get the recent apps file path
+pub fn recent_apps_path() -> PathBuf {
+    Config::config_dir().join("recent_apps.json")
+}
+
+/// Count a paste into the application with `wm_class`
+pub fn record_use(apps: &Mutex<VecDeque<AppUsageRecord>>, wm_class: &str, app_name: &str) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let mut apps = apps.lock();
+    match apps.iter_mut().find(|record| record.wm_class == wm_class) {
+        Some(record) => {
+            record.use_count += 1;
+            record.last_used = now;
+            record.app_name = app_name.to_string();
+        }
+        None => {
+            apps.push_back(AppUsageRecord {
+                wm_class: wm_class.to_string(),
+                app_name: app_name.to_string(),
+                use_count: 1,
+                last_used: now,
+            });
+            if apps.len() > MAX_RECENT_APPS {
+                if let Some(oldest) = (0..apps.len()).min_by_key(|&i| apps[i].last_used) {
+                    apps.remove(oldest);
+                }
+            }
+        }
+    }
+}
+
+/// Up to `limit` records, most used first and most recent among equals
+pub fn most_used(apps: &VecDeque<AppUsageRecord>, limit: usize) -> Vec<AppUsageRecord> {
+    let mut records: Vec<_> = apps.iter().cloned().collect();
+    records.sort_by(|a, b| {
+        b.use_count
+            .cmp(&a.use_count)
+            .then(b.last_used.cmp(&a.last_used))
+    });
+    records.truncate(limit);
+    records
+}
+
+/// Load recent apps from disk, returning an empty list if missing or unreadable
+pub fn load() -> VecDeque<AppUsageRecord> {
+    let path = recent_apps_path();
+    if !path.exists() {
+        return VecDeque::new();
+    }
+
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(records) => records,
+        Err(e) => {
+            warn!("Failed to load recent apps from {:?}: {}", path, e);
+            VecDeque::new()
+        }
+    }
+}
+
+/// Save recent apps to disk
+pub fn save(apps: &VecDeque<AppUsageRecord>) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(Config::config_dir())?;
+
+    let path = recent_apps_path();
+    fs::write(&path, serde_json::to_string(apps)?)?;
+    info!("Saved {} recent apps to {:?}", apps.len(), path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_are_counted_per_wm_class() {
+        let apps = Mutex::new(VecDeque::new());
+        record_use(&apps, "firefox", "Firefox");
+        record_use(&apps, "code", "Code");
+        record_use(&apps, "firefox", "Firefox Nightly");
+
+        let top = most_used(&apps.lock(), 10);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].wm_class, "firefox");
+        assert_eq!(top[0].use_count, 2);
+        assert_eq!(top[0].app_name, "Firefox Nightly");
+        assert_eq!(most_used(&apps.lock(), 1).len(), 1);
+    }
+}
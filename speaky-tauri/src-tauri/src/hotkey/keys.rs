@@ -0,0 +1,184 @@
+//! Hotkey names accepted in the config and the keys they stand for.
+
+use rdev::Key;
+use serde::Serialize;
+
+/// Group a hotkey belongs to in the settings UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum HotkeyCategory {
+    Modifier,
+    FunctionKey,
+    Special,
+}
+
+impl HotkeyCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            HotkeyCategory::Modifier => "Modifier",
+            HotkeyCategory::FunctionKey => "FunctionKey",
+            HotkeyCategory::Special => "Special",
+        }
+    }
+}
+
+/// Names (case-insensitive) that all map to one key
+struct HotkeyName {
+    names: &'static [&'static str],
+    key: Key,
+    display_name: &'static str,
+    category: HotkeyCategory,
+}
+
+const fn hotkey(
+    names: &'static [&'static str],
+    key: Key,
+    display_name: &'static str,
+    category: HotkeyCategory,
+) -> HotkeyName {
+    HotkeyName {
+        names,
+        key,
+        display_name,
+        category,
+    }
+}
+
+use HotkeyCategory::{FunctionKey, Modifier, Special};
+
+/// Every accepted hotkey name; `parse_hotkey` and `supported_hotkeys` both read it
+const HOTKEY_NAMES: &[HotkeyName] = &[
+    hotkey(&["ctrl", "control"], Key::ControlLeft, "Ctrl", Modifier),
+    hotkey(
+        &["ctrl_l", "control_l"],
+        Key::ControlLeft,
+        "Left Ctrl",
+        Modifier,
+    ),
+    hotkey(
+        &["ctrl_r", "control_r"],
+        Key::ControlRight,
+        "Right Ctrl",
+        Modifier,
+    ),
+    hotkey(&["alt"], Key::Alt, "Alt", Modifier),
+    hotkey(&["alt_l"], Key::Alt, "Left Alt", Modifier),
+    hotkey(&["alt_r"], Key::AltGr, "Right Alt", Modifier),
+    hotkey(&["shift"], Key::ShiftLeft, "Shift", Modifier),
+    hotkey(&["shift_l"], Key::ShiftLeft, "Left Shift", Modifier),
+    hotkey(&["shift_r"], Key::ShiftRight, "Right Shift", Modifier),
+    hotkey(
+        &["cmd", "super", "meta"],
+        Key::MetaLeft,
+        "Cmd / Super",
+        Modifier,
+    ),
+    hotkey(
+        &["cmd_l", "super_l", "meta_l"],
+        Key::MetaLeft,
+        "Left Cmd / Super",
+        Modifier,
+    ),
+    hotkey(
+        &["cmd_r", "super_r", "meta_r"],
+        Key::MetaRight,
+        "Right Cmd / Super",
+        Modifier,
+    ),
+    hotkey(&["f1"], Key::F1, "F1", FunctionKey),
+    hotkey(&["f2"], Key::F2, "F2", FunctionKey),
+    hotkey(&["f3"], Key::F3, "F3", FunctionKey),
+    hotkey(&["f4"], Key::F4, "F4", FunctionKey),
+    hotkey(&["f5"], Key::F5, "F5", FunctionKey),
+    hotkey(&["f6"], Key::F6, "F6", FunctionKey),
+    hotkey(&["f7"], Key::F7, "F7", FunctionKey),
+    hotkey(&["f8"], Key::F8, "F8", FunctionKey),
+    hotkey(&["f9"], Key::F9, "F9", FunctionKey),
+    hotkey(&["f10"], Key::F10, "F10", FunctionKey),
+    hotkey(&["f11"], Key::F11, "F11", FunctionKey),
+    hotkey(&["f12"], Key::F12, "F12", FunctionKey),
+    hotkey(&["space"], Key::Space, "Space", Special),
+    hotkey(&["tab"], Key::Tab, "Tab", Special),
+    hotkey(
+        &["caps_lock", "capslock"],
+        Key::CapsLock,
+        "Caps Lock",
+        Special,
+    ),
+    hotkey(
+        &["scroll_lock", "scrolllock"],
+        Key::ScrollLock,
+        "Scroll Lock",
+        Special,
+    ),
+    hotkey(&["pause"], Key::Pause, "Pause", Special),
+    hotkey(&["insert"], Key::Insert, "Insert", Special),
+    hotkey(
+        &["backquote", "`"],
+        Key::BackQuote,
+        "Backquote (`)",
+        Special,
+    ),
+];
+
+/// Hotkey name for the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyInfo {
+    /// String to put in the config
+    pub key: String,
+    pub display_name: String,
+    /// `Modifier`, `FunctionKey` or `Special`
+    pub category: String,
+}
+
+/// Convert hotkey string to rdev Key
+pub(super) fn parse_hotkey(hotkey: &str) -> Option<Key> {
+    let hotkey = hotkey.to_lowercase();
+    HOTKEY_NAMES
+        .iter()
+        .find(|entry| entry.names.contains(&hotkey.as_str()))
+        .map(|entry| entry.key)
+}
+
+/// Whether `hotkey` names a key the listener recognises
+pub fn is_valid_hotkey(hotkey: &str) -> bool {
+    parse_hotkey(hotkey).is_some()
+}
+
+/// Every accepted hotkey name, sorted by category then name
+pub fn supported_hotkeys() -> Vec<HotkeyInfo> {
+    let mut names: Vec<(HotkeyCategory, &str, &str)> = HOTKEY_NAMES
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .names
+                .iter()
+                .map(move |name| (entry.category, *name, entry.display_name))
+        })
+        .collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|(category, key, display_name)| HotkeyInfo {
+            key: key.to_string(),
+            display_name: display_name.to_string(),
+            category: category.as_str().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_supported_hotkey_parses() {
+        let hotkeys = supported_hotkeys();
+
+        assert!(hotkeys.iter().all(|info| is_valid_hotkey(&info.key)));
+        assert_eq!(parse_hotkey("Control_R"), Some(Key::ControlRight));
+        assert_eq!(parse_hotkey("enter"), None);
+        assert_eq!(hotkeys[0].category, "Modifier");
+        assert_eq!(hotkeys.last().unwrap().category, "Special");
+    }
+}
@@ -1,33 +1,71 @@
-use base64::Engine as _;
-use log::{error, info};
+use log::{error, info, warn};
 use parking_lot::Mutex;
 use rdev::{listen, Event, EventType, Key};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut, ShortcutState};
 
-use crate::APP_STATE;
+use super::keys::parse_hotkey;
+use crate::audio::notification::{play_notification, NotificationKind};
+use crate::audio::vad::VadDetector;
+use crate::audio::{compression, processing};
+use crate::config::TriggerMode;
+use crate::engines::{Engine, PartialResultCallback, StreamingSession, VerboseTranscription};
+use crate::error::report_error;
+use crate::window_info::WindowInfo;
+use crate::{set_tray_state, unix_time_ms, TrayState, APP_STATE, LEVEL_HISTORY_CAPACITY};
 
-/// Hotkey manager for handling press-and-hold detection
+/// Samples quieter than this at either end of a recording are trimmed
+const TRIM_THRESHOLD: i16 = 500;
+
+/// Audio kept after trimming even if the recording is silent
+const TRIM_MIN_SPEECH_MS: u32 = 300;
+
+/// How often the VAD thread samples the input level
+const VAD_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Bumped on every hotkey press so a pending idle release knows to back off
+static IDLE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// State of one recording, shared between the press and release handlers
+#[derive(Default)]
+struct RecordingSession {
+    /// Window that was focused when recording started
+    window: Mutex<Option<WindowInfo>>,
+    /// Live engine session fed while recording, when streaming mode is on
+    stream: Mutex<Option<StreamingSession>>,
+}
+
+/// Hotkey manager for handling hold, double-tap and push-to-talk detection
 pub struct HotkeyManager {
     hotkey: String,
-    hold_time: Duration,
+    key: Key,
+    language: String,
+    trigger_mode: TriggerMode,
     press_time: Arc<Mutex<Option<Instant>>>,
+    /// Start of the previous tap, while waiting for a second one in double-tap mode
+    last_press: Mutex<Option<Instant>>,
     is_recording: Arc<AtomicBool>,
     hold_triggered: Arc<AtomicBool>,
     app_handle: Arc<Mutex<Option<AppHandle>>>,
+    session: Arc<RecordingSession>,
 }
 
 impl HotkeyManager {
-    pub fn new(hotkey: &str, hold_time: f64) -> Self {
+    pub fn new(hotkey: &str, trigger_mode: TriggerMode, language: &str) -> Self {
         Self {
             hotkey: hotkey.to_lowercase(),
-            hold_time: Duration::from_secs_f64(hold_time),
+            key: parse_hotkey_or_default(hotkey),
+            language: language.to_string(),
+            trigger_mode,
             press_time: Arc::new(Mutex::new(None)),
+            last_press: Mutex::new(None),
             is_recording: Arc::new(AtomicBool::new(false)),
             hold_triggered: Arc::new(AtomicBool::new(false)),
             app_handle: Arc::new(Mutex::new(None)),
+            session: Arc::new(RecordingSession::default()),
         }
     }
 
@@ -37,10 +75,12 @@ impl HotkeyManager {
 
     pub fn update_hotkey(&mut self, hotkey: &str) {
         self.hotkey = hotkey.to_lowercase();
+        self.key = parse_hotkey_or_default(hotkey);
     }
 
-    pub fn update_hold_time(&mut self, hold_time: f64) {
-        self.hold_time = Duration::from_secs_f64(hold_time);
+    pub fn update_trigger_mode(&mut self, trigger_mode: TriggerMode) {
+        self.trigger_mode = trigger_mode;
+        *self.last_press.lock() = None;
     }
 
     pub fn get_hotkey(&self) -> &str {
@@ -53,110 +93,201 @@ impl HotkeyManager {
             None => return,
         };
 
+        IDLE_GENERATION.fetch_add(1, Ordering::SeqCst);
+        if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
+            recorder.reacquire_device();
+        }
+
+        if APP_STATE.config.read().core.asr.vad_mode {
+            self.on_press_vad(app);
+            return;
+        }
+
+        match self.trigger_mode {
+            TriggerMode::Hold { hold_time_secs } => {
+                self.on_press_hold(app, Duration::from_secs_f64(hold_time_secs))
+            }
+            TriggerMode::DoubleTap { max_interval_ms } => {
+                self.on_press_double_tap(app, Duration::from_millis(max_interval_ms))
+            }
+            TriggerMode::PushToTalk => self.on_press_push_to_talk(app),
+        }
+    }
+
+    /// Push-to-talk mode: start recording right away, release stops it
+    fn on_press_push_to_talk(&self, app: AppHandle) {
+        let mut press_time = self.press_time.lock();
+        if press_time.is_some() {
+            // Key auto-repeat while held
+            return;
+        }
+        *press_time = Some(Instant::now());
+        if !claim_recognition_slot(&app) {
+            return;
+        }
+
+        info!("Hotkey {} pressed, starting recording", self.hotkey);
+        self.hold_triggered.store(true, Ordering::SeqCst);
+        self.is_recording.store(true, Ordering::SeqCst);
+        begin_recording(&app, &self.session, &self.language);
+    }
+
+    /// Hold mode: start recording once the key has been held for `hold_time`
+    fn on_press_hold(&self, app: AppHandle, hold_time: Duration) {
         let mut press_time = self.press_time.lock();
         if press_time.is_none() {
             *press_time = Some(Instant::now());
             info!("Hotkey {} pressed, waiting for hold...", self.hotkey);
 
             // Spawn a timer to check hold time
-            let hold_time = self.hold_time;
             let press_time_arc = Arc::clone(&self.press_time);
             let is_recording = Arc::clone(&self.is_recording);
             let hold_triggered = Arc::clone(&self.hold_triggered);
+            let session = Arc::clone(&self.session);
+            let language = self.language.clone();
             let app_handle = app.clone();
 
             std::thread::spawn(move || {
                 std::thread::sleep(hold_time);
 
                 // Check if still pressed
-                if press_time_arc.lock().is_some() && !hold_triggered.load(Ordering::SeqCst) {
+                if press_time_arc.lock().is_some()
+                    && !hold_triggered.load(Ordering::SeqCst)
+                    && claim_recognition_slot(&app_handle)
+                {
                     hold_triggered.store(true, Ordering::SeqCst);
                     info!("Hold time reached, starting recording");
 
-                    // Start recording
                     is_recording.store(true, Ordering::SeqCst);
+                    begin_recording(&app_handle, &session, &language);
+                }
+            });
+        }
+    }
 
-                    // Get focused window info and emit app-info event
-                    if let Some(info) = crate::window_info::get_focused_window_info() {
-                        // Convert icon to base64 data URL if it exists
-                        let icon_data = info.icon_path.as_ref().and_then(|path| {
-                            std::fs::read(path).ok().map(|data| {
-                                let ext = std::path::Path::new(path)
-                                    .extension()
-                                    .and_then(|e| e.to_str())
-                                    .unwrap_or("png");
-                                let mime = match ext {
-                                    "svg" => "image/svg+xml",
-                                    "png" => "image/png",
-                                    "jpg" | "jpeg" => "image/jpeg",
-                                    _ => "image/png",
-                                };
-                                format!(
-                                    "data:{};base64,{}",
-                                    mime,
-                                    base64::Engine::encode(
-                                        &base64::engine::general_purpose::STANDARD,
-                                        &data
-                                    )
-                                )
-                            })
-                        });
+    /// Double-tap mode: two quick presses start recording, two more stop it
+    fn on_press_double_tap(&self, app: AppHandle, max_interval: Duration) {
+        let mut press_time = self.press_time.lock();
+        if press_time.is_some() {
+            // Key auto-repeat while held
+            return;
+        }
+        let now = Instant::now();
+        *press_time = Some(now);
 
-                        let _ = app_handle.emit(
-                            "app-info",
-                            serde_json::json!({
-                                "name": info.app_name,
-                                "icon": icon_data
-                            }),
-                        );
-                        info!(
-                            "Focused app: {} (icon: {})",
-                            info.app_name,
-                            icon_data.is_some()
-                        );
-                    }
+        let mut last_press = self.last_press.lock();
+        let is_double_tap = last_press.is_some_and(|last| now - last <= max_interval);
+        if !is_double_tap {
+            // A single tap does nothing until a second one follows
+            *last_press = Some(now);
+            return;
+        }
+        *last_press = None;
 
-                    // Emit recording state event
-                    let _ = app_handle.emit(
-                        "recording-state",
-                        serde_json::json!({
-                            "state": "started"
-                        }),
-                    );
+        if self.hold_triggered.swap(false, Ordering::SeqCst) {
+            info!("Hotkey {} double-tapped, stopping recording", self.hotkey);
+            self.is_recording.store(false, Ordering::SeqCst);
+            finish_recording(&app, &self.session, &self.language);
+        } else if claim_recognition_slot(&app) {
+            info!("Hotkey {} double-tapped, starting recording", self.hotkey);
+            self.hold_triggered.store(true, Ordering::SeqCst);
+            self.is_recording.store(true, Ordering::SeqCst);
+            begin_recording(&app, &self.session, &self.language);
+        }
+    }
 
-                    // Show main window
-                    if let Some(window) = app_handle.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
+    /// VAD mode: a press starts recording, silence (or a second press) stops it
+    fn on_press_vad(&self, app: AppHandle) {
+        let mut press_time = self.press_time.lock();
+        if press_time.is_some() {
+            // Key auto-repeat while held
+            return;
+        }
+        *press_time = Some(Instant::now());
 
-                    // Start audio recording
-                    if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
-                        // Set up audio level callback
-                        let app_for_level = app_handle.clone();
-                        recorder.set_audio_level_callback(move |level| {
-                            // Multiply by 3 to match Python implementation
-                            let _ = app_for_level.emit(
-                                "audio-level",
-                                serde_json::json!({
-                                    "level": level * 3.0
-                                }),
-                            );
-                        });
-
-                        if let Err(e) = recorder.start() {
-                            error!("Failed to start recording: {}", e);
-                            let _ = app_handle.emit(
-                                "recognition-error",
-                                serde_json::json!({
-                                    "message": e
-                                }),
-                            );
-                        }
-                    }
+        if self.hold_triggered.swap(false, Ordering::SeqCst) {
+            info!(
+                "Hotkey {} pressed again, stopping VAD recording",
+                self.hotkey
+            );
+            self.is_recording.store(false, Ordering::SeqCst);
+            finish_recording(&app, &self.session, &self.language);
+            return;
+        }
+        if !claim_recognition_slot(&app) {
+            return;
+        }
+
+        info!("Hotkey {} pressed, starting VAD recording", self.hotkey);
+        self.hold_triggered.store(true, Ordering::SeqCst);
+        self.is_recording.store(true, Ordering::SeqCst);
+        begin_recording(&app, &self.session, &self.language);
+
+        let (threshold, silence_ms) = {
+            let config = APP_STATE.config.read();
+            (
+                config.core.asr.noise_gate_threshold,
+                config.core.asr.vad_silence_ms,
+            )
+        };
+        let is_recording = Arc::clone(&self.is_recording);
+        let hold_triggered = Arc::clone(&self.hold_triggered);
+        let session = Arc::clone(&self.session);
+        let language = self.language.clone();
+
+        std::thread::spawn(move || {
+            let mut detector = VadDetector::new(threshold, Duration::from_millis(silence_ms));
+
+            while hold_triggered.load(Ordering::SeqCst) {
+                std::thread::sleep(VAD_POLL_INTERVAL);
+
+                let level = match *APP_STATE.recorder.read() {
+                    Some(ref recorder) => recorder.get_noise_level(),
+                    None => 0.0,
+                };
+
+                if detector.update(level) && hold_triggered.swap(false, Ordering::SeqCst) {
+                    info!("Silence detected, stopping VAD recording");
+                    is_recording.store(false, Ordering::SeqCst);
+                    finish_recording(&app, &session, &language);
+                    break;
                 }
-            });
+            }
+        });
+    }
+
+    /// Start recording without a key press, e.g. for a remote client.
+    /// Returns false if this manager is already recording or is rate limited.
+    pub fn start_programmatic(&self) -> bool {
+        let Some(app) = self.app_handle.lock().clone() else {
+            return false;
+        };
+        if self.hold_triggered.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        if !claim_recognition_slot(&app) {
+            self.hold_triggered.store(false, Ordering::SeqCst);
+            return false;
+        }
+        info!("Starting recording programmatically");
+        self.is_recording.store(true, Ordering::SeqCst);
+        begin_recording(&app, &self.session, &self.language);
+        true
+    }
+
+    /// Stop a recording and recognize it, however it was started.
+    /// Returns false if this manager is not recording.
+    pub fn stop_programmatic(&self) -> bool {
+        let Some(app) = self.app_handle.lock().clone() else {
+            return false;
+        };
+        if !self.hold_triggered.swap(false, Ordering::SeqCst) {
+            return false;
         }
+        info!("Stopping recording programmatically");
+        self.is_recording.store(false, Ordering::SeqCst);
+        finish_recording(&app, &self.session, &self.language);
+        true
     }
 
     pub fn on_release(&self) {
@@ -168,143 +299,575 @@ impl HotkeyManager {
         let mut press_time = self.press_time.lock();
         *press_time = None;
 
-        if self.hold_triggered.swap(false, Ordering::SeqCst) {
-            info!("Hotkey released, stopping recording");
-            self.is_recording.store(false, Ordering::SeqCst);
+        if APP_STATE.config.read().core.asr.vad_mode {
+            // Recording is stopped by the VAD thread
+            return;
+        }
+
+        match self.trigger_mode {
+            TriggerMode::Hold { .. } | TriggerMode::PushToTalk => {
+                if self.hold_triggered.swap(false, Ordering::SeqCst) {
+                    info!("Hotkey released, stopping recording");
+                    self.is_recording.store(false, Ordering::SeqCst);
+                    finish_recording(&app, &self.session, &self.language);
+                } else {
+                    info!("Released before hold time, ignoring");
+                }
+            }
+            TriggerMode::DoubleTap { .. } => {
+                // Recording is stopped by the next double-tap
+            }
+        }
+    }
+}
+
+/// Build a callback that forwards partial results to the frontend
+fn partial_result_callback(app: &AppHandle) -> PartialResultCallback {
+    let app = app.clone();
+    Box::new(move |text: &str| {
+        let _ = app.emit(
+            "partial-result",
+            serde_json::json!({
+                "text": text,
+                "emitted_at_ms": unix_time_ms()
+            }),
+        );
+    })
+}
+
+/// Open a live session on the current engine; `None` falls back to recognizing after release
+fn open_streaming_session(app: &AppHandle, language: &str) -> Option<StreamingSession> {
+    let engine = APP_STATE.engine.read();
+    let engine = engine.as_ref()?;
+    if !streams_live(engine.as_ref()) {
+        return None;
+    }
+
+    match engine.start_streaming_session(language, partial_result_callback(app)) {
+        Ok(stream) => {
+            info!("Streaming audio to {} while recording", engine.name());
+            Some(stream)
+        }
+        Err(e) => {
+            warn!(
+                "Live streaming unavailable, recognizing after release: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Whether a live session can stand in for recognizing after release; a
+/// session only returns the final text, so engines returning alternatives or
+/// timestamps recognize after release
+fn streams_live(engine: &dyn Engine) -> bool {
+    engine.supports_streaming() && !engine.supports_alternatives() && engine.as_verbose().is_none()
+}
+
+/// Capture the focused window, show the floating window and start the recorder
+fn begin_recording(app_handle: &AppHandle, session: &Arc<RecordingSession>, language: &str) {
+    // Get focused window info and emit app-info event
+    let focused = crate::window_info::get_focused_window_info();
+    *session.window.lock() = focused.clone();
+    if let Some(info) = focused {
+        let _ = app_handle.emit(
+            "app-info",
+            serde_json::json!({
+                "name": info.app_name,
+                "icon": info.icon_data
+            }),
+        );
+        info!(
+            "Focused app: {} (icon: {})",
+            info.app_name,
+            info.icon_data.is_some()
+        );
+    }
 
-            // Emit recognizing state
-            let _ = app.emit(
-                "recording-state",
+    // Emit recording state event
+    let _ = app_handle.emit(
+        "recording-state",
+        serde_json::json!({
+            "state": "started"
+        }),
+    );
+    set_tray_state(app_handle, TrayState::Recording);
+
+    // Show main window
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    // Let focus settle before audio starts flowing
+    let delay_ms = APP_STATE.config.read().core.asr.pre_recording_delay_ms;
+    if delay_ms > 0 {
+        std::thread::sleep(Duration::from_millis(delay_ms));
+    }
+
+    let stream = if APP_STATE.config.read().core.asr.streaming_mode {
+        open_streaming_session(app_handle, language)
+    } else {
+        None
+    };
+    let streaming = stream.is_some();
+    *session.stream.lock() = stream;
+
+    // Start audio recording
+    if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
+        if streaming {
+            let session = Arc::clone(session);
+            recorder.set_audio_data_callback(move |pcm| {
+                if let Some(ref mut stream) = *session.stream.lock() {
+                    stream.push_chunk(pcm);
+                }
+            });
+        } else {
+            recorder.clear_audio_data_callback();
+        }
+
+        // Set up audio level callback
+        let app_for_level = app_handle.clone();
+        let level_history = Arc::clone(&APP_STATE.level_history);
+        level_history.lock().clear();
+        recorder.set_audio_level_callback(move |level, levels| {
+            // Multiply by 3 to match Python implementation
+            let level = level * 3.0;
+            {
+                let mut history = level_history.lock();
+                if history.len() == LEVEL_HISTORY_CAPACITY {
+                    history.pop_front();
+                }
+                history.push_back(level);
+            }
+            let _ = app_for_level.emit(
+                "audio-level",
                 serde_json::json!({
-                    "state": "recognizing"
+                    "level": level,
+                    "rms": levels.rms,
+                    "peak": levels.peak
                 }),
             );
+        });
 
-            // Stop recording and get audio data
-            let audio_data = if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
-                recorder.stop()
-            } else {
-                Vec::new()
-            };
-
-            if audio_data.is_empty() {
-                let _ = app.emit(
-                    "recognition-error",
+        // Count clipped samples and warn about chunks that clip noticeably
+        let app_for_clip = app_handle.clone();
+        let recorder_stats = Arc::clone(&APP_STATE.recorder_stats);
+        recorder.set_audio_clip_callback(move |chunk| {
+            recorder_stats.lock().record(chunk);
+            if chunk.is_clip_event() {
+                let _ = app_for_clip.emit(
+                    "audio-clip",
                     serde_json::json!({
-                        "message": "No audio captured"
+                        "clipped_fraction": chunk.fraction(),
+                        "chunk_index": chunk.chunk_index
                     }),
                 );
-                return;
             }
+        });
 
-            // Perform recognition
-            let app_handle = app.clone();
-            let config = APP_STATE.config.read().clone();
+        if let Err(e) = recorder.start() {
+            error!("Failed to start recording: {}", e);
+            let _ = app_handle.emit(
+                "recognition-error",
+                serde_json::json!({
+                    "message": e
+                }),
+            );
+            set_tray_state(app_handle, TrayState::Idle);
+            play_notification(NotificationKind::Error);
+        } else {
+            APP_STATE.start_extra_recorders();
+            *APP_STATE.recording_start_time.lock() = Some(Instant::now());
+            play_notification(NotificationKind::StartRecording);
+        }
+    }
+}
 
-            std::thread::spawn(move || {
-                // Create callback for partial results
-                let app_for_partial = app_handle.clone();
-                let partial_callback = Box::new(move |text: &str| {
-                    let _ = app_for_partial.emit(
-                        "partial-result",
+/// Close the input devices once they have sat idle for `idle_release_delay_ms`
+fn schedule_device_release() {
+    let delay_ms = APP_STATE.config.read().core.asr.idle_release_delay_ms;
+    if delay_ms == 0 {
+        return;
+    }
+    let generation = IDLE_GENERATION.load(Ordering::SeqCst);
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        if IDLE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
+            recorder.release_device();
+        }
+        for recorder in APP_STATE.extra_recorders.write().iter_mut() {
+            recorder.release_device();
+        }
+    });
+}
+
+/// Time left before another recognition may start after one started at `last`
+fn rate_limit_wait(last: Option<Instant>, min_interval: Duration) -> Option<Duration> {
+    let remaining = min_interval.checked_sub(last?.elapsed())?;
+    (!remaining.is_zero()).then_some(remaining)
+}
+
+/// Guard against a burst of API calls from repeated hotkey presses: note the
+/// start of a recording, or emit `rate-limited` and return false when the
+/// previous one started less than `min_interval_between_calls_ms` ago
+fn claim_recognition_slot(app: &AppHandle) -> bool {
+    let min_interval =
+        Duration::from_millis(APP_STATE.config.read().engine.min_interval_between_calls_ms);
+    let mut last_started = APP_STATE.last_transcription_started.lock();
+    if let Some(wait) = rate_limit_wait(*last_started, min_interval) {
+        warn!("Not starting a recording, next one allowed in {:?}", wait);
+        let _ = app.emit(
+            "rate-limited",
+            serde_json::json!({
+                "retry_after_ms": wait.as_millis() as u64
+            }),
+        );
+        play_notification(NotificationKind::Error);
+        return false;
+    }
+    *last_started = Some(Instant::now());
+    true
+}
+
+/// Stop the recorder and run recognition on a background thread
+fn finish_recording(app: &AppHandle, session: &RecordingSession, language: &str) {
+    // Emit recognizing state
+    let _ = app.emit(
+        "recording-state",
+        serde_json::json!({
+            "state": "recognizing"
+        }),
+    );
+    set_tray_state(app, TrayState::Processing);
+    play_notification(NotificationKind::StopRecording);
+
+    // Stop recording and get audio data
+    let recording_started_at_ms = APP_STATE
+        .recording_start_time
+        .lock()
+        .take()
+        .map(|start| unix_time_ms().saturating_sub(start.elapsed().as_millis() as u64));
+    let (audio_data, duration_ms) = if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
+        let audio_data = recorder.stop();
+        recorder.clear_audio_data_callback();
+        (audio_data, recorder.duration_ms())
+    } else {
+        (Vec::new(), 0)
+    };
+    let audio_data = APP_STATE.stop_extra_recorders(audio_data);
+    schedule_device_release();
+    // The mix is as long as the longest recording
+    let duration_ms = APP_STATE
+        .extra_recorders
+        .read()
+        .iter()
+        .map(|recorder| recorder.duration_ms())
+        .fold(duration_ms, u64::max);
+    // Dropping an unfinished session closes it
+    let stream = session.stream.lock().take();
+
+    if audio_data.is_empty() {
+        let _ = app.emit(
+            "recognition-error",
+            serde_json::json!({
+                "message": "No audio captured"
+            }),
+        );
+        set_tray_state(app, TrayState::Idle);
+        play_notification(NotificationKind::Error);
+        return;
+    }
+
+    // Don't send leading/trailing silence to the engine
+    let audio_data = match (
+        processing::wav_samples(&audio_data),
+        processing::parse_wav_sample_rate(&audio_data),
+    ) {
+        (Some(samples), Some(sample_rate)) => {
+            let trimmed =
+                processing::trim_silence(&samples, TRIM_THRESHOLD, TRIM_MIN_SPEECH_MS, sample_rate);
+            info!(
+                "Trimmed silence: {} -> {} samples",
+                samples.len(),
+                trimmed.len()
+            );
+            processing::encode_wav(trimmed, sample_rate)
+        }
+        _ => audio_data,
+    };
+
+    // Perform recognition
+    let app_handle = app.clone();
+    let config = APP_STATE.config.read().clone();
+    let language = language.to_string();
+    let window = session.window.lock().take();
+    let hints = window
+        .as_ref()
+        .map(|info| config.vocab_hints_for(&info.wm_class, &info.wm_instance))
+        .unwrap_or_default();
+    let (wm_class, app_name) = window
+        .map(|info| (info.wm_class, info.app_name))
+        .unwrap_or_default();
+    let transforms = config.core.transforms.clone();
+    let profanity_filter = config.core.profanity_filter.clone();
+    let post_process_command = config.core.post_process_command.clone();
+    let auto_export = config.core.auto_export.clone();
+    let retry = config.engine.retry.clone();
+    let compression = config.core.asr.audio_compression;
+    let preview_mode = config.core.asr.preview_mode;
+    let multi_language = config.core.asr.multi_language.is_some();
+
+    std::thread::spawn(move || {
+        // Send the remaining audio of a live session and collect its transcript
+        let streamed = match stream.map(StreamingSession::finish) {
+            Some(Ok(text)) => Some(text),
+            Some(Err(e)) => {
+                warn!(
+                    "Streaming session failed, recognizing full recording: {}",
+                    e
+                );
+                None
+            }
+            None => None,
+        };
+
+        let mut engine_name = String::new();
+        let mut detected_language = None;
+        let mut verbose = None;
+        let mut alternatives = Vec::new();
+        let result = if let Some(ref engine) = *APP_STATE.engine.read() {
+            engine_name = engine.name().to_string();
+            let result = match streamed {
+                Some(text) => Ok(text),
+                None => {
+                    let audio_data = if engine.accepts_opus() {
+                        compression::compress(audio_data, compression)
+                    } else {
+                        audio_data
+                    };
+                    if let Some(verbose_engine) = engine.as_verbose() {
+                        crate::engines::retry_with_backoff(&retry, || {
+                            verbose_engine.transcribe_verbose(&audio_data, &language, &hints)
+                        })
+                        .map(|result| {
+                            let text = result.text.clone();
+                            alternatives = result.alternatives.clone();
+                            verbose = Some(result);
+                            text
+                        })
+                    } else if engine.supports_alternatives() {
+                        crate::engines::retry_with_backoff(&retry, || {
+                            engine.transcribe_nbest(&audio_data, &language, &hints)
+                        })
+                        .map(|ranked| {
+                            let text = ranked.first().cloned().unwrap_or_default();
+                            alternatives = ranked;
+                            text
+                        })
+                    } else {
+                        crate::engines::retry_with_backoff(&retry, || {
+                            engine.transcribe_with_hints(
+                                &audio_data,
+                                &language,
+                                &hints,
+                                partial_result_callback(&app_handle),
+                            )
+                        })
+                    }
+                }
+            };
+            if language == crate::engines::AUTO_LANGUAGE || multi_language {
+                detected_language = engine.detected_language();
+            }
+            result
+        } else {
+            Err("No engine configured".to_string())
+        };
+
+        match result {
+            Ok(text) => {
+                info!("Recognition result: {}", text);
+                if multi_language {
+                    emit_segment_languages(
+                        &app_handle,
+                        verbose.as_ref(),
+                        detected_language.as_deref(),
+                        duration_ms,
+                    );
+                }
+                if let Some(code) = detected_language {
+                    info!("Detected language: {}", code);
+                    let _ = app_handle.emit(
+                        "language-detected",
                         serde_json::json!({
-                            "text": text
+                            "language": code
                         }),
                     );
-                });
-
-                let result = if let Some(ref engine) = *APP_STATE.engine.read() {
-                    engine.transcribe_with_callback(
-                        &audio_data,
-                        &config.core.asr.language,
-                        partial_callback,
-                    )
-                } else {
-                    Err("No engine configured".to_string())
-                };
+                }
+                let mut text = crate::text::apply_transforms(&text, &transforms);
+                if let Some(ref filter) = profanity_filter {
+                    text = crate::text::apply_profanity_filter(&text, filter);
+                }
+                if let Some(ref command) = post_process_command {
+                    match crate::text::run_post_process(command, &text) {
+                        Ok(processed) => text = processed,
+                        Err(e) => report_error(&app_handle, "post_process", &e, true),
+                    }
+                }
+                if !text.is_empty() {
+                    crate::history::push(
+                        &APP_STATE.history,
+                        crate::history::TranscriptionRecord::new(
+                            &text,
+                            &app_name,
+                            &engine_name,
+                            duration_ms,
+                        ),
+                    );
 
-                match result {
-                    Ok(text) => {
-                        info!("Recognition result: {}", text);
+                    if let Some(ref export) = auto_export {
+                        if let Err(e) = crate::output::append_transcript(export, &text) {
+                            report_error(
+                                &app_handle,
+                                "export",
+                                &format!("Failed to export transcript: {}", e),
+                                true,
+                            );
+                        }
+                    }
+                }
+                let _ = app_handle.emit(
+                    "final-result",
+                    crate::final_result_payload(&text, recording_started_at_ms),
+                );
+                // Timestamps refer to the engine's transcript, before transforms
+                if let Some(ref verbose) = verbose {
+                    let _ = app_handle.emit("verbose-result", verbose);
+                }
+                if alternatives.len() > 1 {
+                    let _ = app_handle.emit(
+                        "nbest-result",
+                        serde_json::json!({
+                            "alternatives": alternatives
+                        }),
+                    );
+                }
+                if !text.is_empty() {
+                    crate::audio::tts::speak_result(&text);
+                }
+
+                if preview_mode {
+                    // Leave the window up so the text can be checked before `confirm_paste`
+                    if !text.is_empty() {
+                        let id = uuid::Uuid::new_v4().to_string();
+                        APP_STATE
+                            .pending_previews
+                            .lock()
+                            .insert(id.clone(), text.clone());
                         let _ = app_handle.emit(
-                            "final-result",
+                            "preview-result",
                             serde_json::json!({
-                                "text": text.clone()
+                                "id": id,
+                                "text": text
                             }),
                         );
-
-                        // Paste text to current application
-                        if !text.is_empty() {
-                            if let Err(e) = crate::input::paste_text(&app_handle, &text) {
-                                error!("Failed to paste text: {}", e);
-                            } else {
-                                info!("Text pasted successfully");
+                    }
+                } else {
+                    // Paste text to current application
+                    if !text.is_empty() {
+                        if let Err(e) = crate::input::paste_text(&app_handle, &text) {
+                            report_error(
+                                &app_handle,
+                                "paste",
+                                &format!("Failed to paste text: {}", e),
+                                true,
+                            );
+                        } else {
+                            info!("Text pasted successfully");
+                            if !wm_class.is_empty() {
+                                crate::history::recent_apps::record_use(
+                                    &APP_STATE.recent_apps,
+                                    &wm_class,
+                                    &app_name,
+                                );
                             }
                         }
-
-                        // Hide window after a delay
-                        std::thread::sleep(Duration::from_millis(500));
-                        if let Some(window) = app_handle.get_webview_window("main") {
-                            let _ = window.hide();
-                        }
                     }
-                    Err(e) => {
-                        error!("Recognition error: {}", e);
-                        let _ = app_handle.emit(
-                            "recognition-error",
-                            serde_json::json!({
-                                "message": e
-                            }),
-                        );
+
+                    // Hide window after a delay
+                    std::thread::sleep(Duration::from_millis(500));
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        let _ = window.hide();
                     }
                 }
-            });
-        } else {
-            info!("Released before hold time, ignoring");
+            }
+            Err(e) => {
+                error!("Recognition error: {}", e);
+                play_notification(NotificationKind::Error);
+                let _ = app_handle.emit(
+                    "recognition-error",
+                    serde_json::json!({
+                        "message": e
+                    }),
+                );
+            }
         }
+
+        set_tray_state(&app_handle, TrayState::Idle);
+    });
+}
+
+/// Emit `detected-language` for each segment found by `segment_languages`
+fn emit_segment_languages(
+    app: &AppHandle,
+    verbose: Option<&VerboseTranscription>,
+    detected: Option<&str>,
+    duration_ms: u64,
+) {
+    for (language, start_ms, end_ms) in segment_languages(verbose, detected, duration_ms) {
+        let _ = app.emit(
+            "detected-language",
+            serde_json::json!({
+                "language": language,
+                "start_ms": start_ms,
+                "end_ms": end_ms
+            }),
+        );
     }
 }
 
-/// Convert hotkey string to rdev Key
-fn parse_hotkey(hotkey: &str) -> Option<Key> {
-    let key = hotkey.to_lowercase();
-
-    match key.as_str() {
-        // Modifier keys (now supported with rdev!)
-        "ctrl" | "control" => Some(Key::ControlLeft),
-        "ctrl_l" | "control_l" => Some(Key::ControlLeft),
-        "ctrl_r" | "control_r" => Some(Key::ControlRight),
-        "alt" => Some(Key::Alt),
-        "alt_l" => Some(Key::Alt),
-        "alt_r" => Some(Key::AltGr),
-        "shift" => Some(Key::ShiftLeft),
-        "shift_l" => Some(Key::ShiftLeft),
-        "shift_r" => Some(Key::ShiftRight),
-        "cmd" | "super" | "meta" => Some(Key::MetaLeft),
-        "cmd_l" | "super_l" | "meta_l" => Some(Key::MetaLeft),
-        "cmd_r" | "super_r" | "meta_r" => Some(Key::MetaRight),
-        // Function keys
-        "f1" => Some(Key::F1),
-        "f2" => Some(Key::F2),
-        "f3" => Some(Key::F3),
-        "f4" => Some(Key::F4),
-        "f5" => Some(Key::F5),
-        "f6" => Some(Key::F6),
-        "f7" => Some(Key::F7),
-        "f8" => Some(Key::F8),
-        "f9" => Some(Key::F9),
-        "f10" => Some(Key::F10),
-        "f11" => Some(Key::F11),
-        "f12" => Some(Key::F12),
-        // Other keys
-        "space" => Some(Key::Space),
-        "tab" => Some(Key::Tab),
-        "caps_lock" | "capslock" => Some(Key::CapsLock),
-        "scroll_lock" | "scrolllock" => Some(Key::ScrollLock),
-        "pause" => Some(Key::Pause),
-        "insert" => Some(Key::Insert),
-        "backquote" | "`" => Some(Key::BackQuote),
-        _ => None,
+/// Language, start and end of each utterance the engine labelled with a
+/// language, or the whole recording when it only reports one
+fn segment_languages<'a>(
+    verbose: Option<&'a VerboseTranscription>,
+    detected: Option<&'a str>,
+    duration_ms: u64,
+) -> Vec<(&'a str, u64, u64)> {
+    match verbose {
+        Some(verbose) if verbose.utterances.iter().any(|u| u.language.is_some()) => verbose
+            .utterances
+            .iter()
+            .filter_map(|u| Some((u.language.as_deref()?, u.start_ms, u.end_ms)))
+            .collect(),
+        _ => detected
+            .map(|code| (code, 0, duration_ms))
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Parse a hotkey string, falling back to Ctrl when it is not recognised
+fn parse_hotkey_or_default(hotkey: &str) -> Key {
+    match parse_hotkey(hotkey) {
+        Some(key) => key,
+        None => {
+            error!("Invalid hotkey: {}, using Ctrl as default", hotkey);
+            Key::ControlLeft
+        }
     }
 }
 
@@ -334,55 +897,185 @@ fn key_matches(event_key: &Key, target_key: &Key) -> bool {
     }
 }
 
-/// Start keyboard listener in a separate thread using rdev
-pub fn start_keyboard_listener(app: AppHandle) {
+/// Call `handler` for every manager bound to `key`
+fn dispatch_key(key: &Key, handler: impl Fn(&HotkeyManager)) {
+    let managers = APP_STATE.hotkey_manager.read();
+
+    // Prefer exact matches so e.g. "ctrl_r" and "ctrl" can be bound separately
+    if managers.iter().any(|manager| manager.key == *key) {
+        managers
+            .iter()
+            .filter(|manager| manager.key == *key)
+            .for_each(handler);
+    } else {
+        managers
+            .iter()
+            .filter(|manager| key_matches(key, &manager.key))
+            .for_each(handler);
+    }
+}
+
+/// (Re)create one hotkey manager for the primary hotkey and one per extra profile
+pub fn reload_hotkeys(app: AppHandle) {
     let config = APP_STATE.config.read();
-    let hotkey_str = config.core.asr.hotkey.clone();
-    let hold_time = config.core.asr.hotkey_hold_time;
+    let asr = &config.core.asr;
+
+    let mut managers = vec![HotkeyManager::new(
+        &asr.hotkey,
+        asr.trigger_mode,
+        &asr.language,
+    )];
+    for profile in &config.core.extra_hotkeys {
+        info!(
+            "Hotkey profile {:?}: {} -> {}",
+            profile.label, profile.hotkey, profile.language
+        );
+        managers.push(HotkeyManager::new(
+            &profile.hotkey,
+            asr.trigger_mode,
+            &profile.language,
+        ));
+    }
     drop(config);
 
-    // Create hotkey manager
-    let manager = HotkeyManager::new(&hotkey_str, hold_time);
-    manager.set_app_handle(app.clone());
-    *APP_STATE.hotkey_manager.write() = Some(manager);
+    for manager in &managers {
+        manager.set_app_handle(app.clone());
+    }
+    *APP_STATE.hotkey_manager.write() = managers;
 
-    let target_key = match parse_hotkey(&hotkey_str) {
-        Some(key) => key,
-        None => {
-            error!("Invalid hotkey: {}, using Ctrl as default", hotkey_str);
-            Key::ControlLeft
+    if !crate::permissions::accessibility_trusted() {
+        register_fallback_shortcuts(&app);
+    }
+}
+
+/// Register the hotkeys with the global-shortcut plugin, which works without
+/// Accessibility access but cannot bind a modifier key on its own
+fn register_fallback_shortcuts(app: &AppHandle) {
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        warn!("Failed to unregister global shortcuts: {}", e);
+    }
+
+    let keys: Vec<Key> = APP_STATE
+        .hotkey_manager
+        .read()
+        .iter()
+        .map(|manager| manager.key)
+        .collect();
+    for key in keys {
+        let Some(code) = shortcut_code(&key) else {
+            warn!(
+                "{:?} cannot be a global shortcut, it needs Accessibility access",
+                key
+            );
+            continue;
+        };
+        let shortcut = Shortcut::new(None, code);
+        // Profiles sharing a key are all dispatched by one shortcut
+        if shortcuts.is_registered(shortcut) {
+            continue;
         }
-    };
+        let result = shortcuts.on_shortcut(shortcut, move |_, _, event| match event.state {
+            ShortcutState::Pressed => dispatch_key(&key, HotkeyManager::on_press),
+            ShortcutState::Released => dispatch_key(&key, HotkeyManager::on_release),
+        });
+        match result {
+            Ok(()) => info!("Registered {:?} as a global shortcut", key),
+            Err(e) => warn!("Failed to register {:?} as a global shortcut: {}", key, e),
+        }
+    }
+}
+
+/// Key code the global-shortcut plugin registers for `key`, if it is not a modifier
+fn shortcut_code(key: &Key) -> Option<Code> {
+    Some(match key {
+        Key::F1 => Code::F1,
+        Key::F2 => Code::F2,
+        Key::F3 => Code::F3,
+        Key::F4 => Code::F4,
+        Key::F5 => Code::F5,
+        Key::F6 => Code::F6,
+        Key::F7 => Code::F7,
+        Key::F8 => Code::F8,
+        Key::F9 => Code::F9,
+        Key::F10 => Code::F10,
+        Key::F11 => Code::F11,
+        Key::F12 => Code::F12,
+        Key::Space => Code::Space,
+        Key::Tab => Code::Tab,
+        Key::CapsLock => Code::CapsLock,
+        Key::ScrollLock => Code::ScrollLock,
+        Key::Pause => Code::Pause,
+        Key::Insert => Code::Insert,
+        Key::BackQuote => Code::Backquote,
+        _ => return None,
+    })
+}
+
+/// Start recording with the primary hotkey's settings
+pub fn start_remote_recording() -> Result<(), String> {
+    let managers = APP_STATE.hotkey_manager.read();
+    let manager = managers.first().ok_or("Hotkeys are not initialized")?;
+    if manager.start_programmatic() {
+        Ok(())
+    } else {
+        Err("Already recording".to_string())
+    }
+}
+
+/// Stop the primary hotkey's recording and run recognition
+pub fn stop_remote_recording() -> Result<(), String> {
+    let managers = APP_STATE.hotkey_manager.read();
+    let manager = managers.first().ok_or("Hotkeys are not initialized")?;
+    if manager.stop_programmatic() {
+        Ok(())
+    } else {
+        Err("Not recording".to_string())
+    }
+}
+
+/// Start keyboard listener in a separate thread using rdev
+pub fn start_keyboard_listener(app: AppHandle) {
+    reload_hotkeys(app.clone());
+
+    // rdev gets no events without Accessibility access on macOS, so
+    // `reload_hotkeys` registered the hotkeys as global shortcuts instead
+    if !crate::permissions::accessibility_trusted() {
+        warn!("Accessibility access is missing, falling back to global shortcuts");
+        crate::permissions::report_required(
+            &app,
+            "accessibility",
+            crate::permissions::ACCESSIBILITY_GUIDE_URL,
+        );
+        return;
+    }
 
     info!(
-        "Starting keyboard listener for hotkey: {} (key: {:?})",
-        hotkey_str, target_key
+        "Starting keyboard listener for hotkeys: {:?}",
+        APP_STATE
+            .hotkey_manager
+            .read()
+            .iter()
+            .map(|manager| manager.get_hotkey().to_string())
+            .collect::<Vec<_>>()
     );
 
     // Start listener in a separate thread
     std::thread::spawn(move || {
-        let callback = move |event: Event| {
-            match event.event_type {
-                EventType::KeyPress(key) => {
-                    if key_matches(&key, &target_key) {
-                        if let Some(ref manager) = *APP_STATE.hotkey_manager.read() {
-                            manager.on_press();
-                        }
-                    }
-                }
-                EventType::KeyRelease(key) => {
-                    if key_matches(&key, &target_key) {
-                        if let Some(ref manager) = *APP_STATE.hotkey_manager.read() {
-                            manager.on_release();
-                        }
-                    }
-                }
-                _ => {}
-            }
+        let callback = move |event: Event| match event.event_type {
+            EventType::KeyPress(key) => dispatch_key(&key, HotkeyManager::on_press),
+            EventType::KeyRelease(key) => dispatch_key(&key, HotkeyManager::on_release),
+            _ => {}
         };
 
         if let Err(error) = listen(callback) {
-            error!("Keyboard listener error: {:?}", error);
+            // Hotkeys stay dead until the app is restarted
+            report_error(
+                &app,
+                "hotkey",
+                &format!("Keyboard listener error: {:?}", error),
+                false,
+            );
         }
     });
 
@@ -394,3 +1087,106 @@ pub fn register_hotkeys(app: AppHandle) -> Result<(), Box<dyn std::error::Error>
     start_keyboard_listener(app);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engines::MockEngine;
+
+    #[test]
+    fn every_supported_hotkey_is_listened_for() {
+        for info in crate::hotkey::supported_hotkeys() {
+            let key = parse_hotkey(&info.key).expect("supported hotkeys parse");
+            assert_eq!(parse_hotkey_or_default(&info.key), key, "{}", info.key);
+            assert!(key_matches(&key, &key), "{} should match itself", info.key);
+        }
+        assert_eq!(parse_hotkey_or_default("unknown_key_xyz"), Key::ControlLeft);
+    }
+
+    #[test]
+    fn engines_returning_alternatives_are_not_streamed() {
+        let streaming = MockEngine::new([]).with_streaming();
+        assert!(streams_live(&streaming));
+
+        let nbest = MockEngine::new([]).with_streaming().with_alternatives();
+        assert!(!streams_live(&nbest));
+        assert!(!streams_live(&MockEngine::new([])));
+    }
+
+    #[test]
+    fn verbose_engines_are_not_streamed() {
+        let verbose = MockEngine::new([Ok("hello world".to_string())])
+            .with_streaming()
+            .with_verbose();
+        assert!(!streams_live(&verbose));
+
+        let transcript = verbose
+            .as_verbose()
+            .unwrap()
+            .transcribe_verbose(&[], "en", &[]);
+        assert_eq!(transcript.unwrap().text, "hello world");
+    }
+
+    #[test]
+    fn multi_language_recordings_report_each_segment() {
+        let engine = crate::engines::VolcBigModelEngine::new("app", "key", 200)
+            .with_languages(vec!["zh".to_string(), "en".to_string()]);
+        assert!(!streams_live(&engine));
+
+        let utterance = |language: Option<&str>, start_ms, end_ms| crate::engines::Utterance {
+            speaker: String::new(),
+            start_ms,
+            end_ms,
+            text: String::new(),
+            language: language.map(str::to_string),
+        };
+        let verbose = VerboseTranscription {
+            utterances: vec![
+                utterance(Some("zh"), 0, 900),
+                utterance(None, 900, 1200),
+                utterance(Some("en"), 1200, 2000),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_languages(Some(&verbose), Some("zh"), 2000),
+            [("zh", 0, 900), ("en", 1200, 2000)]
+        );
+        assert_eq!(segment_languages(None, Some("en"), 2000), [("en", 0, 2000)]);
+        assert!(segment_languages(None, None, 2000).is_empty());
+    }
+
+    #[test]
+    fn only_non_modifier_hotkeys_become_global_shortcuts() {
+        for info in crate::hotkey::supported_hotkeys() {
+            let key = parse_hotkey(&info.key).unwrap();
+            assert_eq!(
+                shortcut_code(&key).is_some(),
+                info.category != "Modifier",
+                "{}",
+                info.key
+            );
+        }
+    }
+
+    #[test]
+    fn left_and_right_modifiers_match_each_other() {
+        assert!(key_matches(&Key::ControlLeft, &Key::ControlRight));
+        assert!(key_matches(&Key::ControlRight, &Key::ControlLeft));
+        assert!(key_matches(&Key::AltGr, &Key::Alt));
+        assert!(!key_matches(&Key::ControlLeft, &Key::ShiftLeft));
+        assert!(!key_matches(&Key::F1, &Key::F2));
+    }
+
+    #[test]
+    fn recognitions_are_rate_limited() {
+        let interval = Duration::from_millis(500);
+        assert_eq!(rate_limit_wait(None, interval), None);
+
+        let wait = rate_limit_wait(Some(Instant::now()), interval).unwrap();
+        assert!(wait > Duration::from_millis(400) && wait <= interval);
+        let long_ago = Instant::now() - Duration::from_secs(1);
+        assert_eq!(rate_limit_wait(Some(long_ago), interval), None);
+        assert_eq!(rate_limit_wait(Some(Instant::now()), Duration::ZERO), None);
+    }
+}
@@ -1,3 +1,7 @@
+mod keys;
 mod listener;
 
-pub use listener::{register_hotkeys, HotkeyManager};
+pub use keys::{is_valid_hotkey, supported_hotkeys, HotkeyInfo};
+pub use listener::{
+    register_hotkeys, reload_hotkeys, start_remote_recording, stop_remote_recording, HotkeyManager,
+};
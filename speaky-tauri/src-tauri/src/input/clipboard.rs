@@ -1,41 +1,436 @@
-use log::info;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use std::time::Duration;
 use tauri::AppHandle;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
-/// Write text to clipboard and simulate paste
+use crate::config::{OutputMode, PasteMode, PasteStrategy};
+use crate::APP_STATE;
+
+/// Deliver text to the focused application using the strategy configured for it
 pub fn paste_text(app: &AppHandle, text: &str) -> Result<(), String> {
     info!("Pasting text: {}...", &text.chars().take(30).collect::<String>());
 
-    // Write to clipboard using Tauri plugin
+    let output_mode = APP_STATE.config.read().core.asr.output_mode;
+    match output_mode {
+        OutputMode::PasteImmediately => {}
+        OutputMode::CopyOnly => {
+            info!("Copy-only output, skipping the paste");
+            return write_clipboard(app, text);
+        }
+        OutputMode::PasteAfterDelay { delay_ms } => {
+            info!("Waiting {} ms before pasting", delay_ms);
+            std::thread::sleep(Duration::from_millis(delay_ms));
+        }
+    }
+
+    let (strategy, delay_ms, terminal) = strategy_for_focused_window();
+    let (mode, force_clipboard) = {
+        let asr = &APP_STATE.config.read().core.asr;
+        (asr.paste_mode, asr.force_clipboard_for_unicode)
+    };
+
+    let strategy = if cfg!(target_os = "linux") && force_clipboard {
+        unicode_paste_strategy(strategy, text, terminal)
+    } else {
+        strategy
+    };
+    info!(
+        "Paste strategy: {:?}, mode {:?}, delay {} ms",
+        strategy, mode, delay_ms
+    );
+
+    // Typing inserts at the cursor, but would still overwrite a selection
+    if mode == PasteMode::InsertAtCursor {
+        if let Err(e) = press_end() {
+            warn!("Failed to clear the selection: {}", e);
+        }
+    }
+
+    let typed = match strategy {
+        PasteStrategy::ClipboardCtrlV => {
+            return paste_via_clipboard(app, text, false, delay_ms, mode)
+        }
+        PasteStrategy::ClipboardCmdV => {
+            return paste_via_clipboard(app, text, true, delay_ms, mode)
+        }
+        PasteStrategy::XdotoolType => xdotool_type(text),
+        PasteStrategy::DirectInput => type_text(text),
+    };
+
+    typed.or_else(|e| {
+        warn!("{:?} failed, falling back to clipboard: {}", strategy, e);
+        let command = PasteStrategy::default() == PasteStrategy::ClipboardCmdV;
+        paste_via_clipboard(app, text, command, delay_ms, mode)
+    })
+}
+
+/// Terminal emulators that ignore Ctrl+V and get typed input by default
+const TERMINAL_WM_CLASSES: &[&str] = &["alacritty", "kitty", "gnome-terminal", "xterm", "konsole"];
+
+/// Pick the override matching the focused window, typed input for terminals,
+/// or the platform default; also returns the delay and whether the window is
+/// a terminal
+fn strategy_for_focused_window() -> (PasteStrategy, u64, bool) {
+    let info = crate::window_info::get_focused_window_info();
+    let config = APP_STATE.config.read();
+    let delay_ms = config.core.asr.paste_delay_ms;
+    let Some(info) = info else {
+        return (PasteStrategy::default(), delay_ms, false);
+    };
+
+    let terminal = is_terminal(&info.wm_class) || is_terminal(&info.wm_instance);
+    if let Some(entry) = config.paste_override_for(&info.wm_class, &info.wm_instance) {
+        (entry.strategy, entry.delay_ms, terminal)
+    } else if terminal {
+        (PasteStrategy::DirectInput, delay_ms, terminal)
+    } else {
+        (PasteStrategy::default(), delay_ms, terminal)
+    }
+}
+
+/// Switch a typed strategy to the clipboard for text outside ASCII.
+///
+/// xdotool types each character through a temporary keysym remapping, which
+/// many input methods and some apps drop or garble for characters outside
+/// ASCII, CJK in particular; pasting carries them intact. Terminals keep
+/// their strategy since they ignore the Ctrl+V the clipboard path sends.
+fn unicode_paste_strategy(strategy: PasteStrategy, text: &str, terminal: bool) -> PasteStrategy {
+    let typed = matches!(
+        strategy,
+        PasteStrategy::XdotoolType | PasteStrategy::DirectInput
+    );
+    if typed && !terminal && !text.is_ascii() {
+        info!("Text is not ASCII, pasting through the clipboard");
+        PasteStrategy::default()
+    } else {
+        strategy
+    }
+}
+
+/// Check a WM_CLASS against the known terminal emulators
+fn is_terminal(wm_class: &str) -> bool {
+    TERMINAL_WM_CLASSES
+        .iter()
+        .any(|terminal| terminal.eq_ignore_ascii_case(wm_class))
+}
+
+/// Write text to clipboard and simulate Ctrl+V (or Cmd+V when `command` is set).
+/// In `InsertAtCursor` mode the previous clipboard text is put back afterwards.
+fn paste_via_clipboard(
+    app: &AppHandle,
+    text: &str,
+    command: bool,
+    delay_ms: u64,
+    mode: PasteMode,
+) -> Result<(), String> {
+    let previous = match mode {
+        PasteMode::InsertAtCursor => read_clipboard(app),
+        PasteMode::Replace => None,
+    };
+
+    write_clipboard(app, text)?;
+
+    // Let the clipboard settle before pasting
+    std::thread::sleep(Duration::from_millis(delay_ms));
+
+    let result = simulate_paste(command);
+
+    if let Some(previous) = previous {
+        // The target app reads the clipboard asynchronously after the key press
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        if let Err(e) = write_clipboard(app, &previous) {
+            warn!("Failed to restore the clipboard: {}", e);
+        }
+    }
+    result
+}
+
+/// Whether wl-clipboard should be used instead of the clipboard plugin, which
+/// can fail silently on Wayland when no X11 clipboard is available
+fn use_wl_clipboard() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some() && *WL_CLIPBOARD_INSTALLED
+}
+
+#[cfg(target_os = "linux")]
+static WL_CLIPBOARD_INSTALLED: Lazy<bool> = Lazy::new(|| {
+    let installed = std::process::Command::new("wl-copy")
+        .arg("--version")
+        .output()
+        .is_ok();
+    if !installed {
+        info!("wl-copy not found, using the clipboard plugin");
+    }
+    installed
+});
+
+#[cfg(not(target_os = "linux"))]
+static WL_CLIPBOARD_INSTALLED: Lazy<bool> = Lazy::new(|| false);
+
+/// Put text on the clipboard, through wl-copy on Wayland when it is installed
+pub fn write_clipboard(app: &AppHandle, text: &str) -> Result<(), String> {
+    if use_wl_clipboard() {
+        return write_text_wayland(text);
+    }
     app.clipboard()
         .write_text(text)
-        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))
+}
+
+/// Current clipboard text, through wl-paste on Wayland when it is installed
+pub fn read_clipboard(app: &AppHandle) -> Option<String> {
+    if use_wl_clipboard() {
+        return match read_text_wayland() {
+            Ok(text) => Some(text),
+            Err(e) => {
+                warn!("{}", e);
+                None
+            }
+        };
+    }
+    app.clipboard().read_text().ok()
+}
+
+/// Write text to the Wayland clipboard with `wl-copy`
+#[cfg(target_os = "linux")]
+pub fn write_text_wayland(text: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // wl-copy forks a process that keeps serving the selection and inherits
+    // the output pipes, so reading them would block until the selection
+    // changes; only the parent's exit status is waited on
+    let mut child = Command::new("wl-copy")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run wl-copy: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write to wl-copy: {}", e))?;
+    }
 
-    // Small delay before paste
-    std::thread::sleep(std::time::Duration::from_millis(50));
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to run wl-copy: {}", e))?;
+    if !status.success() {
+        return Err(format!("wl-copy exited with {}", status));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn write_text_wayland(_text: &str) -> Result<(), String> {
+    Err("wl-copy is only available on Linux".to_string())
+}
+
+/// Read the Wayland clipboard with `wl-paste`
+#[cfg(target_os = "linux")]
+fn read_text_wayland() -> Result<String, String> {
+    use std::process::Command;
+
+    let output = Command::new("wl-paste")
+        .args(["--no-newline", "--type", "text"])
+        .output()
+        .map_err(|e| format!("Failed to run wl-paste: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("wl-paste failed: {}", stderr));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_text_wayland() -> Result<String, String> {
+    Err("wl-paste is only available on Linux".to_string())
+}
+
+/// Read audio from the clipboard, taking the first `audio/*` type offered,
+/// through wl-paste on Wayland and xclip on X11
+#[cfg(target_os = "linux")]
+pub fn read_clipboard_audio() -> Result<Vec<u8>, String> {
+    use std::process::Command;
+
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let run = |args: &[&str]| -> Result<Vec<u8>, String> {
+        let (program, base): (&str, &[&str]) = if wayland {
+            ("wl-paste", &[])
+        } else {
+            ("xclip", &["-selection", "clipboard", "-o"])
+        };
+        let output = Command::new(program)
+            .args(base)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("{} failed: {}", program, stderr.trim()));
+        }
+        Ok(output.stdout)
+    };
+
+    let types = if wayland {
+        run(&["--list-types"])?
+    } else {
+        run(&["-t", "TARGETS"])?
+    };
+    let types = String::from_utf8_lossy(&types);
+    let audio_type = types
+        .lines()
+        .map(str::trim)
+        .find(|t| t.starts_with("audio/"))
+        .ok_or("The clipboard holds no audio")?;
+    info!("Reading clipboard audio as {}", audio_type);
+
+    if wayland {
+        run(&["--type", audio_type])
+    } else {
+        run(&["-t", audio_type])
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_clipboard_audio() -> Result<Vec<u8>, String> {
+    Err("Reading audio from the clipboard is only supported on Linux".to_string())
+}
+
+/// Type text with `xdotool type`
+#[cfg(target_os = "linux")]
+fn xdotool_type(text: &str) -> Result<(), String> {
+    use std::process::Command;
 
-    // Simulate Ctrl+V / Cmd+V based on platform
-    simulate_paste()?;
+    let output = Command::new("xdotool")
+        .args(["type", "--clearmodifiers", "--", text])
+        .output()
+        .map_err(|e| format!("Failed to run xdotool: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("xdotool failed: {}", stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn xdotool_type(_text: &str) -> Result<(), String> {
+    Err("xdotool is only available on Linux".to_string())
+}
+
+/// Type text as synthetic key events with xdotool, falling back to ydotool on Wayland
+#[cfg(target_os = "linux")]
+fn type_text(text: &str) -> Result<(), String> {
+    use std::process::Command;
+
+    // The text goes to xdotool as a single argv entry rather than through a shell,
+    // so quotes, `$` and backticks reach the terminal verbatim without escaping;
+    // `--` keeps a leading `-` from being parsed as an option
+    let result = Command::new("xdotool")
+        .args(["type", "--clearmodifiers", "--delay", "5", "--", text])
+        .output();
+    match result {
+        Ok(output) if output.status.success() => return Ok(()),
+        Ok(output) => warn!(
+            "xdotool type failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        Err(e) => warn!("Failed to run xdotool: {}", e),
+    }
+
+    let output = Command::new("ydotool")
+        .args(["type", "--", text])
+        .output()
+        .map_err(|e| format!("Failed to run ydotool: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ydotool failed: {}", stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn type_text(text: &str) -> Result<(), String> {
+    use std::process::Command;
+
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(format!(
+            "tell application \"System Events\" to keystroke \"{}\"",
+            escaped
+        ))
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("osascript failed: {}", stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn type_text(text: &str) -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+        VIRTUAL_KEY,
+    };
+
+    // One key down/up pair per UTF-16 code unit
+    let inputs: Vec<INPUT> = text
+        .encode_utf16()
+        .flat_map(|unit| {
+            [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP].map(|flags| INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: VIRTUAL_KEY(0),
+                        wScan: unit,
+                        dwFlags: flags,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    },
+                },
+            })
+        })
+        .collect();
+
+    let result = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if result != inputs.len() as u32 {
+        return Err("Failed to send input".to_string());
+    }
 
     Ok(())
 }
 
 #[cfg(target_os = "windows")]
-fn simulate_paste() -> Result<(), String> {
+fn simulate_paste(command: bool) -> Result<(), String> {
     use windows::Win32::UI::Input::KeyboardAndMouse::{
         SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
-        VIRTUAL_KEY, VK_CONTROL, VK_V,
+        VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_V,
     };
 
+    let modifier = if command { VK_LWIN } else { VK_CONTROL };
+
     unsafe {
         let mut inputs: Vec<INPUT> = Vec::with_capacity(4);
 
-        // Press Ctrl
+        // Press modifier
         inputs.push(INPUT {
             r#type: INPUT_KEYBOARD,
             Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
                 ki: KEYBDINPUT {
-                    wVk: VK_CONTROL,
+                    wVk: modifier,
                     wScan: 0,
                     dwFlags: KEYBD_EVENT_FLAGS(0),
                     time: 0,
@@ -72,12 +467,12 @@ fn simulate_paste() -> Result<(), String> {
             },
         });
 
-        // Release Ctrl
+        // Release modifier
         inputs.push(INPUT {
             r#type: INPUT_KEYBOARD,
             Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
                 ki: KEYBDINPUT {
-                    wVk: VK_CONTROL,
+                    wVk: modifier,
                     wScan: 0,
                     dwFlags: KEYEVENTF_KEYUP,
                     time: 0,
@@ -95,14 +490,76 @@ fn simulate_paste() -> Result<(), String> {
     Ok(())
 }
 
+/// Post Cmd+V / Ctrl+V as CGEvents, falling back to AppleScript.
+///
+/// Note: posting the key events in-process avoids spawning `osascript` for
+/// every paste, which adds around 200 ms of latency. Both ways need the
+/// Accessibility permission.
+#[cfg(target_os = "macos")]
+fn simulate_paste(command: bool) -> Result<(), String> {
+    post_paste_events(command).or_else(|e| {
+        warn!("CGEvent paste failed, falling back to osascript: {}", e);
+        osascript_paste(command)
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn post_paste_events(command: bool) -> Result<(), String> {
+    use core_graphics::event::{CGEventFlags, CGKeyCode};
+
+    /// kVK_ANSI_V
+    const KEY_V: CGKeyCode = 0x09;
+
+    let flags = if command {
+        CGEventFlags::CGEventFlagCommand
+    } else {
+        CGEventFlags::CGEventFlagControl
+    };
+    post_key_events(KEY_V, flags)
+}
+
+/// Post a key down/up pair with `flags` held
+#[cfg(target_os = "macos")]
+fn post_key_events(
+    key: core_graphics::event::CGKeyCode,
+    flags: core_graphics::event::CGEventFlags,
+) -> Result<(), String> {
+    use core_graphics::event::{CGEvent, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "Failed to create CGEvent source".to_string())?;
+
+    for key_down in [true, false] {
+        let event = CGEvent::new_keyboard_event(source.clone(), key, key_down)
+            .map_err(|_| "Failed to create keyboard CGEvent".to_string())?;
+        event.set_flags(flags);
+        event.post(CGEventTapLocation::HID);
+    }
+
+    Ok(())
+}
+
+/// Press End to move the cursor past a selection
+#[cfg(target_os = "macos")]
+fn press_end() -> Result<(), String> {
+    use core_graphics::event::{CGEventFlags, KeyCode};
+
+    post_key_events(KeyCode::END, CGEventFlags::CGEventFlagNull)
+}
+
 #[cfg(target_os = "macos")]
-fn simulate_paste() -> Result<(), String> {
+fn osascript_paste(command: bool) -> Result<(), String> {
     use std::process::Command;
 
-    // Use AppleScript to simulate Cmd+V
+    // Use AppleScript to simulate Cmd+V / Ctrl+V
+    let modifier = if command { "command" } else { "control" };
     let output = Command::new("osascript")
         .arg("-e")
-        .arg("tell application \"System Events\" to keystroke \"v\" using command down")
+        .arg(format!(
+            "tell application \"System Events\" to keystroke \"v\" using {} down",
+            modifier
+        ))
         .output()
         .map_err(|e| format!("Failed to run osascript: {}", e))?;
 
@@ -115,31 +572,204 @@ fn simulate_paste() -> Result<(), String> {
 }
 
 #[cfg(target_os = "linux")]
-fn simulate_paste() -> Result<(), String> {
+fn simulate_paste(command: bool) -> Result<(), String> {
     use std::process::Command;
 
+    let (combo, modifier_down, modifier_up) = if command {
+        ("super+v", "125:1", "125:0")
+    } else {
+        ("ctrl+v", "29:1", "29:0")
+    };
+
     // Try xdotool first, then xclip
-    let result = Command::new("xdotool")
-        .arg("key")
-        .arg("ctrl+v")
-        .output();
+    let result = Command::new("xdotool").arg("key").arg(combo).output();
 
     match result {
         Ok(output) if output.status.success() => Ok(()),
         _ => {
-            // Fallback: try using ydotool for Wayland
+            // Wayland: write the keys to a virtual keyboard, else try ydotool
+            let uinput_error = match uinput_paste(command) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    info!("uinput paste unavailable: {}", e);
+                    e
+                }
+            };
             let result = Command::new("ydotool")
                 .arg("key")
-                .arg("29:1")  // Ctrl down
+                .arg(modifier_down)  // Modifier down
                 .arg("47:1")  // V down
                 .arg("47:0")  // V up
-                .arg("29:0")  // Ctrl up
+                .arg(modifier_up)  // Modifier up
                 .output();
 
             match result {
                 Ok(output) if output.status.success() => Ok(()),
-                _ => Err("Failed to simulate paste: xdotool and ydotool not available".to_string()),
+                _ => Err(format!(
+                    "Failed to simulate paste: xdotool and ydotool not available, uinput: {}",
+                    uinput_error
+                )),
             }
         }
     }
 }
+
+/// Delay after creating the virtual keyboard, so the compositor picks it up
+/// before the first key arrives
+#[cfg(target_os = "linux")]
+const UINPUT_SETTLE: Duration = Duration::from_millis(200);
+
+/// Virtual keyboard used by `uinput_paste`, created on first use
+#[cfg(target_os = "linux")]
+static UINPUT_KEYBOARD: Lazy<parking_lot::Mutex<Option<uinput::Device>>> =
+    Lazy::new(|| parking_lot::Mutex::new(None));
+
+/// Press the paste combination on a virtual keyboard created through `/dev/uinput`.
+///
+/// The events enter below the display server, so this works on any Wayland
+/// compositor without ydotoold. Opening `/dev/uinput` needs write access,
+/// which normal users lack by default: add a udev rule such as
+/// `KERNEL=="uinput", GROUP="input", MODE="0660"` and put the user in the
+/// `input` group, then log in again.
+#[cfg(target_os = "linux")]
+fn uinput_paste(command: bool) -> Result<(), String> {
+    use uinput::event::keyboard::Key;
+
+    if !std::path::Path::new("/dev/uinput").exists() {
+        return Err("/dev/uinput does not exist".to_string());
+    }
+
+    let modifier = if command {
+        Key::LeftMeta
+    } else {
+        Key::LeftControl
+    };
+
+    let mut keyboard = UINPUT_KEYBOARD.lock();
+    let device = match keyboard.take() {
+        Some(device) => device,
+        None => create_uinput_keyboard()?,
+    };
+    let device = keyboard.insert(device);
+
+    // Every change is its own frame. The modifier is released even when
+    // pressing V fails, or it would stay held for every later key press
+    let pasted = device
+        .press(&modifier)
+        .and_then(|()| device.synchronize())
+        .and_then(|()| device.press(&Key::V))
+        .and_then(|()| device.synchronize())
+        .and_then(|()| device.release(&Key::V))
+        .and_then(|()| device.synchronize());
+    let released = device
+        .release(&modifier)
+        .and_then(|()| device.synchronize());
+    pasted
+        .and(released)
+        .map_err(|e| format!("Failed to write to uinput: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn create_uinput_keyboard() -> Result<uinput::Device, String> {
+    use uinput::event::keyboard::Key;
+
+    let device = uinput::open("/dev/uinput")
+        .and_then(|builder| builder.name("Speaky virtual keyboard"))
+        .and_then(|builder| builder.event(Key::LeftControl))
+        .and_then(|builder| builder.event(Key::LeftMeta))
+        .and_then(|builder| builder.event(Key::V))
+        .and_then(|builder| builder.create())
+        .map_err(|e| format!("Failed to create a uinput keyboard: {}", e))?;
+    std::thread::sleep(UINPUT_SETTLE);
+    Ok(device)
+}
+
+/// Press End to move the cursor past a selection
+#[cfg(target_os = "linux")]
+fn press_end() -> Result<(), String> {
+    use std::process::Command;
+
+    let result = Command::new("xdotool").args(["key", "End"]).output();
+    match result {
+        Ok(output) if output.status.success() => Ok(()),
+        _ => {
+            // KEY_END for ydotool on Wayland
+            let result = Command::new("ydotool")
+                .args(["key", "107:1", "107:0"])
+                .output();
+            match result {
+                Ok(output) if output.status.success() => Ok(()),
+                _ => Err("Failed to press End: xdotool and ydotool not available".to_string()),
+            }
+        }
+    }
+}
+
+/// Press End to move the cursor past a selection
+#[cfg(target_os = "windows")]
+fn press_end() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+        VK_END,
+    };
+
+    let inputs: Vec<INPUT> = [KEYBD_EVENT_FLAGS(0), KEYEVENTF_KEYUP]
+        .into_iter()
+        .map(|flags| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VK_END,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        })
+        .collect();
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent != inputs.len() as u32 {
+        return Err("Failed to send input".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminals_are_detected_case_insensitively() {
+        assert!(is_terminal("Alacritty"));
+        assert!(is_terminal("Gnome-terminal"));
+        assert!(is_terminal("konsole"));
+        assert!(!is_terminal("firefox"));
+    }
+
+    #[test]
+    fn non_ascii_text_is_pasted_except_in_terminals() {
+        let pasted = PasteStrategy::default();
+        assert_eq!(
+            unicode_paste_strategy(PasteStrategy::XdotoolType, "你好", false),
+            pasted
+        );
+        assert_eq!(
+            unicode_paste_strategy(PasteStrategy::DirectInput, "café", false),
+            pasted
+        );
+        assert_eq!(
+            unicode_paste_strategy(PasteStrategy::DirectInput, "你好", true),
+            PasteStrategy::DirectInput
+        );
+        assert_eq!(
+            unicode_paste_strategy(PasteStrategy::XdotoolType, "hello", false),
+            PasteStrategy::XdotoolType
+        );
+        assert_eq!(
+            unicode_paste_strategy(PasteStrategy::ClipboardCmdV, "你好", false),
+            PasteStrategy::ClipboardCmdV
+        );
+    }
+}
@@ -1,3 +1,3 @@
 mod clipboard;
 
-pub use clipboard::paste_text;
+pub use clipboard::{paste_text, read_clipboard, read_clipboard_audio, write_clipboard};
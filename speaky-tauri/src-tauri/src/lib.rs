@@ -2,31 +2,82 @@ pub mod audio;
 pub mod commands;
 pub mod config;
 pub mod engines;
+pub mod error;
+pub mod history;
 pub mod hotkey;
 pub mod input;
+pub mod logging;
+pub mod output;
+pub mod permissions;
+pub mod server;
+pub mod startup;
+pub mod system;
+pub mod text;
+pub mod watch;
 pub mod window_info;
 
-use log::info;
+use log::{error, info, warn};
 use once_cell::sync::Lazy;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tauri::{
+    image::Image,
     menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager, RunEvent,
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager, RunEvent,
 };
 
-use audio::AudioRecorder;
+use audio::{AudioRecorder, RecorderStats};
 use config::Config;
 use engines::Engine;
+use history::recent_apps::AppUsageRecord;
+use history::TranscriptionRecord;
 use hotkey::HotkeyManager;
+use permissions::RequiredPermission;
+use startup::StartupIssue;
+use window_info::CachedIcon;
+
+/// Number of audio levels kept in `AppState::level_history`
+pub const LEVEL_HISTORY_CAPACITY: usize = 256;
 
 /// Global application state
 pub struct AppState {
     pub config: RwLock<Config>,
     pub recorder: RwLock<Option<AudioRecorder>>,
+    /// Recorders for `extra_audio_devices`, mixed into the main recording
+    pub extra_recorders: RwLock<Vec<AudioRecorder>>,
     pub engine: RwLock<Option<Box<dyn Engine + Send + Sync>>>,
-    pub hotkey_manager: RwLock<Option<HotkeyManager>>,
+    pub hotkey_manager: RwLock<Vec<HotkeyManager>>,
+    pub history: Arc<Mutex<VecDeque<TranscriptionRecord>>>,
+    /// Applications text was pasted into, persisted on exit
+    pub recent_apps: Arc<Mutex<VecDeque<AppUsageRecord>>>,
+    pub app_handle: RwLock<Option<AppHandle>>,
+    pub tray: Arc<Mutex<Option<TrayIcon>>>,
+    /// Input levels of the current recording, as emitted in `audio-level` events
+    pub level_history: Arc<Mutex<VecDeque<f32>>>,
+    /// When the current recording started, while one is in progress
+    pub recording_start_time: Arc<Mutex<Option<Instant>>>,
+    /// When the last recognition job started, for `min_interval_between_calls_ms`
+    pub last_transcription_started: Arc<Mutex<Option<Instant>>>,
+    /// Clipping totals reported by the recorder
+    pub recorder_stats: Arc<Mutex<RecorderStats>>,
+    /// Encoded app icons by file path
+    pub icon_cache: RwLock<HashMap<String, CachedIcon>>,
+    /// Recognized text waiting for `confirm_paste`, by preview id
+    pub pending_previews: Arc<Mutex<HashMap<String, String>>>,
+    /// Problems found by the checks run at the end of setup
+    pub startup_issues: RwLock<Vec<StartupIssue>>,
+    /// Permissions reported through `permission-required`
+    pub required_permissions: RwLock<Vec<RequiredPermission>>,
+    /// HTTP server for dictation from other devices, while running
+    pub lan_server: Mutex<Option<server::LanServer>>,
+    /// WebSocket server for programmatic clients, while running
+    pub ws_server: Mutex<Option<server::WsServer>>,
+    /// Transcribes new files in `watch_directory`, while running
+    pub directory_watcher: Mutex<Option<watch::DirectoryWatcher>>,
+    _config_watcher: Option<notify::RecommendedWatcher>,
 }
 
 impl AppState {
@@ -35,103 +86,436 @@ impl AppState {
         Self {
             config: RwLock::new(config),
             recorder: RwLock::new(None),
+            extra_recorders: RwLock::new(Vec::new()),
             engine: RwLock::new(None),
-            hotkey_manager: RwLock::new(None),
+            hotkey_manager: RwLock::new(Vec::new()),
+            history: Arc::new(Mutex::new(history::load())),
+            recent_apps: Arc::new(Mutex::new(history::recent_apps::load())),
+            app_handle: RwLock::new(None),
+            tray: Arc::new(Mutex::new(None)),
+            level_history: Arc::new(Mutex::new(VecDeque::with_capacity(LEVEL_HISTORY_CAPACITY))),
+            recording_start_time: Arc::new(Mutex::new(None)),
+            last_transcription_started: Arc::new(Mutex::new(None)),
+            recorder_stats: Arc::new(Mutex::new(RecorderStats::default())),
+            icon_cache: RwLock::new(HashMap::new()),
+            pending_previews: Arc::new(Mutex::new(HashMap::new())),
+            startup_issues: RwLock::new(Vec::new()),
+            required_permissions: RwLock::new(Vec::new()),
+            lan_server: Mutex::new(None),
+            ws_server: Mutex::new(None),
+            directory_watcher: Mutex::new(None),
+            _config_watcher: config::spawn_config_watcher(),
+        }
+    }
+
+    /// Make `config` the active configuration, rebuilding the engine, recorder and hotkeys
+    pub fn apply_config(&self, config: Config) {
+        *self.config.write() = config.clone();
+
+        // Recreate engine with new config
+        let context = engines::EngineContext {
+            app_handle: self.app_handle.read().clone(),
+        };
+        *self.engine.write() = engines::create_engine(&config, &context);
+
+        // Recreate recorder with new settings
+        if let Some(ref mut recorder) = *self.recorder.write() {
+            *recorder = AudioRecorder::from_config(&config.core.asr);
+        }
+        *self.extra_recorders.write() = AudioRecorder::extras_from_config(&config.core.asr);
+
+        if let Some(app) = self.app_handle.read().clone() {
+            hotkey::reload_hotkeys(app);
+        }
+    }
+
+    /// Start the extra recorders; one that fails is left out of the mix
+    pub fn start_extra_recorders(&self) {
+        for recorder in self.extra_recorders.write().iter_mut() {
+            if let Err(e) = recorder.start() {
+                warn!("Failed to start extra recorder: {}", e);
+            }
+        }
+    }
+
+    /// Stop the extra recorders and mix their audio with the main recording's WAV
+    pub fn stop_extra_recorders(&self, primary: Vec<u8>) -> Vec<u8> {
+        let mut extras = self.extra_recorders.write();
+        if extras.is_empty() {
+            return primary;
+        }
+
+        let recordings: Vec<Vec<u8>> = std::iter::once(primary)
+            .chain(extras.iter_mut().map(|recorder| recorder.stop()))
+            .filter(|wav| !wav.is_empty())
+            .collect();
+        let Some(sample_rate) = recordings
+            .iter()
+            .find_map(|wav| audio::processing::parse_wav_sample_rate(wav))
+        else {
+            return Vec::new();
+        };
+
+        let streams: Vec<Vec<i16>> = recordings
+            .iter()
+            .filter_map(|wav| audio::processing::wav_samples(wav))
+            .collect();
+        info!("Mixing {} audio streams", streams.len());
+        let mixed = audio::processing::AudioMixer::mix(&streams);
+        audio::processing::encode_wav(&mixed, sample_rate)
+    }
+
+    /// Emit an event to the frontend once the app is running
+    pub fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(ref app) = *self.app_handle.read() {
+            let _ = app.emit(event, payload);
         }
     }
 }
 
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub static APP_STATE: Lazy<Arc<AppState>> = Lazy::new(|| Arc::new(AppState::new()));
 
+/// Current Unix time in milliseconds, as sent in event timestamps
+pub fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Payload of the `final-result` event. The recording start and duration are
+/// left out when the start is unknown
+pub fn final_result_payload(text: &str, started_at_ms: Option<u64>) -> serde_json::Value {
+    let emitted_at_ms = unix_time_ms();
+    let mut payload = serde_json::json!({
+        "text": text,
+        "emitted_at_ms": emitted_at_ms,
+        "word_count": text.split_whitespace().count() as u32,
+        "char_count": text.chars().count() as u32,
+    });
+    if let Some(started_at_ms) = started_at_ms {
+        payload["recording_started_at_ms"] = started_at_ms.into();
+        payload["duration_secs"] =
+            (emitted_at_ms.saturating_sub(started_at_ms) as f64 / 1000.0).into();
+    }
+    payload
+}
+
+/// Tray tooltips for each recognition state
+pub const TRAY_TOOLTIP_IDLE: &str = "Speaky – Idle";
+pub const TRAY_TOOLTIP_RECORDING: &str = "Speaky – Recording…";
+pub const TRAY_TOOLTIP_RECOGNIZING: &str = "Speaky – Recognizing…";
+
+/// Built-in tray icons, used when `tray_icon_set` leaves a state empty
+const TRAY_ICON_IDLE: &[u8] = include_bytes!("../icons/32x32.png");
+const TRAY_ICON_RECORDING: &[u8] = include_bytes!("../icons/tray-recording.png");
+const TRAY_ICON_PROCESSING: &[u8] = include_bytes!("../icons/tray-processing.png");
+
+/// Recognition state shown by the tray icon and tooltip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayState {
+    Idle,
+    Recording,
+    Processing,
+}
+
+impl TrayState {
+    fn tooltip(self) -> &'static str {
+        match self {
+            TrayState::Idle => TRAY_TOOLTIP_IDLE,
+            TrayState::Recording => TRAY_TOOLTIP_RECORDING,
+            TrayState::Processing => TRAY_TOOLTIP_RECOGNIZING,
+        }
+    }
+
+    /// The configured icon for this state, or the built-in one if unset or unreadable
+    fn icon(self) -> Image<'static> {
+        let icons = APP_STATE.config.read().appearance.tray_icon_set.clone();
+        let (path, builtin) = match self {
+            TrayState::Idle => (icons.idle, TRAY_ICON_IDLE),
+            TrayState::Recording => (icons.recording, TRAY_ICON_RECORDING),
+            TrayState::Processing => (icons.processing, TRAY_ICON_PROCESSING),
+        };
+        if !path.as_os_str().is_empty() {
+            match Image::from_path(&path) {
+                Ok(icon) => return icon,
+                Err(e) => warn!("Failed to load tray icon {:?}: {}", path, e),
+            }
+        }
+        Image::from_bytes(builtin).expect("built-in tray icons are valid PNGs")
+    }
+}
+
+/// Update the tray icon and tooltip; safe to call from any thread
+pub fn set_tray_state(app: &AppHandle, state: TrayState) {
+    let Some(tray) = APP_STATE.tray.lock().clone() else {
+        return;
+    };
+
+    // Tray updates must happen on the main thread on macOS
+    let icon = state.icon();
+    let result = app.run_on_main_thread(move || {
+        if let Err(e) = tray.set_icon(Some(icon)) {
+            error!("Failed to set tray icon: {}", e);
+        }
+        if let Err(e) = tray.set_tooltip(Some(state.tooltip())) {
+            error!("Failed to set tray tooltip: {}", e);
+        }
+    });
+    if let Err(e) = result {
+        error!("Failed to schedule tray update: {}", e);
+    }
+}
+
+/// Build the tray icon and menu; does nothing if the icon already exists
+pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
+    if APP_STATE.tray.lock().is_some() {
+        return Ok(());
+    }
+
+    // Create tray menu
+    let settings_item = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&settings_item, &quit_item])?;
+
+    // Create tray icon
+    let tray = TrayIconBuilder::new()
+        .menu(&menu)
+        .icon(TrayState::Idle.icon())
+        .tooltip(TRAY_TOOLTIP_IDLE)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "settings" => {
+                if let Some(window) = app.get_webview_window("settings") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            "quit" => {
+                app.exit(0);
+            }
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let app = tray.app_handle();
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        })
+        .build(app)?;
+    *APP_STATE.tray.lock() = Some(tray);
+    Ok(())
+}
+
+/// Remove the tray icon, leaving the app controllable through its commands
+pub fn remove_tray(app: &AppHandle) {
+    if let Some(tray) = APP_STATE.tray.lock().take() {
+        app.remove_tray_by_id(tray.id());
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    logging::init();
+
+    if std::env::args().any(|arg| arg == "--reset-config-cache") {
+        match Config::reset_binary_cache() {
+            Ok(()) => info!("Config cache reset"),
+            Err(e) => warn!("Failed to reset the config cache: {}", e),
+        }
+    }
 
     info!("Starting Speaky...");
 
     tauri::Builder::default()
         // Note: Using rdev for keyboard listening instead of global-shortcut plugin
-        // to support modifier keys (ctrl, alt, shift) as hotkeys
+        // to support modifier keys (ctrl, alt, shift) as hotkeys; the plugin is
+        // only used when rdev gets no events
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             info!("Setting up application...");
+            *APP_STATE.app_handle.write() = Some(app.handle().clone());
 
             // Initialize audio recorder
             {
                 let config = APP_STATE.config.read();
-                let device_index = config.core.asr.audio_device;
-                let gain = config.core.asr.audio_gain;
-                let recorder = AudioRecorder::new(device_index, gain);
+                let recorder = AudioRecorder::from_config(&config.core.asr);
                 *APP_STATE.recorder.write() = Some(recorder);
+                *APP_STATE.extra_recorders.write() =
+                    AudioRecorder::extras_from_config(&config.core.asr);
             }
 
             // Initialize engine based on config
             {
                 let config = APP_STATE.config.read();
-                let engine = engines::create_engine(&config);
+                let context = engines::EngineContext {
+                    app_handle: Some(app.handle().clone()),
+                };
+                let engine = engines::create_engine(&config, &context);
                 *APP_STATE.engine.write() = engine;
             }
 
-            // Create tray menu
-            let settings_item = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&settings_item, &quit_item])?;
-
-            // Create tray icon
-            let _tray = TrayIconBuilder::new()
-                .menu(&menu)
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "settings" => {
-                        if let Some(window) = app.get_webview_window("settings") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                })
-                .build(app)?;
+            // Kiosk setups run without a tray icon and are driven through the
+            // commands or the WebSocket server
+            if APP_STATE.config.read().appearance.run_in_background {
+                info!("Running in background, skipping the tray icon");
+            } else {
+                create_tray(app.handle())?;
+            }
 
             // Register hotkeys
             let app_handle = app.handle().clone();
             hotkey::register_hotkeys(app_handle)?;
 
+            // Start the LAN server if configured; failing to bind is not fatal
+            let lan_config = APP_STATE.config.read().lan_server.clone();
+            if let Some(lan_config) = lan_config {
+                match server::LanServer::start(&lan_config) {
+                    Ok(lan_server) => *APP_STATE.lan_server.lock() = Some(lan_server),
+                    Err(e) => error!("{}", e),
+                }
+            }
+            if APP_STATE.config.read().ws_server.is_some() {
+                if let Err(e) = server::restart_ws_server(app.handle()) {
+                    error!("{}", e);
+                }
+            }
+
+            let watch_config = APP_STATE.config.read().watch_directory.clone();
+            if let Some(watch_config) = watch_config {
+                match watch::DirectoryWatcher::start(&watch_config) {
+                    Ok(watcher) => *APP_STATE.directory_watcher.lock() = Some(watcher),
+                    Err(e) => error!("{}", e),
+                }
+            }
+
+            startup::run_startup_checks(app.handle());
+
             info!("Application setup complete");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_config,
             commands::save_config,
+            commands::reset_config,
+            commands::export_config,
+            commands::import_config,
+            commands::save_profile,
+            commands::load_profile,
+            commands::list_profiles,
+            commands::delete_profile,
+            commands::get_vocab_hints,
+            commands::save_vocab_hints,
+            commands::get_paste_overrides,
+            commands::save_paste_overrides,
+            commands::get_transforms,
+            commands::save_transforms,
+            commands::test_post_process_command,
+            commands::get_profanity_list,
+            commands::set_profanity_list,
             commands::start_recording,
             commands::stop_recording,
+            commands::flush_audio_buffer,
+            commands::pause_recording,
+            commands::resume_recording,
+            commands::transcribe_file,
+            commands::transcribe_clipboard_audio,
+            commands::test_engine,
+            commands::check_engine_connectivity,
+            commands::get_engine_capabilities,
             commands::get_audio_devices,
+            commands::get_audio_device_info,
+            commands::benchmark_devices,
+            commands::calibrate_gain,
+            commands::get_noise_level,
+            commands::get_levels,
+            commands::preview_tts,
+            commands::stop_tts,
+            commands::get_waveform_snapshot,
+            commands::get_level_history,
+            commands::get_recording_duration,
+            commands::get_recorder_stats,
+            commands::get_supported_hotkeys,
+            commands::check_accessibility_permission,
+            commands::request_accessibility_permission,
+            commands::get_required_permissions,
             commands::set_hotkey,
+            commands::add_hotkey_profile,
+            commands::remove_hotkey_profile,
+            commands::get_focused_window,
+            commands::clear_icon_cache,
+            commands::show_tray_icon,
+            commands::hide_tray_icon,
+            commands::reset_tray_icons,
             commands::show_window,
             commands::hide_window,
             commands::paste_text,
+            commands::confirm_paste,
+            commands::get_history,
+            commands::clear_history,
+            commands::get_recent_apps,
+            commands::clear_recent_apps,
+            commands::get_startup_issues,
+            commands::get_system_info,
+            commands::get_log_path,
+            commands::get_recent_logs,
+            commands::start_lan_server,
+            commands::stop_lan_server,
+            commands::start_ws_server,
+            commands::stop_ws_server,
+            commands::start_watch,
+            commands::stop_watch,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|app_handle, event| {
-            if let RunEvent::ExitRequested { api, .. } = event {
+        .run(|_app_handle, event| {
+            if let RunEvent::ExitRequested { .. } = event {
                 // Clean up resources before exit
                 info!("Application exiting...");
+
+                if let Some(ref mut recorder) = *APP_STATE.recorder.write() {
+                    recorder.unload_echo_cancel();
+                }
+
+                if let Err(e) = history::save(&APP_STATE.history.lock()) {
+                    error!("Failed to save history: {}", e);
+                }
+                if let Err(e) = history::recent_apps::save(&APP_STATE.recent_apps.lock()) {
+                    error!("Failed to save recent apps: {}", e);
+                }
             }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_result_omits_duration_without_a_start() {
+        let payload = final_result_payload("hello world", None);
+        assert_eq!(payload["word_count"], 2);
+        assert!(payload.get("recording_started_at_ms").is_none());
+        assert!(payload.get("duration_secs").is_none());
+
+        let started_at_ms = unix_time_ms() - 1500;
+        let payload = final_result_payload("hello", Some(started_at_ms));
+        assert_eq!(payload["recording_started_at_ms"], started_at_ms);
+        assert!(payload["duration_secs"].as_f64().unwrap() >= 1.5);
+    }
+}
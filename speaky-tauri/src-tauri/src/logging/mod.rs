@@ -0,0 +1,121 @@
+//! Logging to stdout and to a daily file in `{config_dir}/logs`, so bug
+//! reports from installed builds can include recent entries.
+
+use chrono::{Local, NaiveDate};
+use log::{warn, LevelFilter};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+const LOG_FILE_PREFIX: &str = "speaky_";
+const LOG_FILE_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Daily log files older than this are deleted on startup
+const LOG_RETENTION_DAYS: i64 = 7;
+
+/// Directory holding the daily log files
+pub fn log_dir() -> PathBuf {
+    Config::config_dir().join("logs")
+}
+
+/// Path of today's log file
+pub fn log_path() -> PathBuf {
+    log_dir().join(format!(
+        "{}{}.log",
+        LOG_FILE_PREFIX,
+        Local::now().format(LOG_FILE_DATE_FORMAT)
+    ))
+}
+
+/// Install the global logger. `RUST_LOG` may hold a single level
+/// (e.g. `debug`); the default is `info`.
+pub fn init() {
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(LevelFilter::Info);
+
+    let mut dispatch = fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{} {} {}] {}",
+                Local::now().format("%Y-%m-%dT%H:%M:%S%.3f"),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .level(level)
+        .chain(std::io::stdout());
+
+    let dir = log_dir();
+    let file_error = match fs::create_dir_all(&dir) {
+        Ok(()) => {
+            let prefix = dir.join(LOG_FILE_PREFIX);
+            let file = fern::DateBased::new(prefix, format!("{}.log", LOG_FILE_DATE_FORMAT));
+            dispatch = dispatch.chain(file);
+            None
+        }
+        Err(e) => Some(e),
+    };
+
+    if let Err(e) = dispatch.apply() {
+        eprintln!("Failed to install logger: {}", e);
+        return;
+    }
+
+    match file_error {
+        Some(e) => warn!("Logging to stdout only, cannot create {:?}: {}", dir, e),
+        None => prune_old_logs(),
+    }
+}
+
+/// Last `lines` lines of today's log file
+pub fn recent_lines(lines: usize) -> Vec<String> {
+    let content = fs::read_to_string(log_path()).unwrap_or_default();
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    all[start..].iter().map(|line| line.to_string()).collect()
+}
+
+fn prune_old_logs() {
+    let entries = match fs::read_dir(log_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let cutoff = Local::now().date_naive() - chrono::Duration::days(LOG_RETENTION_DAYS);
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let date = name.to_str().and_then(log_file_date);
+        if date.is_some_and(|date| date < cutoff) {
+            if let Err(e) = fs::remove_file(entry.path()) {
+                warn!("Failed to delete old log {:?}: {}", entry.path(), e);
+            }
+        }
+    }
+}
+
+/// Date of a `speaky_{date}.log` file name
+fn log_file_date(file_name: &str) -> Option<NaiveDate> {
+    let date = file_name
+        .strip_prefix(LOG_FILE_PREFIX)?
+        .strip_suffix(".log")?;
+    NaiveDate::parse_from_str(date, LOG_FILE_DATE_FORMAT).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dates_from_log_file_names() {
+        assert_eq!(
+            log_file_date("speaky_2024-03-09.log"),
+            NaiveDate::from_ymd_opt(2024, 3, 9)
+        );
+        assert_eq!(log_file_date("speaky_latest.log"), None);
+        assert_eq!(log_file_date("config.yaml"), None);
+    }
+}
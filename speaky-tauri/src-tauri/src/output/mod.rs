@@ -0,0 +1,85 @@
+//! Automatic export of recognised text to dated transcript files.
+
+use chrono::Local;
+use log::info;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::{AutoExportConfig, ExportFormat};
+
+/// Append `text` with a timestamp to today's transcript file and return its path
+pub fn append_transcript(config: &AutoExportConfig, text: &str) -> Result<PathBuf, String> {
+    let now = Local::now();
+    let filename = config
+        .filename_pattern
+        .replace("{date}", &now.format("%Y-%m-%d").to_string());
+    let path = config.directory.join(filename);
+
+    fs::create_dir_all(&config.directory)
+        .map_err(|e| format!("Failed to create {:?}: {}", config.directory, e))?;
+
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+
+    let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    file.write_all(format_entry(config.format, &timestamp, text, is_new).as_bytes())
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+    info!("Appended transcript to {:?}", path);
+    Ok(path)
+}
+
+/// Render one entry; CSV files get a header row when first created
+fn format_entry(format: ExportFormat, timestamp: &str, text: &str, is_new: bool) -> String {
+    match format {
+        ExportFormat::PlainText => format!("[{}] {}\n", timestamp, text),
+        ExportFormat::Markdown => format!("- **{}** {}\n", timestamp, text),
+        ExportFormat::Csv => {
+            let header = if is_new { "timestamp,text\n" } else { "" };
+            format!(
+                "{}{},\"{}\"\n",
+                header,
+                timestamp,
+                text.replace('"', "\"\"")
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_entry_quotes_text_and_adds_header_once() {
+        let first = format_entry(
+            ExportFormat::Csv,
+            "2024-01-01 09:00:00",
+            "say \"hi\", ok",
+            true,
+        );
+        assert_eq!(
+            first,
+            "timestamp,text\n2024-01-01 09:00:00,\"say \"\"hi\"\", ok\"\n"
+        );
+
+        let next = format_entry(ExportFormat::Csv, "2024-01-01 09:01:00", "bye", false);
+        assert_eq!(next, "2024-01-01 09:01:00,\"bye\"\n");
+    }
+
+    #[test]
+    fn plain_text_entry_is_prefixed_with_timestamp() {
+        let entry = format_entry(
+            ExportFormat::PlainText,
+            "2024-01-01 09:00:00",
+            "hello",
+            true,
+        );
+        assert_eq!(entry, "[2024-01-01 09:00:00] hello\n");
+    }
+}
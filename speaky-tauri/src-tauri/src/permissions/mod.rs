@@ -0,0 +1,82 @@
+//! OS permissions the app depends on. On macOS the keyboard listener needs
+//! Accessibility access; without it rdev receives no events and fails silently.
+
+use log::warn;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::APP_STATE;
+
+/// Apple's instructions for granting Accessibility access
+pub const ACCESSIBILITY_GUIDE_URL: &str =
+    "https://support.apple.com/guide/mac-help/allow-accessibility-apps-to-access-your-mac-mh43185/mac";
+
+/// Missing permission, as sent in the `permission-required` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequiredPermission {
+    pub permission: String,
+    pub guide_url: String,
+}
+
+/// Emit `permission-required` and keep the permission for
+/// `get_required_permissions`, since this can fire before the webview listens
+pub fn report_required(app: &AppHandle, permission: &str, guide_url: &str) {
+    let required = RequiredPermission {
+        permission: permission.to_string(),
+        guide_url: guide_url.to_string(),
+    };
+    {
+        let mut queued = APP_STATE.required_permissions.write();
+        if !queued.contains(&required) {
+            queued.push(required.clone());
+        }
+    }
+    if let Err(e) = app.emit("permission-required", &required) {
+        warn!("Failed to emit permission-required: {}", e);
+    }
+}
+
+/// Whether the app may observe global key events
+#[cfg(target_os = "macos")]
+pub fn accessibility_trusted() -> bool {
+    unsafe { ffi::AXIsProcessTrusted() }
+}
+
+/// Open the system dialog asking for Accessibility access, returning whether
+/// the app is already trusted. The grant only takes effect after a restart.
+#[cfg(target_os = "macos")]
+pub fn request_accessibility() -> bool {
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::string::CFString;
+
+    unsafe {
+        let prompt = CFString::wrap_under_get_rule(ffi::kAXTrustedCheckOptionPrompt);
+        let options = CFDictionary::from_CFType_pairs(&[(prompt, CFBoolean::true_value())]);
+        ffi::AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn accessibility_trusted() -> bool {
+    true
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn request_accessibility() -> bool {
+    true
+}
+
+#[cfg(target_os = "macos")]
+mod ffi {
+    use core_foundation::dictionary::CFDictionaryRef;
+    use core_foundation::string::CFStringRef;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        pub static kAXTrustedCheckOptionPrompt: CFStringRef;
+        pub fn AXIsProcessTrusted() -> bool;
+        pub fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> bool;
+    }
+}
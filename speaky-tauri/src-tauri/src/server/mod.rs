@@ -0,0 +1,187 @@
+//! Minimal HTTP server so phones and other devices on the LAN can use
+//! Speaky's engine: `POST /transcribe` with a WAV body returns `{"text": ...}`.
+//! The WebSocket server for live events lives in `ws`.
+
+use log::{info, warn};
+use std::io::Read;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tauri::AppHandle;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::config::LanServerConfig;
+use crate::APP_STATE;
+
+mod ws;
+
+pub use ws::WsServer;
+
+/// Largest upload accepted, about 13 minutes of 16 kHz mono WAV
+const MAX_BODY_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Running server; dropping it does not stop it, call `stop`
+pub struct LanServer {
+    server: Arc<Server>,
+    worker: JoinHandle<()>,
+}
+
+impl LanServer {
+    /// Bind to the configured address and handle requests on a background thread
+    pub fn start(config: &LanServerConfig) -> Result<Self, String> {
+        if config.auth_token.is_empty() {
+            return Err("The LAN server needs an auth token".to_string());
+        }
+
+        let addr = format!("{}:{}", config.bind_addr, config.port);
+        let server = Arc::new(
+            Server::http(&addr).map_err(|e| format!("Failed to listen on {}: {}", addr, e))?,
+        );
+        info!("LAN server listening on {}", addr);
+
+        let auth_token = config.auth_token.clone();
+        let requests = Arc::clone(&server);
+        let worker = std::thread::spawn(move || {
+            // Ends when `unblock` is called
+            for request in requests.incoming_requests() {
+                handle(request, &auth_token);
+            }
+        });
+
+        Ok(Self { server, worker })
+    }
+
+    /// Stop accepting requests and wait for the worker to finish
+    pub fn stop(self) {
+        self.server.unblock();
+        if self.worker.join().is_err() {
+            warn!("LAN server thread panicked");
+        }
+        info!("LAN server stopped");
+    }
+}
+
+/// Start the WebSocket server from the saved `ws_server` settings, stopping
+/// the running one first
+pub fn restart_ws_server(app: &AppHandle) -> Result<(), String> {
+    let (ws_config, auth_token) = {
+        let config = APP_STATE.config.read();
+        let ws_config = config
+            .ws_server
+            .clone()
+            .ok_or("The WebSocket server is not configured")?;
+        let auth_token = config
+            .lan_server
+            .as_ref()
+            .map(|server| server.auth_token.clone())
+            .unwrap_or_default();
+        (ws_config, auth_token)
+    };
+
+    let mut running = APP_STATE.ws_server.lock();
+    if let Some(server) = running.take() {
+        server.stop();
+    }
+    *running = Some(WsServer::start(&ws_config, &auth_token, app)?);
+    Ok(())
+}
+
+fn handle(mut request: Request, auth_token: &str) {
+    let (status, body) = route(&mut request, auth_token);
+    if status != 200 {
+        warn!(
+            "LAN server: {} {} -> {}",
+            request.method(),
+            request.url(),
+            status
+        );
+    }
+
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        warn!("LAN server failed to respond: {}", e);
+    }
+}
+
+fn route(request: &mut Request, auth_token: &str) -> (u16, serde_json::Value) {
+    if request.url() != "/transcribe" {
+        return error(404, "Not found");
+    }
+    if *request.method() != Method::Post {
+        return error(405, "Use POST");
+    }
+    let authorized = request
+        .headers()
+        .iter()
+        .any(|h| h.field.equiv("Authorization") && bearer_matches(h.value.as_str(), auth_token));
+    if !authorized {
+        return error(401, "Invalid or missing bearer token");
+    }
+
+    let mut audio = Vec::new();
+    let read = request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut audio);
+    if let Err(e) = read {
+        return error(400, &format!("Failed to read body: {}", e));
+    }
+    if audio.len() as u64 > MAX_BODY_BYTES {
+        return error(413, "Audio too large");
+    }
+    if crate::audio::processing::parse_wav_sample_rate(&audio).is_none() {
+        return error(400, "Body must be a WAV file");
+    }
+
+    let language = APP_STATE.config.read().core.asr.language.clone();
+    let engine = APP_STATE.engine.read();
+    let Some(engine) = engine.as_ref() else {
+        return error(503, "No engine configured");
+    };
+
+    info!("LAN server: transcribing {} bytes", audio.len());
+    match engine.transcribe(&audio, &language) {
+        Ok(text) => (200, serde_json::json!({ "text": text })),
+        Err(e) => error(502, &e),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, serde_json::Value) {
+    (status, serde_json::json!({ "error": message }))
+}
+
+/// Whether an `Authorization` header value carries `auth_token` as a bearer token
+fn bearer_matches(header: &str, auth_token: &str) -> bool {
+    header
+        .strip_prefix("Bearer ")
+        .is_some_and(|token| tokens_match(token, auth_token))
+}
+
+/// Compare tokens in time that does not depend on where they first differ,
+/// so the token cannot be guessed byte by byte from response times
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bearer_token_must_match_exactly() {
+        assert!(bearer_matches("Bearer secret", "secret"));
+        assert!(!bearer_matches("Bearer secrets", "secret"));
+        assert!(!bearer_matches("Bearer secreT", "secret"));
+        assert!(!bearer_matches("Bearer ", "secret"));
+        assert!(!bearer_matches("secret", "secret"));
+        assert!(!bearer_matches("Basic secret", "secret"));
+    }
+}
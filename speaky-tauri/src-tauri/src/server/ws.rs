@@ -0,0 +1,256 @@
+//! WebSocket server for programmatic clients. Each client receives recognition
+//! events as `{"event": name, "payload": ...}` and may send
+//! `{"command": "start"}` or `{"command": "stop"}` to control recording.
+
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn};
+use tauri::{AppHandle, EventId, Listener};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::config::WsServerConfig;
+
+/// App events forwarded to every client
+const FORWARDED_EVENTS: &[&str] = &[
+    "audio-level",
+    "partial-result",
+    "final-result",
+    "recording-state",
+];
+
+/// Events a slow client may fall behind by before it starts missing some
+const EVENT_BUFFER: usize = 256;
+
+/// Running server; call `stop` to shut it down
+pub struct WsServer {
+    shutdown: watch::Sender<bool>,
+    listeners: Vec<EventId>,
+    app: AppHandle,
+}
+
+impl WsServer {
+    /// Bind to the configured address and serve clients on the async runtime
+    pub fn start(
+        config: &WsServerConfig,
+        auth_token: &str,
+        app: &AppHandle,
+    ) -> Result<Self, String> {
+        if auth_token.is_empty() {
+            return Err("The WebSocket server needs an auth token".to_string());
+        }
+
+        // Bind here so an address in use is reported to the caller
+        let addr = format!("{}:{}", config.bind_addr, config.port);
+        let listener = std::net::TcpListener::bind(&addr)
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+        info!("WebSocket server listening on {}", addr);
+
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        let listeners = FORWARDED_EVENTS
+            .iter()
+            .map(|&name| {
+                let events = events.clone();
+                app.listen_any(name, move |event| {
+                    let payload: serde_json::Value =
+                        serde_json::from_str(event.payload()).unwrap_or_default();
+                    let message = serde_json::json!({ "event": name, "payload": payload });
+                    // No receivers just means no client is connected
+                    let _ = events.send(message.to_string());
+                })
+            })
+            .collect();
+
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let auth_token = auth_token.to_string();
+        tauri::async_runtime::spawn(async move {
+            let listener = match TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!("WebSocket server failed to start: {}", e);
+                    return;
+                }
+            };
+            accept_loop(listener, events, shutdown_rx, auth_token).await;
+        });
+
+        Ok(Self {
+            shutdown,
+            listeners,
+            app: app.clone(),
+        })
+    }
+
+    /// Stop accepting clients and close the open connections
+    pub fn stop(self) {
+        for id in self.listeners {
+            self.app.unlisten(id);
+        }
+        let _ = self.shutdown.send(true);
+        info!("WebSocket server stopped");
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    events: broadcast::Sender<String>,
+    mut shutdown: watch::Receiver<bool>,
+    auth_token: String,
+) {
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    info!("WebSocket client connected from {}", peer);
+                    tokio::spawn(handle_client(
+                        stream,
+                        events.subscribe(),
+                        shutdown.clone(),
+                        auth_token.clone(),
+                    ));
+                }
+                Err(e) => warn!("WebSocket server failed to accept: {}", e),
+            },
+        }
+    }
+}
+
+// The handshake callback's error type is fixed by tungstenite
+#[allow(clippy::result_large_err)]
+async fn handle_client(
+    stream: TcpStream,
+    mut events: broadcast::Receiver<String>,
+    mut shutdown: watch::Receiver<bool>,
+    auth_token: String,
+) {
+    let check_auth = |request: &Request, response: Response| {
+        if is_authorized(request, &auth_token) {
+            Ok(response)
+        } else {
+            let mut rejection = ErrorResponse::new(Some("Invalid or missing token".to_string()));
+            *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(rejection)
+        }
+    };
+    let ws = match tokio_tungstenite::accept_hdr_async(stream, check_auth).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            warn!("WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+    let (mut sink, mut incoming) = ws.split();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            event = events.recv() => match event {
+                Ok(text) => {
+                    if sink.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("WebSocket client missed {} events", missed);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = run_command(&text).await;
+                    if sink.send(Message::Text(reply.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    warn!("WebSocket client error: {}", e);
+                    break;
+                }
+            },
+        }
+    }
+
+    let _ = sink.close().await;
+    info!("WebSocket client disconnected");
+}
+
+/// Accept the token as `Authorization: Bearer {token}` or, for browsers that
+/// cannot set headers on a WebSocket, as a `token` query parameter
+fn is_authorized(request: &Request, auth_token: &str) -> bool {
+    let header_ok = request
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| super::tokens_match(token, auth_token));
+    let query_ok = request.uri().query().is_some_and(|query| {
+        query.split('&').any(|pair| {
+            pair.strip_prefix("token=")
+                .is_some_and(|token| super::tokens_match(token, auth_token))
+        })
+    });
+    header_ok || query_ok
+}
+
+/// Handle a client message, replying with `{"command", "ok"}` and an `error` on failure
+async fn run_command(text: &str) -> serde_json::Value {
+    let command = serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|message| message["command"].as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    // Starting and stopping touch the audio device, so keep them off the runtime
+    let result = match command.as_str() {
+        "start" => tauri::async_runtime::spawn_blocking(crate::hotkey::start_remote_recording)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result),
+        "stop" => tauri::async_runtime::spawn_blocking(crate::hotkey::stop_remote_recording)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result),
+        _ => Err(format!("Unknown command: {:?}", command)),
+    };
+
+    match result {
+        Ok(()) => serde_json::json!({ "command": command, "ok": true }),
+        Err(e) => serde_json::json!({ "command": command, "ok": false, "error": e }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(value) = authorization {
+            builder = builder.header("Authorization", value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn token_is_accepted_from_header_or_query() {
+        assert!(is_authorized(
+            &request("/", Some("Bearer secret")),
+            "secret"
+        ));
+        assert!(is_authorized(
+            &request("/?a=1&token=secret", None),
+            "secret"
+        ));
+        assert!(!is_authorized(&request("/?token=secrets", None), "secret"));
+        assert!(!is_authorized(
+            &request("/", Some("Bearer wrong")),
+            "secret"
+        ));
+        assert!(!is_authorized(&request("/", None), "secret"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Checks run once setup is done, so first-run problems can be shown to the
+//! user instead of only being logged.
+
+use log::warn;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::hotkey;
+use crate::APP_STATE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// Speaky works, but not as configured
+    Warning,
+    /// Recording or recognition cannot work until this is fixed
+    Error,
+}
+
+/// Problem found by `run_startup_checks`
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupIssue {
+    pub severity: IssueSeverity,
+    /// Stable identifier the frontend can match on
+    pub code: String,
+    pub message: String,
+}
+
+impl StartupIssue {
+    fn new(severity: IssueSeverity, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Check the audio device, engine and hotkeys, then emit `startup-status`
+/// with the issues found
+pub fn run_startup_checks(app: &AppHandle) -> Vec<StartupIssue> {
+    let mut issues = Vec::new();
+
+    let has_device = APP_STATE
+        .recorder
+        .read()
+        .as_ref()
+        .is_some_and(|recorder| recorder.has_device());
+    if !has_device {
+        issues.push(StartupIssue::new(
+            IssueSeverity::Error,
+            "no_audio_device",
+            "No audio input device found",
+        ));
+    }
+
+    let permission_error = APP_STATE
+        .recorder
+        .read()
+        .as_ref()
+        .and_then(|recorder| recorder.permission_error().map(str::to_string));
+    if let Some(message) = permission_error {
+        issues.push(StartupIssue::new(
+            IssueSeverity::Error,
+            "microphone_permission_denied",
+            message,
+        ));
+    }
+
+    if APP_STATE.engine.read().is_none() {
+        let current = APP_STATE.config.read().engine.current.clone();
+        issues.push(StartupIssue::new(
+            IssueSeverity::Error,
+            "engine_not_configured",
+            format!("The {} engine is not configured", current),
+        ));
+    }
+
+    issues.extend(check_hotkeys());
+
+    for issue in &issues {
+        warn!("Startup check {}: {}", issue.code, issue.message);
+    }
+    // The event can fire before the webview listens, so keep a copy to query
+    *APP_STATE.startup_issues.write() = issues.clone();
+    if let Err(e) = app.emit("startup-status", &issues) {
+        warn!("Failed to emit startup-status: {}", e);
+    }
+    issues
+}
+
+fn check_hotkeys() -> Vec<StartupIssue> {
+    let mut issues = Vec::new();
+
+    // rdev reads key events through X11, which pure Wayland sessions do not offer
+    #[cfg(target_os = "linux")]
+    if std::env::var_os("DISPLAY").is_none() {
+        issues.push(StartupIssue::new(
+            IssueSeverity::Error,
+            "hotkeys_unavailable",
+            "Global hotkeys need an X11 display (or XWayland)",
+        ));
+    }
+
+    if !crate::permissions::accessibility_trusted() {
+        issues.push(StartupIssue::new(
+            IssueSeverity::Error,
+            "hotkeys_unavailable",
+            "Global hotkeys need Accessibility access in System Settings > Privacy & Security",
+        ));
+    }
+
+    let config = APP_STATE.config.read();
+    let hotkeys = std::iter::once(&config.core.asr.hotkey)
+        .chain(config.core.extra_hotkeys.iter().map(|p| &p.hotkey));
+    for hotkey in hotkeys {
+        if !hotkey::is_valid_hotkey(hotkey) {
+            issues.push(StartupIssue::new(
+                IssueSeverity::Warning,
+                "invalid_hotkey",
+                format!("Unknown hotkey {:?}, using Ctrl instead", hotkey),
+            ));
+        }
+    }
+
+    if let Err(errors) = config.validate() {
+        issues.extend(
+            errors
+                .into_iter()
+                .map(|error| StartupIssue::new(IssueSeverity::Warning, "invalid_config", error)),
+        );
+    }
+    issues
+}
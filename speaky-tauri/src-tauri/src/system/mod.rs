@@ -0,0 +1,37 @@
+//! Facts about the machine for support requests.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+
+/// Platform and audio details reported by `get_system_info`
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub os: String,
+    pub os_version: String,
+    pub arch: String,
+    pub cpal_host: String,
+    pub default_device_name: Option<String>,
+    pub speaky_version: String,
+}
+
+/// Gather the current system's details
+pub fn system_info() -> SystemInfo {
+    let host = cpal::default_host();
+    SystemInfo {
+        os: std::env::consts::OS.to_string(),
+        os_version: os_version().unwrap_or_else(|| "unknown".to_string()),
+        arch: std::env::consts::ARCH.to_string(),
+        cpal_host: host.id().name().to_string(),
+        default_device_name: host.default_input_device().and_then(|d| d.name().ok()),
+        speaky_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// OS name and version, e.g. "Ubuntu 24.04" or "Windows 10.0.22631"
+fn os_version() -> Option<String> {
+    let info = os_info::get();
+    match info.version() {
+        os_info::Version::Unknown => None,
+        version => Some(format!("{} {}", info.os_type(), version)),
+    }
+}
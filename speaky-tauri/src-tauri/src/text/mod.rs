@@ -0,0 +1,133 @@
+use log::warn;
+use regex::Regex;
+
+use crate::config::{ProfanityFilterConfig, TextTransform};
+
+mod post_process;
+
+pub use post_process::run_post_process;
+
+/// Apply `transforms` to `text` in order
+pub fn apply_transforms(text: &str, transforms: &[TextTransform]) -> String {
+    transforms
+        .iter()
+        .fold(text.to_string(), |text, transform| match transform {
+            TextTransform::Lowercase => text.to_lowercase(),
+            TextTransform::Uppercase => text.to_uppercase(),
+            TextTransform::TitleCase => title_case(&text),
+            TextTransform::Trim => text.trim().to_string(),
+            TextTransform::CollapseWhitespace => {
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+            TextTransform::RegexReplace {
+                pattern,
+                replacement,
+            } => match Regex::new(pattern) {
+                Ok(re) => re.replace_all(&text, replacement.as_str()).into_owned(),
+                Err(e) => {
+                    warn!("Skipping invalid transform pattern {:?}: {}", pattern, e);
+                    text
+                }
+            },
+        })
+}
+
+/// Replace whole-word, case-insensitive matches of the configured words
+pub fn apply_profanity_filter(text: &str, cfg: &ProfanityFilterConfig) -> String {
+    let words: Vec<String> = cfg
+        .wordlist
+        .iter()
+        .map(|word| word.trim())
+        .filter(|word| !word.is_empty())
+        .map(regex::escape)
+        .collect();
+    if words.is_empty() {
+        return text.to_string();
+    }
+
+    let pattern = format!(r"(?i)\b(?:{})\b", words.join("|"));
+    match Regex::new(&pattern) {
+        Ok(re) => re
+            .replace_all(text, regex::NoExpand(&cfg.replacement))
+            .into_owned(),
+        Err(e) => {
+            warn!("Skipping profanity filter: {}", e);
+            text.to_string()
+        }
+    }
+}
+
+/// Capitalize the first letter of every word and lowercase the rest
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut at_word_start = true;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            at_word_start = true;
+            result.push(c);
+        } else if at_word_start {
+            at_word_start = false;
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforms_apply_in_order() {
+        let transforms = vec![
+            TextTransform::Trim,
+            TextTransform::CollapseWhitespace,
+            TextTransform::TitleCase,
+        ];
+
+        assert_eq!(
+            apply_transforms("  hello   WORLD\n", &transforms),
+            "Hello World"
+        );
+    }
+
+    #[test]
+    fn regex_replace_uses_capture_groups() {
+        let transforms = vec![TextTransform::RegexReplace {
+            pattern: r"(\d+) percent".to_string(),
+            replacement: "$1%".to_string(),
+        }];
+
+        assert_eq!(apply_transforms("up 5 percent", &transforms), "up 5%");
+    }
+
+    #[test]
+    fn profanity_filter_matches_whole_words_only() {
+        let cfg = ProfanityFilterConfig {
+            wordlist: vec!["darn".to_string(), "heck".to_string()],
+            replacement: "***".to_string(),
+        };
+
+        assert_eq!(
+            apply_profanity_filter("Darn it, what the HECK. darned", &cfg),
+            "*** it, what the ***. darned"
+        );
+    }
+
+    #[test]
+    fn invalid_regex_leaves_text_unchanged() {
+        let transforms = vec![
+            TextTransform::RegexReplace {
+                pattern: "(".to_string(),
+                replacement: String::new(),
+            },
+            TextTransform::Uppercase,
+        ];
+
+        assert_eq!(apply_transforms("ok", &transforms), "OK");
+    }
+}
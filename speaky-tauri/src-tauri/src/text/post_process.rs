@@ -0,0 +1,133 @@
+//! Piping recognized text through a user-supplied shell command.
+
+use log::debug;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// The command is killed if it has not exited by then
+const POST_PROCESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Run `command` through the shell with `text` on stdin and return its stdout,
+/// without the trailing newline most tools print
+pub fn run_post_process(command: &str, text: &str) -> Result<String, String> {
+    run_with_timeout(command, text, POST_PROCESS_TIMEOUT)
+}
+
+fn run_with_timeout(command: &str, text: &str, timeout: Duration) -> Result<String, String> {
+    debug!("Running post-process command: {}", command);
+
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run post-process command: {}", e))?;
+
+    // Feed stdin and drain the pipes on their own threads so a command that
+    // fills a pipe buffer cannot stall the timeout
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = text.as_bytes().to_vec();
+    std::thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+    let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+
+    let deadline = Instant::now() + timeout;
+    let output = stdout.recv_timeout(timeout).ok();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) if output.is_some() => break status,
+            Ok(_) if Instant::now() < deadline => std::thread::sleep(POLL_INTERVAL),
+            Ok(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Post-process command timed out after {}s",
+                    timeout.as_secs_f32()
+                ));
+            }
+            Err(e) => return Err(format!("Failed to wait for post-process command: {}", e)),
+        }
+    };
+    debug!("Post-process command exited with {:?}", status.code());
+
+    if !status.success() {
+        // A background process left behind can keep stderr open
+        let stderr = stderr
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .unwrap_or_default();
+        return Err(format!(
+            "Post-process command failed ({}): {}",
+            status,
+            String::from_utf8_lossy(&stderr).trim()
+        ));
+    }
+
+    let output = output.unwrap_or_default();
+    Ok(String::from_utf8_lossy(&output)
+        .trim_end_matches(['\r', '\n'])
+        .to_string())
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+/// Read `pipe` to the end on a new thread, sending the bytes when done
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        let _ = tx.send(buffer);
+    });
+    rx
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stdout_is_returned_without_the_trailing_newline() {
+        assert_eq!(
+            run_post_process("cat; echo", "hello world"),
+            Ok("hello world".to_string())
+        );
+        // Only line endings are trimmed
+        assert_eq!(
+            run_post_process("cat", "  spaced \n\n"),
+            Ok("  spaced ".to_string())
+        );
+    }
+
+    #[test]
+    fn non_zero_exit_reports_stderr() {
+        let err = run_post_process("echo broken >&2; exit 3", "text").unwrap_err();
+        assert!(err.contains("exit status: 3"), "{}", err);
+        assert!(err.ends_with("broken"), "{}", err);
+    }
+
+    #[test]
+    fn slow_command_is_killed() {
+        let started = Instant::now();
+        let err = run_with_timeout("sleep 10", "text", Duration::from_millis(200)).unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
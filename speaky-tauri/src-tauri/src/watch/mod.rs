@@ -0,0 +1,179 @@
+//! Watch mode: transcribe audio files as they appear in a directory.
+
+use log::{error, info, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::audio;
+use crate::config::WatchDirConfig;
+use crate::APP_STATE;
+
+/// How often a new file's size is checked while it is still being written
+const SETTLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Give up waiting for a file that keeps growing after this many polls
+const SETTLE_MAX_POLLS: u32 = 60;
+
+/// Running directory watcher; dropping it stops the watch
+pub struct DirectoryWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl DirectoryWatcher {
+    /// Watch `config.path` and transcribe matching files on a background thread
+    pub fn start(config: &WatchDirConfig) -> Result<Self, String> {
+        std::fs::create_dir_all(&config.output_dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", config.output_dir, e))?;
+
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to create directory watcher: {}", e))?;
+        watcher
+            .watch(&config.path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {:?}: {}", config.path, e))?;
+        info!("Watching {:?} for {} files", config.path, config.pattern);
+
+        let config = config.clone();
+        std::thread::spawn(move || {
+            // Ends when the watcher, and with it the sender, is dropped
+            for event in rx {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("Directory watcher error: {}", e);
+                        continue;
+                    }
+                };
+                if !matches!(event.kind, EventKind::Create(_)) {
+                    continue;
+                }
+
+                for path in event.paths {
+                    let matches = path.file_name().is_some_and(|name| {
+                        matches_pattern(&config.pattern, &name.to_string_lossy())
+                    });
+                    if !matches || !path.is_file() {
+                        continue;
+                    }
+                    match transcribe_to_file(&path, &config.output_dir) {
+                        Ok(output) => APP_STATE.emit(
+                            "watch-transcribed",
+                            serde_json::json!({
+                                "input": path.to_string_lossy(),
+                                "output": output.to_string_lossy()
+                            }),
+                        ),
+                        Err(e) => error!("Failed to transcribe {:?}: {}", path, e),
+                    }
+                }
+            }
+        });
+
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// Transcribe `input` once it has been fully written, returning the text file's path
+fn transcribe_to_file(input: &Path, output_dir: &Path) -> Result<PathBuf, String> {
+    wait_until_written(input);
+
+    let data = std::fs::read(input).map_err(|e| format!("Failed to read: {}", e))?;
+    let wav = audio::decode::to_wav(data)?;
+    let language = APP_STATE.config.read().core.asr.language.clone();
+    let text = {
+        let engine = APP_STATE.engine.read();
+        let engine = engine
+            .as_ref()
+            .ok_or_else(|| "No engine configured".to_string())?;
+        engine.transcribe(&wav, &language)?
+    };
+
+    let output = transcript_path(input, output_dir);
+    std::fs::write(&output, text).map_err(|e| format!("Failed to write {:?}: {}", output, e))?;
+    info!("Transcribed {:?} to {:?}", input, output);
+    Ok(output)
+}
+
+/// Where the transcript of `input` goes: its full file name plus `.txt`, so
+/// `a.wav` and `a.mp3` get separate transcripts
+fn transcript_path(input: &Path, output_dir: &Path) -> PathBuf {
+    let name = input.file_name().unwrap_or_default().to_string_lossy();
+    output_dir.join(format!("{}.txt", name))
+}
+
+/// Create events arrive before the writer is done; wait for the size to settle
+fn wait_until_written(path: &Path) {
+    let size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).ok();
+    let mut last = size(path);
+    for _ in 0..SETTLE_MAX_POLLS {
+        std::thread::sleep(SETTLE_POLL_INTERVAL);
+        let current = size(path);
+        if current == last {
+            return;
+        }
+        last = current;
+    }
+    warn!("{:?} is still growing, transcribing it anyway", path);
+}
+
+/// Match a file name against a glob where `*` is any run and `?` any one character
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star + 1;
+                    n = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_patterns_match_file_names() {
+        assert!(matches_pattern("*.wav", "meeting.wav"));
+        assert!(matches_pattern("*.wav", ".wav"));
+        assert!(!matches_pattern("*.wav", "meeting.wav.part"));
+        assert!(matches_pattern("rec-??.*", "rec-01.mp3"));
+        assert!(!matches_pattern("rec-??.*", "rec-1.mp3"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("a*b*c", "axxbyyc"));
+        assert!(!matches_pattern("a*b*c", "axxbyy"));
+    }
+
+    #[test]
+    fn transcripts_keep_the_audio_extension() {
+        let out = Path::new("/out");
+        assert_eq!(
+            transcript_path(Path::new("/in/a.wav"), out),
+            Path::new("/out/a.wav.txt")
+        );
+        assert_eq!(
+            transcript_path(Path::new("/in/a.mp3"), out),
+            Path::new("/out/a.mp3.txt")
+        );
+    }
+}
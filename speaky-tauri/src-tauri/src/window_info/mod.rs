@@ -1,21 +1,26 @@
 //! Window information utilities for getting focused app name and icon.
 
-use log::{debug, warn};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Mutex;
+use std::time::SystemTime;
 use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::APP_STATE;
 
 /// Information about the focused window
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct WindowInfo {
     pub wm_class: String,
     pub wm_instance: String,
     pub window_name: String,
     pub app_name: String,
     pub icon_path: Option<String>,
+    /// The icon as a `data:` URL the frontend can display directly
+    pub icon_data: Option<String>,
 }
 
 /// Cache for desktop entries
@@ -35,18 +40,827 @@ struct DesktopEntry {
 
 /// Get information about the currently focused window
 pub fn get_focused_window_info() -> Option<WindowInfo> {
+    platform_window_info().map(|mut info| {
+        info.icon_data = info.icon_path.as_deref().and_then(icon_data_url);
+        info
+    })
+}
+
+/// Encoded icon kept in `AppState::icon_cache`
+pub struct CachedIcon {
+    modified: SystemTime,
+    data_url: String,
+}
+
+/// Icon as a `data:` URL, re-encoded only when the file has changed since it was cached
+fn icon_data_url(path: &str) -> Option<String> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    if let Some(cached) = APP_STATE.icon_cache.read().get(path) {
+        if cached.modified == modified {
+            return Some(cached.data_url.clone());
+        }
+    }
+
+    let data_url = encode_icon(path)?;
+    APP_STATE.icon_cache.write().insert(
+        path.to_string(),
+        CachedIcon {
+            modified,
+            data_url: data_url.clone(),
+        },
+    );
+    Some(data_url)
+}
+
+/// Read an icon file into a base64 `data:` URL
+fn encode_icon(path: &str) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("png");
+    let mime = match ext {
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => "image/png",
+    };
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data)
+    ))
+}
+
+fn platform_window_info() -> Option<WindowInfo> {
     #[cfg(target_os = "linux")]
     {
         get_linux_window_info()
     }
-    #[cfg(not(target_os = "linux"))]
+    #[cfg(target_os = "macos")]
+    {
+        get_macos_window_info()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        get_windows_window_info()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     {
         None
     }
 }
 
+#[cfg(target_os = "windows")]
+fn get_windows_window_info() -> Option<WindowInfo> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{CloseHandle, MAX_PATH};
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+
+        let mut title = [0u16; 512];
+        let title_len = GetWindowTextW(hwnd, &mut title);
+        let window_name = String::from_utf16_lossy(&title[..title_len.max(0) as usize]);
+
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        if process_id == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+        let mut exe_buf = [0u16; MAX_PATH as usize * 4];
+        let mut exe_len = exe_buf.len() as u32;
+        let query = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(exe_buf.as_mut_ptr()),
+            &mut exe_len,
+        );
+        let _ = CloseHandle(process);
+        query.ok()?;
+
+        let exe_path = String::from_utf16_lossy(&exe_buf[..exe_len as usize]);
+        let exe_stem = Path::new(&exe_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+
+        // Prefer the FileDescription from the version resource ("Visual Studio Code"
+        // rather than "Code")
+        let app_name = read_file_description(&exe_path).unwrap_or_else(|| exe_stem.clone());
+
+        let cached = ICON_CACHE
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&exe_path).cloned());
+        let icon_path = match cached {
+            Some(path) => path,
+            None => {
+                let path = extract_exe_icon_png(&exe_path)
+                    .and_then(|png| write_icon_file(&exe_stem, &png));
+                if let Ok(mut cache) = ICON_CACHE.lock() {
+                    cache.insert(exe_path.clone(), path.clone());
+                }
+                path
+            }
+        };
+
+        Some(WindowInfo {
+            wm_class: exe_stem.clone(),
+            wm_instance: exe_stem,
+            window_name,
+            app_name,
+            icon_path,
+            icon_data: None,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Read the FileDescription string from an executable's version resource
+#[cfg(target_os = "windows")]
+fn read_file_description(exe_path: &str) -> Option<String> {
+    use std::ffi::c_void;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+    };
+
+    let wide_path = to_wide(exe_path);
+
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(wide_path.as_ptr()), None);
+        if size == 0 {
+            return None;
+        }
+
+        let mut data = vec![0u8; size as usize];
+        GetFileVersionInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            0,
+            size,
+            data.as_mut_ptr() as *mut c_void,
+        )
+        .ok()?;
+
+        // First entry of the translation table gives language + code page
+        let translation_key = to_wide("\\VarFileInfo\\Translation");
+        let mut translation_ptr: *mut c_void = std::ptr::null_mut();
+        let mut translation_len = 0u32;
+        if !VerQueryValueW(
+            data.as_ptr() as *const c_void,
+            PCWSTR(translation_key.as_ptr()),
+            &mut translation_ptr,
+            &mut translation_len,
+        )
+        .as_bool()
+            || translation_len < 4
+        {
+            return None;
+        }
+        let translation = translation_ptr as *const u16;
+        let language = *translation;
+        let code_page = *translation.add(1);
+
+        let description_key = to_wide(&format!(
+            "\\StringFileInfo\\{:04x}{:04x}\\FileDescription",
+            language, code_page
+        ));
+        let mut description_ptr: *mut c_void = std::ptr::null_mut();
+        let mut description_len = 0u32;
+        if !VerQueryValueW(
+            data.as_ptr() as *const c_void,
+            PCWSTR(description_key.as_ptr()),
+            &mut description_ptr,
+            &mut description_len,
+        )
+        .as_bool()
+            || description_len == 0
+        {
+            return None;
+        }
+
+        let chars =
+            std::slice::from_raw_parts(description_ptr as *const u16, description_len as usize);
+        let description = String::from_utf16_lossy(chars)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string();
+        if description.is_empty() {
+            None
+        } else {
+            Some(description)
+        }
+    }
+}
+
+/// Extract the large icon of an executable and encode it as PNG
+#[cfg(target_os = "windows")]
+fn extract_exe_icon_png(exe_path: &str) -> Option<Vec<u8>> {
+    use std::ffi::c_void;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::UI::Shell::ExtractIconExW;
+    use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, HICON, ICONINFO};
+
+    let wide_path = to_wide(exe_path);
+
+    unsafe {
+        let mut large_icon = HICON::default();
+        let extracted = ExtractIconExW(
+            PCWSTR(wide_path.as_ptr()),
+            0,
+            Some(&mut large_icon),
+            None,
+            1,
+        );
+        if extracted == 0 || large_icon.is_invalid() {
+            return None;
+        }
+
+        let mut icon_info = ICONINFO::default();
+        let info_result = GetIconInfo(large_icon, &mut icon_info);
+        let _ = DestroyIcon(large_icon);
+        info_result.ok()?;
+
+        let mut bitmap = BITMAP::default();
+        let got = GetObjectW(
+            icon_info.hbmColor,
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut bitmap as *mut BITMAP as *mut c_void),
+        );
+
+        let width = bitmap.bmWidth;
+        let height = bitmap.bmHeight;
+        let mut pixels = vec![0u8; (width.max(0) * height.max(0) * 4) as usize];
+
+        let mut bitmap_info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative height requests a top-down DIB
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let lines = if got != 0 && !pixels.is_empty() {
+            let hdc = GetDC(HWND::default());
+            let lines = GetDIBits(
+                hdc,
+                icon_info.hbmColor,
+                0,
+                height as u32,
+                Some(pixels.as_mut_ptr() as *mut c_void),
+                &mut bitmap_info,
+                DIB_RGB_COLORS,
+            );
+            ReleaseDC(HWND::default(), hdc);
+            lines
+        } else {
+            0
+        };
+
+        let _ = DeleteObject(icon_info.hbmColor);
+        let _ = DeleteObject(icon_info.hbmMask);
+
+        if lines == 0 {
+            return None;
+        }
+
+        // BGRA -> RGBA; legacy icons without an alpha channel are fully opaque
+        let has_alpha = pixels.chunks_exact(4).any(|px| px[3] != 0);
+        for px in pixels.chunks_exact_mut(4) {
+            px.swap(0, 2);
+            if !has_alpha {
+                px[3] = 255;
+            }
+        }
+
+        let image = image::RgbaImage::from_raw(width as u32, height as u32, pixels)?;
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .ok()?;
+        Some(png)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn get_macos_window_info() -> Option<WindowInfo> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSAutoreleasePool;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let app: id = msg_send![workspace, frontmostApplication];
+        if app == nil {
+            pool.drain();
+            return None;
+        }
+
+        let localized_name: id = msg_send![app, localizedName];
+        let bundle_identifier: id = msg_send![app, bundleIdentifier];
+        let app_name = nsstring_to_string(localized_name).unwrap_or_else(|| "Unknown".to_string());
+        let bundle_id = nsstring_to_string(bundle_identifier).unwrap_or_default();
+
+        // Bundle id plays the role of WM_CLASS on macOS
+        let cache_key = if bundle_id.is_empty() {
+            app_name.clone()
+        } else {
+            bundle_id.clone()
+        };
+
+        let cached = ICON_CACHE
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&cache_key).cloned());
+        let icon_path = match cached {
+            Some(path) => path,
+            None => {
+                let icon: id = msg_send![app, icon];
+                let path = nsimage_to_png(icon).and_then(|png| write_icon_file(&cache_key, &png));
+                if let Ok(mut cache) = ICON_CACHE.lock() {
+                    cache.insert(cache_key, path.clone());
+                }
+                path
+            }
+        };
+
+        pool.drain();
+
+        Some(WindowInfo {
+            wm_class: bundle_id.clone(),
+            wm_instance: bundle_id,
+            window_name: String::new(),
+            app_name,
+            icon_path,
+            icon_data: None,
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn nsstring_to_string(string: cocoa::base::id) -> Option<String> {
+    use objc::{msg_send, sel, sel_impl};
+
+    if string == cocoa::base::nil {
+        return None;
+    }
+    let utf8: *const std::os::raw::c_char = msg_send![string, UTF8String];
+    if utf8.is_null() {
+        return None;
+    }
+    Some(
+        std::ffi::CStr::from_ptr(utf8)
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// Convert an NSImage to a PNG byte buffer via NSBitmapImageRep
+#[cfg(target_os = "macos")]
+unsafe fn nsimage_to_png(image: cocoa::base::id) -> Option<Vec<u8>> {
+    use cocoa::base::{id, nil};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    // NSBitmapImageFileTypePNG
+    const PNG_FILE_TYPE: u64 = 4;
+
+    if image == nil {
+        return None;
+    }
+
+    let tiff: id = msg_send![image, TIFFRepresentation];
+    if tiff == nil {
+        return None;
+    }
+    let rep: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+    if rep == nil {
+        return None;
+    }
+    let properties: id = msg_send![class!(NSDictionary), dictionary];
+    let png: id = msg_send![rep, representationUsingType: PNG_FILE_TYPE properties: properties];
+    if png == nil {
+        return None;
+    }
+
+    let length: usize = msg_send![png, length];
+    let bytes: *const u8 = msg_send![png, bytes];
+    if bytes.is_null() || length == 0 {
+        return None;
+    }
+    Some(std::slice::from_raw_parts(bytes, length).to_vec())
+}
+
+/// Write PNG icon data to the icon cache directory and return its path.
+///
+/// Platforms without icon files on disk (macOS, Windows) extract icons in
+/// memory; persisting them lets the `app-info` event keep using `icon_path`.
+#[cfg(not(target_os = "linux"))]
+fn write_icon_file(key: &str, png: &[u8]) -> Option<String> {
+    let dir = crate::config::Config::config_dir().join("icons");
+    fs::create_dir_all(&dir).ok()?;
+
+    let file_name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = dir.join(format!("{}.png", file_name));
+    fs::write(&path, png).ok()?;
+    path.to_str().map(|s| s.to_string())
+}
+
 #[cfg(target_os = "linux")]
 fn get_linux_window_info() -> Option<WindowInfo> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let x11 = std::env::var_os("DISPLAY").is_some();
+
+    if wayland {
+        if let Some(info) = get_wayland_window_info() {
+            return Some(info);
+        }
+        log::debug!("Wayland window lookup failed");
+    }
+
+    // Under Wayland this only sees XWayland clients, so it is a fallback
+    if x11 {
+        return get_x11_window_info();
+    }
+
+    None
+}
+
+/// Query the focused window on Wayland compositors.
+///
+/// Wayland has no global protocol for this, so each desktop is asked in turn:
+/// GNOME Shell over D-Bus, the wlroots (sway/Hyprland) CLIs, then KWin over D-Bus.
+#[cfg(target_os = "linux")]
+fn get_wayland_window_info() -> Option<WindowInfo> {
+    let (wm_class, wm_instance, window_name) = query_gnome_shell()
+        .or_else(query_sway)
+        .or_else(query_hyprland)
+        .or_else(query_kwin)?;
+
+    Some(resolve_window_info(wm_class, wm_instance, window_name))
+}
+
+/// Ask GNOME Shell for the focused window via `org.gnome.Shell.Eval`.
+///
+/// Eval is only available when GNOME Shell runs in unsafe mode or with an
+/// extension enabling it; otherwise the call fails and we move on.
+#[cfg(target_os = "linux")]
+fn query_gnome_shell() -> Option<(String, String, String)> {
+    const SCRIPT: &str = "(() => { const w = global.display.focus_window; \
+        return w ? { wm_class: w.get_wm_class() || '', \
+        wm_instance: w.get_wm_class_instance() || '', title: w.get_title() || '' } : null; })()";
+
+    let connection = zbus::blocking::Connection::session().ok()?;
+    let reply = connection
+        .call_method(
+            Some("org.gnome.Shell"),
+            "/org/gnome/Shell",
+            Some("org.gnome.Shell"),
+            "Eval",
+            &(SCRIPT,),
+        )
+        .ok()?;
+
+    let (success, value): (bool, String) = reply.body().deserialize().ok()?;
+    if !success {
+        log::debug!("GNOME Shell Eval rejected: {}", value);
+        return None;
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&value).ok()?;
+    let field = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let wm_class = field("wm_class");
+    if wm_class.is_empty() {
+        return None;
+    }
+    Some((wm_class, field("wm_instance"), field("title")))
+}
+
+/// Find the focused node in the sway (or other i3-ipc wlroots compositor) tree
+#[cfg(target_os = "linux")]
+fn query_sway() -> Option<(String, String, String)> {
+    let output = Command::new("swaymsg")
+        .args(["-t", "get_tree", "-r"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let tree: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let node = find_focused_sway_node(&tree)?;
+
+    // Native Wayland clients expose app_id, XWayland clients window_properties
+    let app_id = node
+        .get("app_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    let properties = node.get("window_properties");
+    let class = properties
+        .and_then(|p| p.get("class"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(app_id)
+        .to_string();
+    let instance = properties
+        .and_then(|p| p.get("instance"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(app_id)
+        .to_string();
+    let title = node
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    if class.is_empty() {
+        return None;
+    }
+    Some((class, instance, title))
+}
+
+#[cfg(target_os = "linux")]
+fn find_focused_sway_node(node: &serde_json::Value) -> Option<&serde_json::Value> {
+    if node.get("focused").and_then(|v| v.as_bool()) == Some(true) {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(*key).and_then(|v| v.as_array()))
+        .flatten()
+        .find_map(find_focused_sway_node)
+}
+
+#[cfg(target_os = "linux")]
+fn query_hyprland() -> Option<(String, String, String)> {
+    let output = Command::new("hyprctl")
+        .args(["activewindow", "-j"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let class = json.get("class").and_then(|v| v.as_str())?.to_string();
+    let initial_class = json
+        .get("initialClass")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&class)
+        .to_string();
+    let title = json
+        .get("title")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    if class.is_empty() {
+        return None;
+    }
+    Some((class, initial_class, title))
+}
+
+/// D-Bus object KWin calls back into with the active window's details
+#[cfg(target_os = "linux")]
+struct KWinReport(std::sync::mpsc::Sender<(String, String, String)>);
+
+#[cfg(target_os = "linux")]
+#[zbus::interface(name = "com.speaky.app.KWinReport")]
+impl KWinReport {
+    fn report(&self, wm_class: String, wm_instance: String, title: String) {
+        let _ = self.0.send((wm_class, wm_instance, title));
+    }
+}
+
+/// A KWin script reporting every window activation to `KWinReport`
+#[cfg(target_os = "linux")]
+struct KWinWatch {
+    connection: zbus::blocking::Connection,
+    /// Bus name of the KWin instance running the script, to notice restarts
+    kwin_owner: String,
+    reports: std::sync::mpsc::Receiver<(String, String, String)>,
+    active: Option<(String, String, String)>,
+}
+
+/// The running watch, set up by the first `query_kwin`
+#[cfg(target_os = "linux")]
+static KWIN_WATCH: Lazy<Mutex<Option<KWinWatch>>> = Lazy::new(|| Mutex::new(None));
+
+#[cfg(target_os = "linux")]
+const KWIN_SCRIPT_NAME: &str = "speaky-active-window";
+
+/// Ask KWin for the active window over D-Bus (`org.kde.KWin`).
+///
+/// KWin has no method returning the active window directly, so a script is
+/// loaded through `org.kde.kwin.Scripting` that reports
+/// `workspace.activeWindow` (Plasma 6) or `workspace.activeClient` (Plasma 5),
+/// and then every activation, back to an object we serve on the session bus.
+/// It is loaded once and stays loaded, so later queries only check that KWin
+/// has not restarted and read the latest report.
+#[cfg(target_os = "linux")]
+fn query_kwin() -> Option<(String, String, String)> {
+    let mut watch = KWIN_WATCH.lock().ok()?;
+    let current = watch
+        .as_ref()
+        .is_some_and(|w| kwin_owner(&w.connection).as_ref() == Some(&w.kwin_owner));
+    if !current {
+        *watch = watch_kwin();
+    }
+
+    let watch = watch.as_mut()?;
+    while let Ok(report) = watch.reports.try_recv() {
+        watch.active = Some(report);
+    }
+    let (wm_class, wm_instance, title) = watch.active.clone()?;
+    if wm_class.is_empty() {
+        return None;
+    }
+    Some((wm_class, wm_instance, title))
+}
+
+/// Load and run the reporting script, waiting for its first report
+#[cfg(target_os = "linux")]
+fn watch_kwin() -> Option<KWinWatch> {
+    const REPORT_PATH: &str = "/com/speaky/app/KWinReport";
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+    let (tx, reports) = std::sync::mpsc::channel();
+    let connection = zbus::blocking::connection::Builder::session()
+        .ok()?
+        .serve_at(REPORT_PATH, KWinReport(tx))
+        .ok()?
+        .build()
+        .ok()?;
+    let kwin_owner = kwin_owner(&connection)?;
+    let unique_name = connection.unique_name()?.to_string();
+
+    let script = format!(
+        "function report(w) {{ \
+             callDBus('{}', '{}', 'com.speaky.app.KWinReport', 'Report', \
+             w ? String(w.resourceClass) : '', w ? String(w.resourceName) : '', \
+             w ? String(w.caption) : ''); \
+         }} \
+         report(workspace.activeWindow || workspace.activeClient); \
+         (workspace.windowActivated || workspace.clientActivated).connect(report);",
+        unique_name, REPORT_PATH
+    );
+    let script_path = write_private_file("js", &script)?;
+
+    // A script left loaded by an earlier run blocks loadScript
+    let _ = unload_kwin_script(&connection, KWIN_SCRIPT_NAME);
+    let loaded: Option<i32> = connection
+        .call_method(
+            Some("org.kde.KWin"),
+            "/Scripting",
+            Some("org.kde.kwin.Scripting"),
+            "loadScript",
+            &(script_path.to_string_lossy().as_ref(), KWIN_SCRIPT_NAME),
+        )
+        .ok()
+        .and_then(|reply| reply.body().deserialize().ok());
+    let script_id = match loaded {
+        Some(id) if id >= 0 => id,
+        _ => {
+            log::debug!("KWin refused to load the active window script");
+            let _ = fs::remove_file(&script_path);
+            return None;
+        }
+    };
+
+    // Plasma 6 moved script objects under /Scripting
+    let run = |path: String| {
+        connection.call_method(
+            Some("org.kde.KWin"),
+            path.as_str(),
+            Some("org.kde.kwin.Script"),
+            "run",
+            &(),
+        )
+    };
+    let ran =
+        run(format!("/Scripting/Script{}", script_id)).or_else(|_| run(format!("/{}", script_id)));
+    // KWin may read the file after run returns, so it is kept until the first report
+    let active = match ran {
+        Ok(_) => reports.recv_timeout(TIMEOUT).ok(),
+        Err(e) => {
+            log::debug!("Failed to run KWin script: {}", e);
+            None
+        }
+    };
+    let _ = fs::remove_file(&script_path);
+
+    if active.is_none() {
+        let _ = unload_kwin_script(&connection, KWIN_SCRIPT_NAME);
+        return None;
+    }
+    Some(KWinWatch {
+        connection,
+        kwin_owner,
+        reports,
+        active,
+    })
+}
+
+/// Unique bus name currently owning `org.kde.KWin`
+#[cfg(target_os = "linux")]
+fn kwin_owner(connection: &zbus::blocking::Connection) -> Option<String> {
+    connection
+        .call_method(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            Some("org.freedesktop.DBus"),
+            "GetNameOwner",
+            &("org.kde.KWin",),
+        )
+        .ok()?
+        .body()
+        .deserialize()
+        .ok()
+}
+
+/// Create a file only this user can read, with an unpredictable name in the
+/// per-user runtime directory, falling back to the temp directory
+#[cfg(target_os = "linux")]
+fn write_private_file(extension: &str, contents: &str) -> Option<PathBuf> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let dir = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(format!("speaky-{}.{}", uuid::Uuid::new_v4(), extension));
+    // create_new refuses to follow a symlink planted at the path
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)
+        .ok()?;
+    if file.write_all(contents.as_bytes()).is_err() {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    Some(path)
+}
+
+#[cfg(target_os = "linux")]
+fn unload_kwin_script(
+    connection: &zbus::blocking::Connection,
+    plugin_name: &str,
+) -> zbus::Result<()> {
+    connection.call_method(
+        Some("org.kde.KWin"),
+        "/Scripting",
+        Some("org.kde.kwin.Scripting"),
+        "unloadScript",
+        &(plugin_name,),
+    )?;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn get_x11_window_info() -> Option<WindowInfo> {
     // Get active window ID
     let output = Command::new("xprop")
         .args(["-root", "_NET_ACTIVE_WINDOW"])
@@ -86,6 +900,12 @@ fn get_linux_window_info() -> Option<WindowInfo> {
         String::new()
     };
 
+    Some(resolve_window_info(wm_class, wm_instance, window_name))
+}
+
+/// Resolve display name and icon for a window from its class/instance
+#[cfg(target_os = "linux")]
+fn resolve_window_info(wm_class: String, wm_instance: String, window_name: String) -> WindowInfo {
     // Find app name and icon
     let app_name = if !wm_class.is_empty() {
         wm_class.clone()
@@ -105,13 +925,14 @@ fn get_linux_window_info() -> Option<WindowInfo> {
     // Find icon
     let icon_path = find_icon_for_wm_class(&wm_class, &wm_instance);
 
-    Some(WindowInfo {
+    WindowInfo {
         wm_class,
         wm_instance,
         window_name,
         app_name: final_app_name,
         icon_path,
-    })
+        icon_data: None,
+    }
 }
 
 fn extract_hex_id(text: &str) -> Option<String> {
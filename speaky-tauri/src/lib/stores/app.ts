@@ -4,6 +4,7 @@ import { writable, derived } from "svelte/store";
 export type RecordingState =
   | "idle"
   | "recording"
+  | "paused"
   | "recognizing"
   | "done"
   | "error";
@@ -16,6 +17,7 @@ export interface AppState {
   errorMessage: string;
   appName: string;
   appIcon: string | null;
+  detectedLanguage: string | null;
 }
 
 const initialState: AppState = {
@@ -26,6 +28,7 @@ const initialState: AppState = {
   errorMessage: "",
   appName: "",
   appIcon: null,
+  detectedLanguage: null,
 };
 
 function createAppStore() {
@@ -41,6 +44,19 @@ function createAppStore() {
         partialResult: "",
         finalResult: "",
         errorMessage: "",
+        detectedLanguage: null,
+      })),
+
+    setDetectedLanguage: (language: string) =>
+      update((state) => ({
+        ...state,
+        detectedLanguage: language,
+      })),
+
+    setPaused: (paused: boolean) =>
+      update((state) => ({
+        ...state,
+        recordingState: paused ? "paused" : "recording",
       })),
 
     setRecognizing: () =>
@@ -95,6 +111,15 @@ function createAppStore() {
 
 export const appState = createAppStore();
 
+export interface StartupIssue {
+  severity: "warning" | "error";
+  code: string;
+  message: string;
+}
+
+// Problems found at startup; kept apart from appState so reset() leaves them
+export const startupIssues = writable<StartupIssue[]>([]);
+
 // Helper function for direct state setting
 export function setRecordingState(state: RecordingState) {
   appState.setRecordingState(state);
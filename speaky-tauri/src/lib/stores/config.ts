@@ -1,68 +1,218 @@
 import { writable } from "svelte/store";
 import { invoke } from "@tauri-apps/api/core";
 
+// Serialized like the Rust enum: unit variants as strings, others as single-key objects
+export type TextTransform =
+  | "lowercase"
+  | "uppercase"
+  | "title_case"
+  | "trim"
+  | "collapse_whitespace"
+  | { regex_replace: { pattern: string; replacement: string } };
+
+export type TriggerMode =
+  | { hold: { hold_time_secs: number } }
+  | { double_tap: { max_interval_ms: number } }
+  | "push_to_talk";
+
+export type OutputMode =
+  | "paste_immediately"
+  | "copy_only"
+  | { paste_after_delay: { delay_ms: number } };
+
+export interface TtsConfig {
+  engine: "system_tts" | "eleven_labs" | "coqui";
+  voice: string;
+  api_key: string;
+  server_url: string;
+}
+
+export type PasteStrategy = "clipboard_ctrl_v" | "clipboard_cmd_v" | "xdotool_type" | "direct_input";
+
+export interface AppPasteOverride {
+  wm_class_pattern: string;
+  strategy: PasteStrategy;
+  delay_ms: number;
+}
+
 export interface Config {
+  version: number;
   core: {
     asr: {
       hotkey: string;
-      hotkey_hold_time: number;
+      trigger_mode: TriggerMode;
       language: string;
+      multi_language: string[] | null;
       streaming_mode: boolean;
       audio_device: number | null;
+      extra_audio_devices: number[];
       audio_gain: number;
       sound_notification: boolean;
+      noise_gate_threshold: number;
+      audio_buffer_size: number | null;
+      idle_release_delay_ms: number;
+      pre_recording_delay_ms: number;
+      paste_delay_ms: number;
+      echo_cancellation: boolean;
+      vad_mode: boolean;
+      vad_silence_ms: number;
+      audio_compression: "none" | "opus" | "mp3";
+      paste_mode: "replace" | "insert_at_cursor";
+      output_mode: OutputMode;
+      force_clipboard_for_unicode: boolean;
+      preview_mode: boolean;
+      itn_enabled: boolean;
+      punctuation_enabled: boolean;
     };
+    extra_hotkeys: {
+      hotkey: string;
+      language: string;
+      label: string;
+    }[];
+    transforms: TextTransform[];
+    auto_export: {
+      directory: string;
+      filename_pattern: string;
+      format: "plain_text" | "markdown" | "csv";
+    } | null;
+    profanity_filter: {
+      wordlist: string[];
+      replacement: string;
+    } | null;
+    post_process_command: string | null;
   };
   engine: {
     current: string;
     volc_bigmodel: {
       app_key: string;
       access_key: string;
+      volc_segment_duration_ms: number;
+      volc_model: "bigmodel" | "bigmodel_flash" | "bigmodel_pro";
+      volc_ws_url: string | null;
+      speaker_labels: boolean;
+      show_alternatives: boolean;
     };
     openai: {
       api_key: string;
       model: string;
       base_url: string;
+      include_word_timestamps: boolean;
     };
+    proxy: {
+      url: string;
+      username: string | null;
+      password: string | null;
+    } | null;
+    min_interval_between_calls_ms: number;
+    // Only honoured by builds with the `dry-run` feature
+    dry_run: boolean;
+    fake_latency_ms: number;
   };
   appearance: {
     theme: string;
     ui_language: string;
     show_waveform: boolean;
     window_opacity: number;
+    notification_sounds: Record<string, string> | null;
+    run_in_background: boolean;
+    tts: TtsConfig | null;
+    // PNG paths per state; empty strings use the built-in icons
+    tray_icon_set: {
+      idle: string;
+      recording: string;
+      processing: string;
+    };
   };
+  lan_server: {
+    bind_addr: string;
+    port: number;
+    auth_token: string;
+  } | null;
+  ws_server: {
+    bind_addr: string;
+    port: number;
+  } | null;
+  watch_directory: {
+    path: string;
+    pattern: string;
+    output_dir: string;
+  } | null;
 }
 
 const defaultConfig: Config = {
+  version: 3,
   core: {
     asr: {
       hotkey: "ctrl",
-      hotkey_hold_time: 1.0,
+      trigger_mode: { hold: { hold_time_secs: 1.0 } },
       language: "zh",
+      multi_language: null,
       streaming_mode: true,
       audio_device: null,
+      extra_audio_devices: [],
       audio_gain: 1.0,
       sound_notification: true,
+      noise_gate_threshold: 0.01,
+      audio_buffer_size: null,
+      idle_release_delay_ms: 30000,
+      pre_recording_delay_ms: 50,
+      paste_delay_ms: 50,
+      echo_cancellation: false,
+      vad_mode: false,
+      vad_silence_ms: 1500,
+      audio_compression: "none",
+      paste_mode: "replace",
+      output_mode: "paste_immediately",
+      force_clipboard_for_unicode: true,
+      preview_mode: false,
+      itn_enabled: true,
+      punctuation_enabled: true,
     },
+    extra_hotkeys: [],
+    transforms: [],
+    auto_export: null,
+    profanity_filter: null,
+    post_process_command: null,
   },
   engine: {
     current: "volc_bigmodel",
     volc_bigmodel: {
       app_key: "",
       access_key: "",
+      volc_segment_duration_ms: 200,
+      volc_model: "bigmodel",
+      volc_ws_url: null,
+      speaker_labels: false,
+      show_alternatives: false,
     },
     openai: {
       api_key: "",
       model: "gpt-4o-transcribe",
       base_url: "https://api.openai.com/v1",
+      include_word_timestamps: false,
     },
+    proxy: null,
+    min_interval_between_calls_ms: 500,
+    dry_run: false,
+    fake_latency_ms: 500,
   },
   appearance: {
     theme: "auto",
     ui_language: "auto",
     show_waveform: true,
     window_opacity: 0.9,
+    notification_sounds: null,
+    run_in_background: false,
+    tts: null,
+    tray_icon_set: {
+      idle: "",
+      recording: "",
+      processing: "",
+    },
   },
+  lan_server: null,
+  ws_server: null,
+  watch_directory: null,
 };
 
 function createConfigStore() {
@@ -11,6 +11,7 @@ export type Locale = typeof locales[number];
 const en: Translations = {
   listening: "Listening...",
   recognizing: "Recognizing...",
+  paused: "Paused",
   done: "Done!",
   error: "Error",
   no_engine: "No recognition engine configured",
@@ -51,6 +52,7 @@ const en: Translations = {
 const zh: Translations = {
   listening: "正在录音...",
   recognizing: "识别中...",
+  paused: "已暂停",
   done: "识别完成",
   error: "识别失败",
   no_engine: "未配置识别引擎",
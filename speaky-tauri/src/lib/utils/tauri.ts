@@ -1,18 +1,90 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import { appState } from "../stores/app";
+import { appState, startupIssues, type StartupIssue } from "../stores/app";
+import type { AppPasteOverride, Config, TriggerMode } from "../stores/config";
 
 // Event types from Rust backend
 export interface AudioLevelEvent {
   level: number;
+  rms: number;
+  peak: number;
 }
 
+export interface AudioLevels {
+  rms: number;
+  peak: number;
+}
+
+export interface UploadProgressEvent {
+  sent_bytes: number;
+  total_bytes: number;
+}
+
+// Timestamps are Unix epoch milliseconds
 export interface PartialResultEvent {
   text: string;
+  emitted_at_ms: number;
 }
 
 export interface FinalResultEvent {
   text: string;
+  emitted_at_ms: number;
+  // When transcribing the clipboard, when the command was invoked;
+  // absent if the start of the recording is unknown
+  recording_started_at_ms?: number;
+  // Words are split on whitespace, so CJK text counts as few words
+  word_count: number;
+  char_count: number;
+  // From recording start to recognition complete, absent along with recording_started_at_ms
+  duration_secs?: number;
+}
+
+export interface WordTimestamp {
+  word: string;
+  start: number;
+  end: number;
+}
+
+export interface Utterance {
+  speaker: string;
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  language: string | null;
+}
+
+// One per segment when `multi_language` is set
+export interface DetectedLanguageEvent {
+  language: string;
+  start_ms: number;
+  end_ms: number;
+}
+
+// Sent after final-result when the engine reports word timestamps or speaker labels
+export interface VerboseResultEvent {
+  text: string;
+  words: WordTimestamp[];
+  utterances: Utterance[];
+  alternatives: string[];
+}
+
+// Engine transcripts, best first, when `show_alternatives` is on
+export interface NbestResultEvent {
+  alternatives: string[];
+}
+
+// Sent instead of pasting when preview mode is on; paste with confirmPaste(id)
+export interface PreviewResultEvent {
+  id: string;
+  text: string;
+}
+
+// Failure in a background task, e.g. pasting or the keyboard listener
+export interface AppErrorEvent {
+  source: string;
+  message: string;
+  recoverable: boolean;
+  timestamp: number;
 }
 
 export interface ErrorEvent {
@@ -20,7 +92,38 @@ export interface ErrorEvent {
 }
 
 export interface RecordingStateEvent {
-  state: "started" | "stopped" | "recognizing";
+  state: "started" | "stopped" | "recognizing" | "paused" | "resumed";
+}
+
+// A recording was not started because the previous one started too recently
+export interface RateLimitedEvent {
+  retry_after_ms: number;
+}
+
+export interface AudioClipEvent {
+  clipped_fraction: number;
+  chunk_index: number;
+}
+
+// Only non-modifier hotkeys work until the permission is granted (macOS Accessibility)
+export interface PermissionRequiredEvent {
+  permission: "accessibility";
+  guide_url: string;
+}
+
+// The OS denied microphone access; sent when a recording is attempted
+export interface PermissionErrorEvent {
+  message: string;
+}
+
+export interface LanguageDetectedEvent {
+  language: string;
+}
+
+// A file in watch_directory was transcribed to `output`
+export interface WatchTranscribedEvent {
+  input: string;
+  output: string;
 }
 
 export interface AppInfoEvent {
@@ -37,12 +140,282 @@ export async function stopRecording(): Promise<string> {
   return invoke("stop_recording");
 }
 
+// WAV of the audio captured since the last flush; empty if there is none
+export async function flushAudioBuffer(): Promise<number[]> {
+  return invoke("flush_audio_buffer");
+}
+
+export async function pauseRecording(): Promise<void> {
+  return invoke("pause_recording");
+}
+
+export async function resumeRecording(): Promise<void> {
+  return invoke("resume_recording");
+}
+
+// Also emits final-result with the text
+export async function transcribeClipboardAudio(): Promise<string> {
+  return invoke("transcribe_clipboard_audio");
+}
+
 export async function getAudioDevices(): Promise<Array<{ index: number; name: string }>> {
   return invoke("get_audio_devices");
 }
 
-export async function setHotkey(hotkey: string, holdTime: number): Promise<void> {
-  return invoke("set_hotkey", { hotkey, holdTime });
+export interface AudioDeviceInfo {
+  name: string;
+  supported_sample_rates: number[];
+  max_channels: number;
+  default_sample_rate: number;
+}
+
+export async function getAudioDeviceInfo(index: number): Promise<AudioDeviceInfo> {
+  return invoke("get_audio_device_info", { index });
+}
+
+export interface DeviceBenchmark {
+  index: number;
+  name: string;
+  open_latency_ms: number;
+  first_frame_latency_ms: number;
+}
+
+export async function benchmarkDevices(): Promise<DeviceBenchmark[]> {
+  return invoke("benchmark_devices");
+}
+
+export interface EngineCapabilities {
+  name: string;
+  supports_streaming: boolean;
+  supported_languages: string[];
+  supports_language_detection: boolean;
+  max_audio_duration_secs: number | null;
+  supported_models: string[];
+}
+
+// Resolves with null when no engine is configured
+export async function getEngineCapabilities(): Promise<EngineCapabilities | null> {
+  return invoke("get_engine_capabilities");
+}
+
+// Resolves with "<engine> responded in <n> ms", rejects with the engine error
+export async function testEngine(): Promise<string> {
+  return invoke("test_engine");
+}
+
+// Resolves with the round-trip time in milliseconds
+export async function checkEngineConnectivity(engineName: string): Promise<number> {
+  return invoke("check_engine_connectivity", { engineName });
+}
+
+// Runs the saved post_process_command on `text`
+export async function testPostProcessCommand(text: string): Promise<string> {
+  return invoke("test_post_process_command", { text });
+}
+
+export async function previewTts(text: string): Promise<void> {
+  return invoke("preview_tts", { text });
+}
+
+export async function stopTts(): Promise<void> {
+  return invoke("stop_tts");
+}
+
+export async function getLevels(): Promise<AudioLevels> {
+  return invoke("get_levels");
+}
+
+export async function getWaveformSnapshot(nPoints: number): Promise<number[]> {
+  return invoke("get_waveform_snapshot", { nPoints });
+}
+
+export async function getLevelHistory(): Promise<number[]> {
+  return invoke("get_level_history");
+}
+
+// Seconds since recording started, or null when not recording
+export async function getRecordingDuration(): Promise<number | null> {
+  return invoke("get_recording_duration");
+}
+
+export interface RecorderStats {
+  chunks: number;
+  samples: number;
+  clipped_samples: number;
+  clip_events: number;
+}
+
+export async function getRecorderStats(): Promise<RecorderStats> {
+  return invoke("get_recorder_stats");
+}
+
+export interface HotkeyInfo {
+  key: string;
+  display_name: string;
+  category: "Modifier" | "FunctionKey" | "Special";
+}
+
+export async function checkAccessibilityPermission(): Promise<boolean> {
+  return invoke("check_accessibility_permission");
+}
+
+// Opens the macOS prompt; resolves with whether access was already granted
+export async function requestAccessibilityPermission(): Promise<boolean> {
+  return invoke("request_accessibility_permission");
+}
+
+// Same permissions as the permission-required events, for windows opened after they fired
+export async function getRequiredPermissions(): Promise<PermissionRequiredEvent[]> {
+  return invoke("get_required_permissions");
+}
+
+export async function getSupportedHotkeys(): Promise<HotkeyInfo[]> {
+  return invoke("get_supported_hotkeys");
+}
+
+export async function setHotkey(hotkey: string, triggerMode: TriggerMode): Promise<void> {
+  return invoke("set_hotkey", { hotkey, triggerMode });
+}
+
+export async function resetConfig(): Promise<Config> {
+  return invoke("reset_config");
+}
+
+// Omit the path to let the user pick one in a native file dialog
+export async function exportConfig(path?: string): Promise<void> {
+  return invoke("export_config", { path: path ?? null });
+}
+
+export async function importConfig(path?: string): Promise<Config> {
+  return invoke("import_config", { path: path ?? null });
+}
+
+export async function saveProfile(name: string): Promise<void> {
+  return invoke("save_profile", { name });
+}
+
+export async function loadProfile(name: string): Promise<Config> {
+  return invoke("load_profile", { name });
+}
+
+export async function listProfiles(): Promise<string[]> {
+  return invoke("list_profiles");
+}
+
+export async function deleteProfile(name: string): Promise<void> {
+  return invoke("delete_profile", { name });
+}
+
+export async function getPasteOverrides(): Promise<AppPasteOverride[]> {
+  return invoke("get_paste_overrides");
+}
+
+export async function savePasteOverrides(overrides: AppPasteOverride[]): Promise<void> {
+  return invoke("save_paste_overrides", { overrides });
+}
+
+export async function getProfanityList(): Promise<string[]> {
+  return invoke("get_profanity_list");
+}
+
+export async function setProfanityList(words: string[]): Promise<void> {
+  return invoke("set_profanity_list", { words });
+}
+
+export interface WindowInfo {
+  wm_class: string;
+  wm_instance: string;
+  window_name: string;
+  app_name: string;
+  icon_path: string | null;
+  icon_data: string | null;
+}
+
+export interface AppUsageRecord {
+  wm_class: string;
+  app_name: string;
+  use_count: number;
+  last_used: number;
+}
+
+export async function getRecentApps(limit: number): Promise<AppUsageRecord[]> {
+  return invoke("get_recent_apps", { limit });
+}
+
+export async function clearRecentApps(): Promise<void> {
+  return invoke("clear_recent_apps");
+}
+
+// Same issues as the startup-status event, for windows opened after it fired
+export async function getStartupIssues(): Promise<StartupIssue[]> {
+  return invoke("get_startup_issues");
+}
+
+export async function getLogPath(): Promise<string> {
+  return invoke("get_log_path");
+}
+
+export interface SystemInfo {
+  os: string;
+  os_version: string;
+  arch: string;
+  cpal_host: string;
+  default_device_name: string | null;
+  speaky_version: string;
+}
+
+export async function getSystemInfo(): Promise<SystemInfo> {
+  return invoke("get_system_info");
+}
+
+export async function getRecentLogs(lines: number): Promise<string[]> {
+  return invoke("get_recent_logs", { lines });
+}
+
+export async function startLanServer(): Promise<void> {
+  return invoke("start_lan_server");
+}
+
+export async function stopLanServer(): Promise<void> {
+  return invoke("stop_lan_server");
+}
+
+// Uses lan_server.auth_token for authentication
+export async function startWsServer(): Promise<void> {
+  return invoke("start_ws_server");
+}
+
+export async function stopWsServer(): Promise<void> {
+  return invoke("stop_ws_server");
+}
+
+// Uses the saved watch_directory settings
+export async function startWatch(): Promise<void> {
+  return invoke("start_watch");
+}
+
+export async function stopWatch(): Promise<void> {
+  return invoke("stop_watch");
+}
+
+export async function getFocusedWindow(): Promise<WindowInfo | null> {
+  return invoke("get_focused_window");
+}
+
+export async function clearIconCache(): Promise<void> {
+  return invoke("clear_icon_cache");
+}
+
+export async function resetTrayIcons(): Promise<void> {
+  return invoke("reset_tray_icons");
+}
+
+export async function showTrayIcon(): Promise<void> {
+  return invoke("show_tray_icon");
+}
+
+export async function hideTrayIcon(): Promise<void> {
+  return invoke("hide_tray_icon");
 }
 
 export async function showWindow(): Promise<void> {
@@ -57,6 +430,10 @@ export async function pasteText(text: string): Promise<void> {
   return invoke("paste_text", { text });
 }
 
+export async function confirmPaste(id: string): Promise<void> {
+  return invoke("confirm_paste", { id });
+}
+
 // Event listeners
 let unlistenFns: UnlistenFn[] = [];
 
@@ -102,6 +479,12 @@ export async function setupEventListeners(): Promise<void> {
         case "recognizing":
           appState.setRecognizing();
           break;
+        case "paused":
+          appState.setPaused(true);
+          break;
+        case "resumed":
+          appState.setPaused(false);
+          break;
         case "stopped":
           // State will be updated by final-result or error event
           break;
@@ -109,6 +492,20 @@ export async function setupEventListeners(): Promise<void> {
     })
   );
 
+  // Language found when recognizing with language "auto"
+  unlistenFns.push(
+    await listen<LanguageDetectedEvent>("language-detected", (event) => {
+      appState.setDetectedLanguage(event.payload.language);
+    })
+  );
+
+  // Problems found by the startup checks
+  unlistenFns.push(
+    await listen<StartupIssue[]>("startup-status", (event) => {
+      startupIssues.set(event.payload);
+    })
+  );
+
   // App info updates
   unlistenFns.push(
     await listen<AppInfoEvent>("app-info", (event) => {